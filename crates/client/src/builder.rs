@@ -1,10 +1,21 @@
-use std::{collections::HashSet, sync::Arc, time::Duration};
+use std::time::Duration;
 
 use portal_common::{timeout, IpVersion, TimeoutResult};
-use portal_proto::{constants::MIN_MESSAGE_SIZE, transfer::Protocol};
+use portal_proto::{
+    constants::MIN_MESSAGE_SIZE,
+    transfer::{Multiplexer, MultiplexerBuilder, Protocol, TcpDnsTransport, UdpDnsTransport},
+};
 use tokio::net::UdpSocket;
 
-use crate::{error::ClientError, Client};
+use crate::{error::ClientError, spawn_multiplexer, ActiveIds, Client};
+
+/// The number of queries buffered by the UDP transport before they have to
+/// be sent.
+const UDP_TRANSPORT_BUFFER_SIZE: usize = 64;
+
+/// The number of queries buffered by the TCP transport before they have to
+/// be sent. This limit is shared by the connections to all targets.
+const TCP_TRANSPORT_BUFFER_SIZE: usize = 64;
 
 pub struct ClientBuilder {
     ip_version: IpVersion,
@@ -13,6 +24,8 @@ pub struct ClientBuilder {
     protocol: Protocol,
     bind_timeout: u64,
     read_timeout: u64,
+    tcp_fallback: bool,
+    dnssec_ok: bool,
 }
 
 impl Default for ClientBuilder {
@@ -24,6 +37,8 @@ impl Default for ClientBuilder {
             write_timeout: 2,
             bind_timeout: 2,
             read_timeout: 2,
+            tcp_fallback: true,
+            dnssec_ok: false,
         }
     }
 }
@@ -36,24 +51,48 @@ impl ClientBuilder {
             IpVersion::V4 => "0.0.0.0:0",
         };
 
-        let stream = match self.protocol {
+        // TCP is always available, either as the only protocol or as the
+        // fallback for truncated UDP responses. Connections are opened on
+        // demand.
+        let tcp = spawn_multiplexer(
+            self.multiplexer()
+                .build(TcpDnsTransport::new(TCP_TRANSPORT_BUFFER_SIZE)),
+        );
+
+        let udp = match self.protocol {
             Protocol::Udp => match timeout(bind_timeout, UdpSocket::bind(bind_address)).await {
                 TimeoutResult::Timeout => return Err(ClientError::WriteTimeout(bind_timeout)),
                 TimeoutResult::Error(err) => return Err(ClientError::IO(err)),
-                TimeoutResult::Ok(socket) => socket,
+                TimeoutResult::Ok(socket) => {
+                    let mut transport = UdpDnsTransport::new(socket, UDP_TRANSPORT_BUFFER_SIZE);
+                    transport.with_recv_buffer_size(self.buffer_size);
+
+                    Some(spawn_multiplexer(self.multiplexer().build(transport)))
+                }
             },
-            Protocol::Tcp => todo!(),
+            Protocol::Tcp => None,
         };
 
         Ok(Client {
-            active_ids: Arc::new(HashSet::new()),
-            write_timeout: self.write_timeout,
+            active_ids: ActiveIds::default(),
             read_timeout: self.read_timeout,
             buffer_size: self.buffer_size,
-            socket: Arc::new(stream),
+            tcp_fallback: self.tcp_fallback,
+            dnssec_ok: self.dnssec_ok,
+            udp,
+            tcp,
         })
     }
 
+    /// Returns a [`MultiplexerBuilder`] with the configured timeouts.
+    fn multiplexer(&self) -> MultiplexerBuilder {
+        let mut builder = Multiplexer::builder();
+        builder
+            .with_write_timeout(Duration::from_secs(self.write_timeout))
+            .with_read_timeout(Duration::from_secs(self.read_timeout));
+        builder
+    }
+
     /// Customize the socket bind timeout.
     pub fn with_bind_timeout(&mut self, bind_timeout: u64) -> &mut Self {
         self.bind_timeout = bind_timeout;
@@ -103,4 +142,21 @@ impl ClientBuilder {
         self.protocol = protocol.into();
         self
     }
+
+    /// Customize if truncated UDP responses (TC bit set) are retried via
+    /// TCP. This is enabled by default. When disabled, the truncated
+    /// response is returned as is.
+    pub fn with_tcp_fallback(&mut self, tcp_fallback: bool) -> &mut Self {
+        self.tcp_fallback = tcp_fallback;
+        self
+    }
+
+    /// Customize if queries set the DNSSEC OK (DO) bit, which requests
+    /// RRSIG, NSEC and NSEC3 records in responses. This is disabled by
+    /// default. When enabled, queries include an OPT record which advertises
+    /// the buffer size. See [RFC 3225](https://datatracker.ietf.org/doc/html/rfc3225).
+    pub fn with_dnssec_ok(&mut self, dnssec_ok: bool) -> &mut Self {
+        self.dnssec_ok = dnssec_ok;
+        self
+    }
 }
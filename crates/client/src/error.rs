@@ -1,6 +1,6 @@
 use std::time;
 
-use portal_proto::{HeaderError, MessageError};
+use portal_proto::{transfer::MultiplexResponseError, HeaderError, MessageError};
 use thiserror::Error;
 use tokio::task::JoinError;
 
@@ -29,4 +29,16 @@ pub enum ClientError {
 
     #[error("Runtime error: {0}")]
     RuntimeError(#[from] JoinError),
+
+    #[error("Multiplexer error: {0}")]
+    MultiplexError(#[from] MultiplexResponseError),
+
+    #[error("No target addresses to query")]
+    NoTargets,
+
+    #[error("Message too long for TCP ({0} octets)")]
+    MessageTooLong(usize),
+
+    #[error("All transaction IDs are in use")]
+    NoFreeId,
 }
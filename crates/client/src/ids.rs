@@ -0,0 +1,66 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+/// The number of random transaction IDs tried before searching for a free
+/// one, which is only required if most IDs are in use.
+const MAX_RANDOM_ATTEMPTS: usize = 16;
+
+/// [`ActiveIds`] keeps track of the transaction IDs of all active queries.
+/// New queries acquire a random unused ID, which is released again once the
+/// returned [`ActiveId`] is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct ActiveIds {
+    ids: Arc<Mutex<HashSet<u16>>>,
+}
+
+impl ActiveIds {
+    /// Acquires a random transaction ID which is currently not used by any
+    /// other query. Returns `None` if all IDs are in use.
+    pub fn acquire(&self) -> Option<ActiveId> {
+        let mut ids = self.ids.lock().unwrap();
+        let start = rand::random::<u16>();
+
+        // Reroll a few times before searching the next free ID, so the
+        // mutex is never held for long
+        let id = (0..MAX_RANDOM_ATTEMPTS)
+            .map(|_| rand::random::<u16>())
+            .chain((0..=u16::MAX).map(|offset| start.wrapping_add(offset)))
+            .find(|id| ids.insert(*id))?;
+
+        Some(ActiveId {
+            ids: self.ids.clone(),
+            id,
+        })
+    }
+
+    /// Returns the number of active transaction IDs.
+    pub fn len(&self) -> usize {
+        self.ids.lock().unwrap().len()
+    }
+
+    /// Returns if there are no active transaction IDs.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// An acquired transaction ID. The ID is released when this is dropped.
+#[derive(Debug)]
+pub struct ActiveId {
+    ids: Arc<Mutex<HashSet<u16>>>,
+    id: u16,
+}
+
+impl ActiveId {
+    pub fn get(&self) -> u16 {
+        self.id
+    }
+}
+
+impl Drop for ActiveId {
+    fn drop(&mut self) {
+        self.ids.lock().unwrap().remove(&self.id);
+    }
+}
@@ -1,39 +1,51 @@
 use std::{
-    collections::{HashSet, VecDeque},
     net::SocketAddr,
-    sync::Arc,
     time::{Duration, Instant},
 };
 
-use binbuf::prelude::*;
-use futures::StreamExt;
-use tokio::{net::UdpSocket, task::JoinSet};
-
-use portal_common::{timeout, TimeoutResult};
+use futures::future::select_ok;
 use portal_proto::{
-    transfer::{Background, Multiplexer, Transport},
+    transfer::{Background, Handler, MultiplexResponseError, Multiplexer, Protocol, Transport},
     Header, Message, Query, Question, ToQuery,
 };
 
 mod builder;
 mod error;
+mod ids;
 
 pub use builder::*;
 pub use error::*;
+pub use ids::*;
+
+/// The number of queries which can be submitted to the background of a
+/// [`MuliplexedClient`] before the submitting tasks have to wait.
+const MULTIPLEXER_BUFFER_SIZE: usize = 1024;
 
 pub type ClientResult<T> = Result<T, ClientError>;
 
+/// Queries are sent via multiplexers, which run in background tasks. A
+/// multiplexer receives all responses and hands them to the matching query,
+/// which allows any number of concurrent queries.
 pub struct Client {
     buffer_size: usize,
-    write_timeout: u64,
     read_timeout: u64,
 
-    active_ids: Arc<HashSet<u16>>,
-    socket: Arc<UdpSocket>,
-}
+    /// Retry truncated UDP responses via TCP
+    tcp_fallback: bool,
+
+    /// Set the DNSSEC OK (DO) bit in queries
+    dnssec_ok: bool,
 
-// TODO (Techassi): Implement a connection struct which finally binds to a socket.
-// This connection then provides methods like query().
+    active_ids: ActiveIds,
+
+    /// Sends queries via one shared UDP socket. This is `None` if the client
+    /// only uses TCP.
+    udp: Option<Handler>,
+
+    /// Sends queries via TCP. Connections are reused for all queries to the
+    /// same target and queries are pipelined.
+    tcp: Handler,
+}
 
 impl Client {
     /// Tries to create a new DNS [`Client`] with default settings. This client
@@ -59,8 +71,6 @@ impl Client {
         ClientBuilder::default()
     }
 
-    // TODO (Techassi): Add query_multi and query_multi_duration methods
-
     /// Sends a query to `addr` asking for `name`, `ty` and `class`.
     ///
     /// ### Example
@@ -78,100 +88,40 @@ impl Client {
         &self,
         query: Q,
         target_addr: SocketAddr,
-    ) -> ClientResult<(Message, usize)>
+    ) -> ClientResult<(Message, usize, Protocol)>
     where
         Q: ToQuery,
     {
-        // TODO (Techassi): Most of this stuff will be replaced by the DNS multiplexer
-        let query = query.to_query();
-
-        let active_ids = self.active_ids.clone();
-        let query = query.clone();
-
-        // NOTE (Techassi): Can we avoid cloning here?
-        let write_timeout = self.write_timeout.clone();
-        let read_timeout = self.read_timeout.clone();
-        let buffer_size = self.buffer_size.clone();
-        let socket = self.socket.clone();
-
-        // TODO (Techassi): Make this behaviour configurable via a CLI argument. Always sending multiple queries
-        // in parallel will result in a huge increase of network traffic. The normal behaviour should query servers
-        // one after another if the client doesn't receive a message during the timeout duration.
-        let handle = tokio::spawn(async move {
-            do_query(
-                query,
-                socket,
-                target_addr,
-                active_ids,
-                write_timeout,
-                read_timeout,
-                buffer_size,
-            )
-            .await
-        });
-
-        match handle.await {
-            Ok(result) => match result {
-                Ok((msg, len, _)) => Ok((msg, len)),
-                Err(err) => Err(err),
-            },
-            Err(err) => Err(ClientError::RuntimeError(err)),
-        }
+        let (message, len, _, protocol) = self.do_query(query.to_query(), target_addr).await?;
+        Ok((message, len, protocol))
     }
 
+    /// Sends the query to all `target_addrs` in parallel and returns the
+    /// first successful response. An error is only returned if all queries
+    /// failed.
     pub async fn query_multi<Q>(
         &self,
         query: Q,
         target_addrs: Vec<SocketAddr>,
-    ) -> ClientResult<(Message, usize, SocketAddr)>
+    ) -> ClientResult<(Message, usize, SocketAddr, Protocol)>
     where
         Q: ToQuery,
     {
-        // TODO (Techassi): Most of this stuff will be replaced by the DNS multiplexer
-        let mut set = JoinSet::new();
+        if target_addrs.is_empty() {
+            return Err(ClientError::NoTargets);
+        }
+
         let query = query.to_query();
 
-        for target in target_addrs {
-            let active_ids = self.active_ids.clone();
-            let query = query.clone();
-
-            // NOTE (Techassi): Can we avoid cloning here?
-            let write_timeout = self.write_timeout.clone();
-            let read_timeout = self.read_timeout.clone();
-            let buffer_size = self.buffer_size.clone();
-            let socket = self.socket.clone();
-
-            // TODO (Techassi): Make this behaviour configurable via a CLI argument. Always sending multiple queries
-            // in parallel will result in a huge increase of network traffic. The normal behaviour should query servers
-            // one after another if the client doesn't receive a message during the timeout duration.
-            set.spawn(async move {
-                do_query(
-                    query,
-                    socket,
-                    target,
-                    active_ids,
-                    write_timeout,
-                    read_timeout,
-                    buffer_size,
-                )
-                .await
-            });
-        }
+        // TODO (Techassi): Make this behaviour configurable via a CLI argument. Always sending multiple queries
+        // in parallel will result in a huge increase of network traffic. The normal behaviour should query servers
+        // one after another if the client doesn't receive a message during the timeout duration.
+        let queries = target_addrs
+            .into_iter()
+            .map(|target| Box::pin(self.do_query(query.clone(), target)));
 
-        match set.join_next().await {
-            Some(result) => {
-                drop(set);
-
-                return match result {
-                    Ok(res) => match res {
-                        Ok(msg) => Ok(msg),
-                        Err(err) => Err(err),
-                    },
-                    Err(err) => Err(ClientError::RuntimeError(err)),
-                };
-            }
-            None => todo!(),
-        }
+        let (result, _) = select_ok(queries).await?;
+        Ok(result)
     }
 
     /// Sends a query to `addr` asking for `name`, `ty` and `class`. In addition
@@ -193,189 +143,204 @@ impl Client {
         &self,
         query: Q,
         target_addr: SocketAddr,
-    ) -> ClientResult<(Message, usize, Duration)>
+    ) -> ClientResult<(Message, usize, Duration, Protocol)>
     where
         Q: ToQuery,
     {
         let now = Instant::now();
-        let (message, len) = self.query(query, target_addr).await?;
-        Ok((message, len, now.elapsed()))
+        let (message, len, protocol) = self.query(query, target_addr).await?;
+        Ok((message, len, now.elapsed(), protocol))
     }
 
     pub async fn query_duration_multi<Q>(
         &self,
         query: Q,
         target_addrs: Vec<SocketAddr>,
-    ) -> ClientResult<(Message, usize, Duration, SocketAddr)>
+    ) -> ClientResult<(Message, usize, Duration, SocketAddr, Protocol)>
     where
         Q: ToQuery,
     {
         let now = Instant::now();
+        let (message, len, target, protocol) = self.query_multi(query, target_addrs).await?;
+        Ok((message, len, now.elapsed(), target, protocol))
+    }
 
-        // TODO (Techassi): Most of this stuff will be replaced by the DNS multiplexer
-        let mut set = JoinSet::new();
-        let query = query.to_query();
+    async fn do_query(
+        &self,
+        query: Query,
+        target: SocketAddr,
+    ) -> ClientResult<(Message, usize, SocketAddr, Protocol)> {
+        // The ID is released again when the guard is dropped
+        let id = self.active_ids.acquire().ok_or(ClientError::NoFreeId)?;
+        let message = self.message(id.get(), &query);
+
+        let udp = match &self.udp {
+            Some(udp) => udp,
+            None => {
+                let (response, len) = self.send(&self.tcp, message, target).await?;
+                return Ok((response, len, target, Protocol::Tcp));
+            }
+        };
 
-        for target in target_addrs {
-            let active_ids = self.active_ids.clone();
-            let query = query.clone();
-
-            // NOTE (Techassi): Can we avoid cloning here?
-            let write_timeout = self.write_timeout.clone();
-            let read_timeout = self.read_timeout.clone();
-            let buffer_size = self.buffer_size.clone();
-            let socket = self.socket.clone();
-
-            // TODO (Techassi): Make this behaviour configurable via a CLI argument. Always sending multiple queries
-            // in parallel will result in a huge increase of network traffic. The normal behaviour should query servers
-            // one after another if the client doesn't receive a message during the timeout duration.
-            set.spawn(async move {
-                do_query(
-                    query,
-                    socket,
-                    target,
-                    active_ids,
-                    write_timeout,
-                    read_timeout,
-                    buffer_size,
-                )
-                .await
-            });
-        }
+        let (response, len) = self.send(udp, message, target).await?;
 
-        match set.join_next().await {
-            Some(result) => {
-                drop(set);
-
-                return match result {
-                    Ok(res) => match res {
-                        Ok((msg, len, target)) => Ok((msg, len, now.elapsed(), target)),
-                        Err(err) => Err(err),
-                    },
-                    Err(err) => Err(ClientError::RuntimeError(err)),
-                };
-            }
-            None => todo!(),
+        // The response didn't fit into the UDP message. Retry the same query
+        // via TCP against the same server to retrieve the full answer. See
+        // RFC 7766 Section 5.
+        if response.is_truncated() && self.tcp_fallback {
+            let message = self.message(id.get(), &query);
+            let (response, len) = self.send(&self.tcp, message, target).await?;
+            return Ok((response, len, target, Protocol::Tcp));
         }
-    }
-}
 
-async fn do_query(
-    query: Query,
-    socket: Arc<UdpSocket>,
-    target: SocketAddr,
-    active_ids: Arc<HashSet<u16>>,
-    write_timeout: u64,
-    read_timeout: u64,
-    buffer_size: usize,
-) -> ClientResult<(Message, usize, SocketAddr)> {
-    let id = get_free_transaction_id(active_ids);
+        Ok((response, len, target, Protocol::Udp))
+    }
 
-    let mut message = Message::new_with_header(Header::new(id));
-    message.add_question(Question::from(query));
+    /// Sends the `message` via `handler` and waits for the response.
+    async fn send(
+        &self,
+        handler: &Handler,
+        message: Message,
+        target: SocketAddr,
+    ) -> ClientResult<(Message, usize)> {
+        match handler.query(message, target).await {
+            Ok(response) => Ok(response),
+            Err(MultiplexResponseError::Timeout) => Err(ClientError::ReadTimeout(
+                Duration::from_secs(self.read_timeout),
+            )),
+            Err(err) => Err(err.into()),
+        }
+    }
 
-    let mut buf = WriteBuffer::new();
-    message.write::<BigEndian>(&mut buf)?;
+    /// Builds the query message with the transaction ID `id`.
+    fn message(&self, id: u16, query: &Query) -> Message {
+        let mut message = Message::new_with_header(Header::new(id));
+        message.add_question(Question::from(query.clone()));
 
-    let write_timeout = Duration::from_secs(write_timeout);
-    let read_timeout = Duration::from_secs(read_timeout);
+        // DNSSEC responses are usually larger than 512 octets, so advertise
+        // the full receive buffer size alongside the DO bit.
+        if self.dnssec_ok {
+            message.set_udp_payload_size(self.buffer_size.min(u16::MAX as usize) as u16);
+            message.set_dnssec_ok(true);
+        }
 
-    // Send DNS query to the remote DNS server
-    match timeout(write_timeout, socket.send_to(buf.bytes(), target)).await {
-        TimeoutResult::Timeout => return Err(ClientError::WriteTimeout(write_timeout)),
-        TimeoutResult::Error(err) => return Err(ClientError::IO(err)),
-        TimeoutResult::Ok(_) => {}
+        message
     }
+}
 
-    // Wait for the DNS response
-    match timeout(
-        read_timeout,
-        wait_for_query_response(socket, target, buffer_size),
-    )
-    .await
-    {
-        TimeoutResult::Timeout => Err(ClientError::ReadTimeout(read_timeout)),
-        TimeoutResult::Error(err) => Err(err),
-        TimeoutResult::Ok(res) => Ok(res),
-    }
+/// Spawns the background task which drives the `multiplexer` and returns
+/// the [`Handler`] to submit queries.
+pub(crate) fn spawn_multiplexer<T>(multiplexer: Multiplexer<T>) -> Handler
+where
+    T: Transport + Send + 'static,
+{
+    let (background, handler) = Background::new(multiplexer, MULTIPLEXER_BUFFER_SIZE);
+    tokio::spawn(background);
+
+    handler
 }
 
-async fn wait_for_query_response(
-    socket: Arc<UdpSocket>,
-    target: SocketAddr,
-    buffer_size: usize,
-) -> ClientResult<(Message, usize, SocketAddr)> {
-    loop {
-        socket.readable().await?;
-
-        let mut buf = vec![0u8; buffer_size];
-        let (len, addr) = match socket.recv_from(&mut buf).await {
-            Ok(result) => result,
-            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
-                // Continue when the socket.readable() call produced a
-                // false positive
-                continue;
-            }
-            Err(err) => {
-                // TODO (Techassi): Log this
-                println!("{err}");
-                continue;
-            }
-        };
+/// A [`MuliplexedClient`] sends all queries via a single [`Multiplexer`],
+/// which runs in a background task. This allows thousands of concurrent
+/// queries to share one socket. Responses are only accepted if the
+/// transaction ID, the source address and the question match the query.
+pub struct MuliplexedClient {
+    active_ids: ActiveIds,
+    handler: Handler,
+}
 
-        // Skip packets which weren't received from the correct remote addr
+impl MuliplexedClient {
+    /// Creates a new multiplexed client with default multiplexer settings.
+    /// This spawns the background task which drives the `transport`.
+    pub fn new<T>(transport: T) -> Self
+    where
+        T: Transport + Send + 'static,
+    {
+        Self::with_multiplexer(Multiplexer::new(transport))
+    }
 
-        // FIXME: This is currently broken, as we query multiple targets at once. It is very likely we receive a valid
-        // answer from a different target then currently assumed in this async task. To handle these situations, we
-        // need to implement a DNS multiplexer. For now we don't ignore the message.
-        if addr != target {
-            // continue;
+    /// Creates a new multiplexed client using the provided `multiplexer`.
+    /// This spawns the background task which drives the multiplexer.
+    pub fn with_multiplexer<T>(multiplexer: Multiplexer<T>) -> Self
+    where
+        T: Transport + Send + 'static,
+    {
+        Self {
+            handler: spawn_multiplexer(multiplexer),
+            active_ids: ActiveIds::default(),
         }
+    }
 
-        return handle_query_response(&buf[..len])
-            .await
-            .map(|r| (r, len, target));
+    /// Sends a query to `target_addr`. See [`Client::query`].
+    pub async fn query<Q>(
+        &self,
+        query: Q,
+        target_addr: SocketAddr,
+    ) -> ClientResult<(Message, usize)>
+    where
+        Q: ToQuery,
+    {
+        let question = Question::from(query.to_query());
+        self.query_question(question, target_addr).await
+    }
+
+    /// Sends a query to `target_addr` and tracks how long the query took.
+    /// See [`Client::query_duration`].
+    pub async fn query_duration<Q>(
+        &self,
+        query: Q,
+        target_addr: SocketAddr,
+    ) -> ClientResult<(Message, usize, Duration)>
+    where
+        Q: ToQuery,
+    {
+        let now = Instant::now();
+        let (message, len) = self.query(query, target_addr).await?;
+        Ok((message, len, now.elapsed()))
     }
-}
 
-async fn handle_query_response(buf: &[u8]) -> ClientResult<Message> {
-    let mut buf = ReadBuffer::new(buf);
+    /// Sends the query to all `target_addrs` in parallel and returns the
+    /// first successful response. See [`Client::query_multi`].
+    pub async fn query_multi<Q>(
+        &self,
+        query: Q,
+        target_addrs: Vec<SocketAddr>,
+    ) -> ClientResult<(Message, usize, SocketAddr)>
+    where
+        Q: ToQuery,
+    {
+        if target_addrs.is_empty() {
+            return Err(ClientError::NoTargets);
+        }
 
-    let header = Header::read::<BigEndian>(&mut buf)?;
-    // Check transaction ID to match. Implement fn accept::accept_as_client
-    let message = Message::read::<BigEndian>(&mut buf, header)?;
+        let question = Question::from(query.to_query());
 
-    Ok(message)
-}
+        let queries = target_addrs.into_iter().map(|target| {
+            let question = question.clone();
 
-fn get_free_transaction_id(active_ids: Arc<HashSet<u16>>) -> u16 {
-    let mut id = rand::random::<u16>();
+            Box::pin(async move {
+                self.query_question(question, target)
+                    .await
+                    .map(|(message, len)| (message, len, target))
+            })
+        });
 
-    // Reroll until we get a free transaction ID
-    while active_ids.contains(&id) {
-        id = rand::random::<u16>();
+        let (result, _) = select_ok(queries).await?;
+        Ok(result)
     }
 
-    id
-}
+    async fn query_question(
+        &self,
+        question: Question,
+        target_addr: SocketAddr,
+    ) -> ClientResult<(Message, usize)> {
+        // The ID is released again when the guard is dropped
+        let id = self.active_ids.acquire().ok_or(ClientError::NoFreeId)?;
 
-pub struct MuliplexedClient<T>
-where
-    T: Transport,
-{
-    background: Background<T>,
-}
+        let mut message = Message::new_with_header(Header::new(id.get()));
+        message.add_question(question);
 
-impl<T> MuliplexedClient<T>
-where
-    T: Transport,
-{
-    pub fn new(transport: T) -> Self {
-        Self {
-            background: Background {
-                stream: Multiplexer::new(transport),
-                enqueued_messages: VecDeque::new(),
-            },
-        }
+        let response = self.handler.query(message, target_addr).await?;
+        Ok(response)
     }
 }
@@ -1,5 +1,14 @@
-use portal_client::Client;
-use portal_proto::Name;
+use binbuf::prelude::*;
+use portal_client::{ActiveId, ActiveIds, Client, MuliplexedClient};
+use portal_common::IpVersion;
+use portal_proto::{
+    transfer::{Protocol, UdpDnsTransport},
+    Class, Header, Message, Name, Question, RType,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, UdpSocket},
+};
 
 #[tokio::test]
 async fn test_client() {
@@ -14,3 +23,285 @@ async fn test_client() {
     //     Err(err) => panic!("{}", err),
     // };
 }
+
+#[tokio::test]
+async fn test_multiplexed_client_validates_responses() {
+    let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = server.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; 512];
+        let (len, client_addr) = server.recv_from(&mut buf).await.unwrap();
+
+        let mut reader = ReadBuffer::new(&buf[..len]);
+        let header = Header::read::<BigEndian>(&mut reader).unwrap();
+        let query = Message::read::<BigEndian>(&mut reader, header).unwrap();
+
+        // First answer with a wrong transaction ID and a wrong question,
+        // which both have to be ignored by the client.
+        let wrong_id = response(query.transaction_id().wrapping_add(1), "example.com");
+        let wrong_question = response(query.transaction_id(), "example.org");
+        let valid = response(query.transaction_id(), "EXAMPLE.com");
+
+        for message in [wrong_id, wrong_question, valid] {
+            let mut writer = WriteBuffer::new();
+            message.write::<BigEndian>(&mut writer).unwrap();
+            server.send_to(writer.bytes(), client_addr).await.unwrap();
+        }
+    });
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let client = MuliplexedClient::new(UdpDnsTransport::new(socket, 16));
+
+    let (message, _) = client
+        .query(
+            (Name::try_from("example.com").unwrap(), RType::A, Class::IN),
+            server_addr,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        message.question().unwrap().name,
+        Name::try_from("EXAMPLE.com").unwrap()
+    );
+}
+
+fn response(id: u16, name: &str) -> Message {
+    let mut message = Message::new_with_header(Header::new(id));
+    message.set_is_response(true);
+    message.add_question(Question::new(
+        Name::try_from(name).unwrap(),
+        RType::A,
+        Class::IN,
+    ));
+    message
+}
+
+#[tokio::test]
+async fn test_client_dual_stack_ipv4_server() {
+    let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = server.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; 512];
+        let (len, client_addr) = server.recv_from(&mut buf).await.unwrap();
+        let query = read_message(&buf[..len]);
+
+        let bytes = write_message(&response(query.transaction_id(), "example.com"));
+        server.send_to(&bytes, client_addr).await.unwrap();
+    });
+
+    // The client socket is bound to [::], so the response arrives from the
+    // IPv4-mapped address of the server
+    let client = Client::builder()
+        .with_ip_version(IpVersion::Both)
+        .with_read_timeout(1)
+        .build()
+        .await
+        .unwrap();
+
+    let (message, _, protocol) = client
+        .query(
+            (Name::try_from("example.com").unwrap(), RType::A, Class::IN),
+            server_addr,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        message.question().unwrap().name,
+        Name::try_from("example.com").unwrap()
+    );
+    assert_eq!(protocol, Protocol::Udp);
+}
+
+#[tokio::test]
+async fn test_client_tcp_fallback() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = listener.local_addr().unwrap();
+    let udp = UdpSocket::bind(server_addr).await.unwrap();
+
+    // The UDP server always answers with a truncated response
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; 512];
+        let (len, client_addr) = udp.recv_from(&mut buf).await.unwrap();
+        let query = read_message(&buf[..len]);
+
+        let mut message = response(query.transaction_id(), "example.com");
+        message.set_truncated(true);
+
+        udp.send_to(&write_message(&message), client_addr)
+            .await
+            .unwrap();
+    });
+
+    // The TCP server answers with the complete response
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        let len = stream.read_u16().await.unwrap() as usize;
+        let mut buf = vec![0u8; len];
+        stream.read_exact(&mut buf).await.unwrap();
+        let query = read_message(&buf);
+
+        let bytes = write_message(&response(query.transaction_id(), "example.com"));
+        stream.write_u16(bytes.len() as u16).await.unwrap();
+        stream.write_all(&bytes).await.unwrap();
+    });
+
+    let client = Client::builder()
+        .with_ip_version(IpVersion::V4)
+        .build()
+        .await
+        .unwrap();
+
+    let (message, _, protocol) = client
+        .query(
+            (Name::try_from("example.com").unwrap(), RType::A, Class::IN),
+            server_addr,
+        )
+        .await
+        .unwrap();
+
+    assert!(!message.is_truncated());
+    assert_eq!(protocol, Protocol::Tcp);
+}
+
+#[tokio::test]
+async fn test_client_concurrent_queries() {
+    let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = server.local_addr().unwrap();
+
+    // Receive both queries first and answer them in reverse order. Each
+    // response has to reach the query it belongs to.
+    tokio::spawn(async move {
+        let mut queries = Vec::new();
+
+        for _ in 0..2 {
+            let mut buf = vec![0u8; 512];
+            let (len, client_addr) = server.recv_from(&mut buf).await.unwrap();
+            queries.push((read_message(&buf[..len]), client_addr));
+        }
+
+        for (query, client_addr) in queries.iter().rev() {
+            let name = query.question().unwrap().name.to_string();
+            let bytes = write_message(&response(query.transaction_id(), &name));
+            server.send_to(&bytes, client_addr).await.unwrap();
+        }
+    });
+
+    let client = Client::builder()
+        .with_ip_version(IpVersion::V4)
+        .build()
+        .await
+        .unwrap();
+
+    let query = |name: &str| (Name::try_from(name).unwrap(), RType::A, Class::IN);
+    let (first, second) = tokio::join!(
+        client.query(query("a.example.com"), server_addr),
+        client.query(query("b.example.com"), server_addr)
+    );
+
+    let (first, first_len, _) = first.unwrap();
+    let (second, _, _) = second.unwrap();
+
+    assert_eq!(
+        first.question().unwrap().name,
+        Name::try_from("a.example.com").unwrap()
+    );
+    assert_eq!(
+        second.question().unwrap().name,
+        Name::try_from("b.example.com").unwrap()
+    );
+    assert_eq!(first_len, write_message(&first).len());
+}
+
+#[tokio::test]
+async fn test_client_tcp_pipelined_queries() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = listener.local_addr().unwrap();
+
+    // Both queries have to arrive via the same connection
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut queries = Vec::new();
+
+        for _ in 0..2 {
+            let len = stream.read_u16().await.unwrap() as usize;
+            let mut buf = vec![0u8; len];
+            stream.read_exact(&mut buf).await.unwrap();
+            queries.push(read_message(&buf));
+        }
+
+        for query in queries.iter().rev() {
+            let name = query.question().unwrap().name.to_string();
+            let bytes = write_message(&response(query.transaction_id(), &name));
+            stream.write_u16(bytes.len() as u16).await.unwrap();
+            stream.write_all(&bytes).await.unwrap();
+        }
+    });
+
+    let client = Client::builder()
+        .with_ip_version(IpVersion::V4)
+        .with_protocol(Protocol::Tcp)
+        .build()
+        .await
+        .unwrap();
+
+    let query = |name: &str| (Name::try_from(name).unwrap(), RType::A, Class::IN);
+    let (first, second) = tokio::join!(
+        client.query(query("a.example.com"), server_addr),
+        client.query(query("b.example.com"), server_addr)
+    );
+
+    let (first, _, first_protocol) = first.unwrap();
+    let (second, _, _) = second.unwrap();
+
+    assert_eq!(first_protocol, Protocol::Tcp);
+    assert_eq!(
+        first.question().unwrap().name,
+        Name::try_from("a.example.com").unwrap()
+    );
+    assert_eq!(
+        second.question().unwrap().name,
+        Name::try_from("b.example.com").unwrap()
+    );
+}
+
+fn read_message(buf: &[u8]) -> Message {
+    let mut reader = ReadBuffer::new(buf);
+    let header = Header::read::<BigEndian>(&mut reader).unwrap();
+    Message::read::<BigEndian>(&mut reader, header).unwrap()
+}
+
+fn write_message(message: &Message) -> Vec<u8> {
+    let mut writer = WriteBuffer::new();
+    message.write::<BigEndian>(&mut writer).unwrap();
+    writer.owned_bytes()
+}
+
+#[test]
+fn test_active_ids_unique() {
+    let ids = ActiveIds::default();
+
+    let first = ids.acquire().unwrap();
+    let second = ids.acquire().unwrap();
+    assert_ne!(first.get(), second.get());
+    assert_eq!(ids.len(), 2);
+
+    drop(first);
+    assert_eq!(ids.len(), 1);
+}
+
+#[test]
+fn test_active_ids_exhausted() {
+    let ids = ActiveIds::default();
+    let mut active: Vec<ActiveId> = (0..=u16::MAX).map(|_| ids.acquire().unwrap()).collect();
+
+    assert!(ids.acquire().is_none());
+
+    // The released ID is handed out again
+    let released = active.pop().unwrap().get();
+    assert_eq!(ids.acquire().unwrap().get(), released);
+}
@@ -1,4 +1,8 @@
-#[derive(Debug)]
+use std::{fmt::Display, net::IpAddr, str::FromStr};
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IpVersion {
     Both,
     V4,
@@ -22,3 +26,43 @@ impl From<(bool, bool)> for IpVersion {
         }
     }
 }
+
+#[derive(Debug, Error)]
+pub struct IpVersionError {
+    input: String,
+}
+
+impl Display for IpVersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid IP version {}, expected v4/v6/both", self.input)
+    }
+}
+
+impl FromStr for IpVersion {
+    type Err = IpVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "v4" | "ipv4" | "4" => Ok(Self::V4),
+            "v6" | "ipv6" | "6" => Ok(Self::V6),
+            "both" => Ok(Self::Both),
+            _ => Err(IpVersionError { input: s.into() }),
+        }
+    }
+}
+
+impl IpVersion {
+    /// Returns if `addr` belongs to one of the address families of this IP
+    /// version. Unspecified addresses are never supported.
+    pub fn supports(&self, addr: &IpAddr) -> bool {
+        if addr.is_unspecified() {
+            return false;
+        }
+
+        match self {
+            Self::Both => true,
+            Self::V4 => addr.is_ipv4(),
+            Self::V6 => addr.is_ipv6(),
+        }
+    }
+}
@@ -7,6 +7,9 @@ use thiserror::Error;
 pub enum Network {
     Tcp,
     Udp,
+
+    /// Listen for both UDP and TCP on the same address
+    Both,
 }
 
 #[derive(Debug, Error)]
@@ -16,7 +19,7 @@ pub struct NetworkError {
 
 impl Display for NetworkError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Invalid network {}, expected tcp/udp", self.input)
+        write!(f, "Invalid network {}, expected tcp/udp/both", self.input)
     }
 }
 
@@ -27,6 +30,7 @@ impl FromStr for Network {
         match s.to_lowercase().as_str() {
             "tcp" | "tcp4" | "tcp6" => Ok(Network::Tcp),
             "udp" | "udp4" | "udp6" => Ok(Network::Udp),
+            "both" => Ok(Network::Both),
             _ => Err(NetworkError { input: s.into() }),
         }
     }
@@ -27,10 +27,14 @@ pub const MAX_LABEL_LENGTH: u8 = 0x3F;
 pub const HEADER_LENGTH: usize = 12;
 
 pub const MIN_MESSAGE_SIZE: usize = 512;
+
+/// The highest supported EDNS version. See RFC 6891 Section 6.1.3.
+pub const EDNS_VERSION: u8 = 0;
 pub const MAX_MESSAGE_SIZE: usize = u16::MAX as usize;
 
 pub const ZONE_CONTROL_ENTRY_INCLUDE: &str = "$INCLUDE";
-pub const ZONE_CONTROL_ENTRY_INCLUDE_LEN: usize = ZONE_CONTROL_ENTRY_INCLUDE.len();
-
 pub const ZONE_CONTROL_ENTRY_ORIGIN: &str = "$ORIGIN";
-pub const ZONE_CONTROL_ENTRY_ORIGIN_LEN: usize = ZONE_CONTROL_ENTRY_ORIGIN.len();
+pub const ZONE_CONTROL_ENTRY_TTL: &str = "$TTL";
+
+/// The maximum nesting level of `$INCLUDE` entries in master files.
+pub const ZONE_MAX_INCLUDE_DEPTH: usize = 16;
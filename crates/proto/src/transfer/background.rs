@@ -4,23 +4,32 @@ use std::{
     task::{Context, Poll},
 };
 
-use futures::Future;
+use futures::{Future, StreamExt};
+use tokio::sync::mpsc;
 
-use crate::{
-    transfer::{Multiplexer, Transport},
-    Message,
-};
+use crate::transfer::{Handler, HandlerRequest, MultiplexResponseError, Multiplexer, Transport};
 
 /// A [`Background<T>`] runs a stream in the background, for example the
 /// multiplexer. [`Background`] implements [`Future`] which allows external
 /// callers to spawn the future within a Tokio task. This is what drives the
 /// [`Background`] (and the internal stream / multiplexer) forward.
+///
+/// Requests are submitted via the [`Handler`] returned by [`Background::new`].
+/// The future completes once all handlers are dropped and all inflight
+/// requests are finished, or when the underlying transport is closed.
 pub struct Background<T>
 where
     T: Transport,
 {
-    pub enqueued_messages: VecDeque<Message>,
+    pub enqueued_messages: VecDeque<HandlerRequest>,
     pub stream: Multiplexer<T>,
+
+    /// Receives the requests submitted by all [`Handler`]s.
+    requests: mpsc::Receiver<HandlerRequest>,
+
+    /// This is set once all handlers were dropped. No new requests can be
+    /// submitted after this point.
+    requests_closed: bool,
 }
 
 impl<S> Future for Background<S>
@@ -29,7 +38,92 @@ where
 {
     type Output = ();
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        todo!()
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // First pull all newly submitted requests from the handlers and
+        // enqueue them.
+        while !self.requests_closed {
+            match self.requests.poll_recv(cx) {
+                Poll::Ready(Some(request)) => self.enqueued_messages.push_back(request),
+                Poll::Ready(None) => self.requests_closed = true,
+                Poll::Pending => break,
+            }
+        }
+
+        // Hand the enqueued requests to the multiplexer as long as the
+        // underlying transport is ready to accept them.
+        while !self.enqueued_messages.is_empty() {
+            match self.stream.poll_ready(cx) {
+                Poll::Ready(Ok(_)) => {
+                    let request = self.enqueued_messages.pop_front().unwrap();
+                    self.stream.start_send(request.request, request.finisher);
+                }
+                Poll::Ready(Err(err)) => {
+                    // The transport is unable to accept the request. Fail the
+                    // request instead of retrying forever.
+                    let request = self.enqueued_messages.pop_front().unwrap();
+                    let _ = request
+                        .finisher
+                        .send(Err(MultiplexResponseError::SendError(err.to_string())));
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        if let Poll::Ready(Err(err)) = self.stream.poll_flush(cx) {
+            eprintln!("{}", err);
+        }
+
+        // Drive receiving of responses and timeouts
+        loop {
+            match self.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(_))) => continue,
+                Poll::Ready(Some(Err(err))) => {
+                    eprintln!("{}", err);
+                    continue;
+                }
+                Poll::Ready(None) => {
+                    // The transport is closed, which means no enqueued
+                    // request will ever be sent.
+                    for request in self.enqueued_messages.drain(..) {
+                        let _ = request
+                            .finisher
+                            .send(Err(MultiplexResponseError::StreamClosed));
+                    }
+
+                    return Poll::Ready(());
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        if self.requests_closed
+            && self.enqueued_messages.is_empty()
+            && self.stream.num_inflights() == 0
+        {
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T> Background<T>
+where
+    T: Transport,
+{
+    /// Creates a new [`Background`] which drives the `multiplexer` and a
+    /// [`Handler`] to submit requests. At most `buffer_size` requests can be
+    /// submitted before the background picks them up.
+    pub fn new(multiplexer: Multiplexer<T>, buffer_size: usize) -> (Self, Handler) {
+        let (tx, rx) = mpsc::channel(buffer_size);
+
+        let background = Self {
+            enqueued_messages: VecDeque::new(),
+            stream: multiplexer,
+            requests: rx,
+            requests_closed: false,
+        };
+
+        (background, Handler::new(tx))
     }
 }
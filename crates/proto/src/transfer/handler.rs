@@ -1,4 +1,65 @@
+use std::net::SocketAddr;
+
+use futures::channel::oneshot;
+use tokio::sync::mpsc;
+
+use crate::{
+    transfer::{MultiplexResponseError, MultiplexResponseStream, MultiplexResult, Request},
+    Message,
+};
+
+/// A [`HandlerRequest`] is submitted by a [`Handler`] to the background. It
+/// bundles the request to send and the channel the response is sent back
+/// with.
+#[derive(Debug)]
+pub struct HandlerRequest {
+    pub request: Request,
+    pub finisher: oneshot::Sender<MultiplexResult>,
+}
+
 /// A [`Handler`] allows external callers to send messages to a background
 /// which will finally send the message and will return the response in
 /// the future.
-pub struct Handler {}
+///
+/// Handlers are cheap to clone. Each task can use its own clone to submit
+/// requests, which are all sent via the same background and thus the same
+/// socket.
+#[derive(Debug, Clone)]
+pub struct Handler {
+    tx: mpsc::Sender<HandlerRequest>,
+}
+
+impl Handler {
+    pub(crate) fn new(tx: mpsc::Sender<HandlerRequest>) -> Self {
+        Self { tx }
+    }
+
+    /// Submits the `message` which will be sent to `target`. The returned
+    /// future resolves to the response once it is received.
+    pub async fn send(
+        &self,
+        message: Message,
+        target: SocketAddr,
+    ) -> Result<MultiplexResponseStream, MultiplexResponseError> {
+        let (finisher, rx) = oneshot::channel();
+
+        let request = HandlerRequest {
+            request: Request::new(message, target),
+            finisher,
+        };
+
+        // This waits for a free slot once the buffer of the background is
+        // full.
+        if self.tx.send(request).await.is_err() {
+            return Err(MultiplexResponseError::StreamClosed);
+        }
+
+        Ok(MultiplexResponseStream::new(rx))
+    }
+
+    /// Submits the `message` which will be sent to `target` and waits for
+    /// the response, which is returned together with its wire length.
+    pub async fn query(&self, message: Message, target: SocketAddr) -> MultiplexResult {
+        self.send(message, target).await?.await
+    }
+}
@@ -19,6 +19,7 @@ pub trait RequestExt {
     fn message(&self) -> &Message;
 }
 
+#[derive(Debug)]
 pub struct Request {
     target_socket_addr: SocketAddr,
     message: Message,
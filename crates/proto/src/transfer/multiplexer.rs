@@ -14,18 +14,21 @@ use futures::{
     Future, FutureExt, SinkExt, Stream, StreamExt,
 };
 use thiserror::Error;
-use tokio::time::Instant;
+use tokio::time::{sleep_until, Instant, Sleep};
 
 use crate::{
-    transfer::{Request, Transport},
-    Message, MessageError,
+    transfer::{Request, RequestExt, Transport, TransportError},
+    Message, MessageError, Question,
 };
 
+/// The response of a multiplexed request together with its wire length.
+pub type MultiplexResult = Result<(Message, usize), MultiplexResponseError>;
+
 #[derive(Debug)]
 pub struct InflightRequest {
     /// This channel will send the received response to the correct request.
     /// This finihes the request.
-    finisher: Option<Sender<Result<Message, MultiplexResponseError>>>,
+    finisher: Option<Sender<MultiplexResult>>,
 
     /// Once this timeout finishes and the request did not receive a response,
     /// this request is marked as stale and will be romved from the multiplexer
@@ -34,17 +37,49 @@ pub struct InflightRequest {
 
     /// This keeps track when the request was enqueued.
     enqueued: Instant,
+
+    /// The question of the request. The response has to contain the same
+    /// question, otherwise it is rejected.
+    question: Option<Question>,
 }
 
 impl InflightRequest {
     pub fn new(
-        finisher: Sender<Result<Message, MultiplexResponseError>>,
+        finisher: Sender<MultiplexResult>,
         timeout: Duration,
+        question: Option<Question>,
     ) -> Self {
         Self {
             finisher: Some(finisher),
             enqueued: Instant::now(),
             timeout,
+            question,
+        }
+    }
+
+    /// Returns if the `response` answers this request. The response has to
+    /// contain the same question as the request. See RFC 5452 Section 4.
+    pub fn accepts(&self, response: &Message) -> bool {
+        match (&self.question, response.question()) {
+            (Some(question), Some(other)) => question.matches(other),
+            (None, _) => true,
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Returns the instant at which this request times out.
+    pub fn deadline(&self) -> Instant {
+        self.enqueued + self.timeout
+    }
+
+    /// Finishes the request by sending `result` to the original caller. The
+    /// caller might have given up on the request already, which is fine.
+    fn finish(&mut self, result: MultiplexResult) {
+        match self.finisher.take() {
+            Some(chan) => {
+                let _ = chan.send(result);
+            }
+            None => println!("The completing channel was already used"),
         }
     }
 }
@@ -53,6 +88,12 @@ impl InflightRequest {
 pub enum MultiplexError {
     #[error("multiplexer stream closed")]
     StreamClosed,
+
+    #[error("failed to send message via transport: {0}")]
+    SendError(String),
+
+    #[error("a request with ID {id} to {target} is already inflight")]
+    DuplicateRequest { id: u16, target: SocketAddr },
 }
 
 pub struct Multiplexer<T>
@@ -80,8 +121,14 @@ where
     read_timeout: Duration,
 
     // === Management of active requests
-    /// This keeps track of inflight requests.
-    inflights: HashMap<u16, InflightRequest>,
+    /// This keeps track of inflight requests. Requests are identified by the
+    /// transaction ID and the target address. Responses are only accepted
+    /// when they are received from the address the request was sent to.
+    inflights: HashMap<(u16, SocketAddr), InflightRequest>,
+
+    /// This timer fires when the next inflight request times out. It is
+    /// re-armed every time the multiplexer is polled.
+    timeout_timer: Option<Pin<Box<Sleep>>>,
 
     /// This is the underlying transport to receive and send messages from and
     /// to. The multiplexer doesn't care what protocol is used here and thus
@@ -107,38 +154,46 @@ where
             match self.transport.poll_next_unpin(cx) {
                 Poll::Ready(poll) => match poll {
                     Some(item) => match item {
-                        Ok(message) => {
+                        Ok((message, len, source)) => {
                             // We received a DNS message (answer from remote
                             // DNS server). Lookup the corresponding inflight
-                            // request and use the mpsc channel to send back
-                            // the received answer.
+                            // request and use the oneshot channel to send
+                            // back the received answer.
 
                             // First we update the message received counter
                             num_recv_messages += 1;
 
-                            // Next look up the messages transaction ID in the
-                            // inflights hash map. All received messages should
-                            // have a corresponding request saved in the map.
-                            // If this is not the case, we know the other end
-                            // sent us a big pile of BS. The multiplexer will
-                            // log this but will silently ignore the message
-                            // from the senders POV.
-                            match self.inflights.entry(message.transaction_id()) {
-                                Occupied(mut request) => {
+                            // Next look up the messages transaction ID and
+                            // source address in the inflights hash map. All
+                            // received messages should have a corresponding
+                            // request saved in the map. If this is not the
+                            // case, we know the other end sent us a big pile
+                            // of BS (or someone tries to spoof responses).
+                            // The multiplexer will log this but will silently
+                            // ignore the message from the senders POV.
+                            let xid = message.transaction_id();
+
+                            match self.inflights.entry((xid, source)) {
+                                Occupied(request) if request.get().accepts(&message) => {
                                     // Send the answer to the inflight response
-                                    // handler
-                                    match request.get_mut().finisher.take() {
-                                        Some(chan) => chan.send(Ok(message)).unwrap(),
-                                        None => {
-                                            println!("The completing channel was already used")
-                                        }
-                                    }
+                                    // handler. The request is finished and
+                                    // can be removed.
+                                    request.remove().finish(Ok((message, len)));
+                                }
+                                Occupied(_) => {
+                                    println!("Question mismatch in response {xid} from {source}")
                                 }
                                 Vacant(_) => {
-                                    println!("Invalid request id: {}", message.transaction_id())
+                                    println!("Invalid request id {xid} from {source}")
                                 }
                             }
                         }
+                        Err(TransportError::ConnectionClosed(target)) => {
+                            // Requests sent via the closed connection will
+                            // never be answered, so there is no point in
+                            // waiting for them to time out.
+                            self.fail_target(target);
+                        }
                         Err(err) => {
                             // We received some invalid blob of bytes which
                             // couldn't be parsed as a DNS message. In this
@@ -152,7 +207,9 @@ where
                         // When we receive None, most likely the underlying
                         // transport was closed and isn't producing any
                         // more messages. In this case we should also terminate
-                        // the multiplexer.
+                        // the multiplexer. All inflight requests will never
+                        // receive a response.
+                        self.fail_inflights(|| MultiplexResponseError::StreamClosed);
                         return Poll::Ready(None);
                     }
                 },
@@ -171,6 +228,10 @@ where
             cx.waker().wake_by_ref()
         }
 
+        // Fail all requests which didn't receive a response in time and make
+        // sure we get woken up when the next request times out.
+        self.poll_timeouts(cx);
+
         Poll::Pending
     }
 }
@@ -193,24 +254,136 @@ where
         target: SocketAddr,
     ) -> Result<MultiplexResponseStream, MultiplexError> {
         let (tx, rx) = oneshot::channel();
-        let xid = message.transaction_id();
+        let key = (message.transaction_id(), target);
 
+        if self.inflights.contains_key(&key) {
+            return Err(MultiplexError::DuplicateRequest { id: key.0, target });
+        }
+
+        let question = message.question().cloned();
         let request = Request::new(message, target);
 
-        match self.transport.send(request).await {
+        if let Err(err) = self.transport.send(request).await {
+            return Err(MultiplexError::SendError(err.to_string()));
+        }
+
+        let request = InflightRequest::new(tx, self.read_timeout, question);
+        self.inflights.insert(key, request);
+
+        Ok(MultiplexResponseStream::new(rx))
+    }
+
+    /// Polls the underlying transport to check if it is ready to accept the
+    /// next request via [`Multiplexer::start_send`].
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), MultiplexError>> {
+        self.transport
+            .poll_ready_unpin(cx)
+            .map_err(|err| MultiplexError::SendError(err.to_string()))
+    }
+
+    /// Hands the `request` to the underlying transport and registers it as
+    /// inflight. The response (or error) is sent back via `finisher`. This
+    /// must only be called after [`Multiplexer::poll_ready`] returned
+    /// `Poll::Ready(Ok(()))`. The request is only actually sent once the
+    /// multiplexer is flushed via [`Multiplexer::poll_flush`].
+    pub fn start_send(&mut self, request: Request, finisher: Sender<MultiplexResult>) {
+        let key = (request.message().transaction_id(), request.target());
+        let question = request.message().question().cloned();
+        let mut inflight = InflightRequest::new(finisher, self.read_timeout, question);
+
+        // Two requests with the same ID to the same target can't be told
+        // apart when the responses arrive.
+        if self.inflights.contains_key(&key) {
+            let err = MultiplexError::DuplicateRequest {
+                id: key.0,
+                target: key.1,
+            };
+
+            inflight.finish(Err(MultiplexResponseError::SendError(err.to_string())));
+            return;
+        }
+
+        match self.transport.start_send_unpin(request) {
             Ok(_) => {
-                let request = InflightRequest::new(tx, self.read_timeout.clone());
-                self.inflights.insert(xid, request)
+                self.inflights.insert(key, inflight);
             }
-            Err(_) => todo!(),
-        };
+            Err(err) => inflight.finish(Err(MultiplexResponseError::SendError(err.to_string()))),
+        }
+    }
 
-        Ok(MultiplexResponseStream::new(rx))
+    /// Flushes all requests buffered in the underlying transport.
+    pub fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), MultiplexError>> {
+        self.transport
+            .poll_flush_unpin(cx)
+            .map_err(|err| MultiplexError::SendError(err.to_string()))
+    }
+
+    /// Returns the number of inflight requests.
+    pub fn num_inflights(&self) -> usize {
+        self.inflights.len()
     }
 
-    /// Drop all timed out requests
+    /// Drop all timed out requests. The original callers receive a
+    /// [`MultiplexResponseError::Timeout`] error.
     fn drop_timeouts(&mut self) {
-        todo!()
+        let now = Instant::now();
+
+        self.inflights.retain(|_, request| {
+            if request.deadline() > now {
+                return true;
+            }
+
+            request.finish(Err(MultiplexResponseError::Timeout));
+            false
+        });
+    }
+
+    /// Drops all timed out requests and arms the timeout timer for the next
+    /// request to time out. The timer registers the waker of `cx`, which
+    /// wakes up the multiplexer even if no messages are received.
+    fn poll_timeouts(&mut self, cx: &mut Context<'_>) {
+        loop {
+            self.drop_timeouts();
+
+            let deadline = match self.inflights.values().map(|r| r.deadline()).min() {
+                Some(deadline) => deadline,
+                None => {
+                    self.timeout_timer = None;
+                    return;
+                }
+            };
+
+            let timer = self
+                .timeout_timer
+                .get_or_insert_with(|| Box::pin(sleep_until(deadline)));
+
+            if timer.deadline() != deadline {
+                timer.as_mut().reset(deadline);
+            }
+
+            if timer.as_mut().poll(cx).is_pending() {
+                return;
+            }
+        }
+    }
+
+    /// Fails all inflight requests sent to `target`.
+    fn fail_target(&mut self, target: SocketAddr) {
+        self.inflights.retain(|(_, request_target), request| {
+            if *request_target != target {
+                return true;
+            }
+
+            request.finish(Err(MultiplexResponseError::ConnectionClosed(target)));
+            false
+        });
+    }
+
+    /// Fails all inflight requests with the error produced by `err`.
+    fn fail_inflights(&mut self, err: impl Fn() -> MultiplexResponseError) {
+        for (_, mut request) in self.inflights.drain() {
+            request.finish(Err(err()));
+        }
     }
 }
 
@@ -255,6 +428,7 @@ impl MultiplexerBuilder {
             write_timeout: self.write_timeout,
             read_timeout: self.read_timeout,
             inflights: HashMap::new(),
+            timeout_timer: None,
             transport,
         }
     }
@@ -267,15 +441,27 @@ pub enum MultiplexResponseError {
 
     #[error("failed to retrieve multiplexed response, channel closed")]
     Canceled(#[from] Canceled),
+
+    #[error("multiplexed request timed out")]
+    Timeout,
+
+    #[error("failed to send multiplexed request: {0}")]
+    SendError(String),
+
+    #[error("multiplexer stream closed")]
+    StreamClosed,
+
+    #[error("connection to {0} closed before a response was received")]
+    ConnectionClosed(SocketAddr),
 }
 
 #[derive(Debug)]
 pub struct MultiplexResponseStream {
-    rx: oneshot::Receiver<Result<Message, MultiplexResponseError>>,
+    rx: oneshot::Receiver<MultiplexResult>,
 }
 
 impl Future for MultiplexResponseStream {
-    type Output = Result<Message, MultiplexResponseError>;
+    type Output = MultiplexResult;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match self.rx.poll_unpin(cx) {
@@ -289,7 +475,7 @@ impl Future for MultiplexResponseStream {
 }
 
 impl MultiplexResponseStream {
-    pub fn new(rx: oneshot::Receiver<Result<Message, MultiplexResponseError>>) -> Self {
+    pub fn new(rx: oneshot::Receiver<MultiplexResult>) -> Self {
         Self { rx }
     }
 }
@@ -298,7 +484,16 @@ impl MultiplexResponseStream {
 mod test {
     use tokio::net::UdpSocket;
 
-    use crate::{transfer::UdpDnsTransport, Class, Header, Name, Question, RType};
+    use binbuf::prelude::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use crate::{
+        transfer::{Background, TcpDnsTransport, UdpDnsTransport},
+        Class, Header, Name, Question, RType,
+    };
 
     use super::*;
 
@@ -326,10 +521,149 @@ mod test {
             },
             r = resp => {
                 match r {
-                    Ok(msg) => println!("{}", msg),
+                    Ok((msg, _)) => println!("{}", msg),
                     Err(err) => eprintln!("{}", err),
                 }
             }
         }
     }
+
+    #[tokio::test]
+    async fn udp_multiplexer_timeout() {
+        // This socket never answers, so the request has to time out
+        let target = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+        let transport = UdpDnsTransport::new(socket, 100);
+        let mp = Multiplexer::builder()
+            .with_read_timeout(Duration::from_millis(100))
+            .build(transport);
+
+        let (background, handler) = Background::new(mp, 16);
+        tokio::spawn(background);
+
+        let mut message = Message::new_with_header(Header::new(123));
+        message.add_question(Question::new(
+            Name::try_from("example.com").unwrap(),
+            RType::A,
+            Class::IN,
+        ));
+
+        let result = handler.query(message, target.local_addr().unwrap()).await;
+
+        assert!(matches!(result, Err(MultiplexResponseError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn tcp_multiplexer_out_of_order() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap();
+
+        // Read two pipelined queries and answer them in reverse order
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut frames = Vec::new();
+
+            for _ in 0..2 {
+                let len = stream.read_u16().await.unwrap() as usize;
+                let mut frame = vec![0u8; len + 2];
+                frame[..2].copy_from_slice(&(len as u16).to_be_bytes());
+                stream.read_exact(&mut frame[2..]).await.unwrap();
+                frames.push(frame);
+            }
+
+            for frame in frames.iter().rev() {
+                stream.write_all(frame).await.unwrap();
+            }
+        });
+
+        let transport = TcpDnsTransport::new(16);
+        let (background, handler) = Background::new(Multiplexer::new(transport), 16);
+        tokio::spawn(background);
+
+        let query = |id: u16, name: &str| {
+            let mut message = Message::new_with_header(Header::new(id));
+            message.add_question(Question::new(
+                Name::try_from(name).unwrap(),
+                RType::A,
+                Class::IN,
+            ));
+            message
+        };
+
+        let (first, second) = tokio::join!(
+            handler.query(query(1, "a.example.com"), target),
+            handler.query(query(2, "b.example.com"), target)
+        );
+
+        assert_eq!(first.unwrap().0.transaction_id(), 1);
+        assert_eq!(second.unwrap().0.transaction_id(), 2);
+    }
+
+    #[tokio::test]
+    async fn tcp_multiplexer_connection_closed() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap();
+
+        // Read the query and close the connection without answering
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let len = stream.read_u16().await.unwrap() as usize;
+            let mut frame = vec![0u8; len];
+            stream.read_exact(&mut frame).await.unwrap();
+        });
+
+        let mp = Multiplexer::builder()
+            .with_read_timeout(Duration::from_secs(30))
+            .build(TcpDnsTransport::new(16));
+
+        let (background, handler) = Background::new(mp, 16);
+        tokio::spawn(background);
+
+        let mut message = Message::new_with_header(Header::new(123));
+        message.add_question(Question::new(
+            Name::try_from("example.com").unwrap(),
+            RType::A,
+            Class::IN,
+        ));
+
+        let result = tokio::time::timeout(Duration::from_secs(5), handler.query(message, target))
+            .await
+            .expect("the request has to fail before the read timeout");
+
+        assert!(matches!(
+            result,
+            Err(MultiplexResponseError::ConnectionClosed(addr)) if addr == target
+        ));
+    }
+
+    #[tokio::test]
+    async fn handler_backpressure() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = socket.local_addr().unwrap();
+
+        // The background is never polled, so only a single request fits into
+        // its buffer
+        let transport = UdpDnsTransport::new(socket, 100);
+        let (_background, handler) = Background::new(Multiplexer::new(transport), 1);
+
+        let query = || {
+            let mut message = Message::new_with_header(Header::new(123));
+            message.add_question(Question::new(
+                Name::try_from("example.com").unwrap(),
+                RType::A,
+                Class::IN,
+            ));
+            message
+        };
+
+        let _pending = handler.send(query(), target).await.unwrap();
+
+        let result = tokio::time::timeout(
+            Duration::from_millis(100),
+            handler.clone().send(query(), target),
+        )
+        .await;
+        assert!(result.is_err());
+    }
 }
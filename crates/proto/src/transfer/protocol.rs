@@ -1,7 +1,7 @@
 #[cfg(feature = "clap")]
 use clap::ValueEnum;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "clap", derive(ValueEnum))]
 pub enum Protocol {
     /// Use UDP for transport
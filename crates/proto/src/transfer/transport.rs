@@ -1,4 +1,8 @@
 use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    io,
+    net::SocketAddr,
     pin::Pin,
     task::{Context, Poll},
 };
@@ -10,26 +14,62 @@ use binbuf::{
 };
 use futures::{Sink, Stream};
 use thiserror::Error;
-use tokio::{io::ReadBuf, net::UdpSocket};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpStream, UdpSocket},
+};
 
-use crate::{transfer::Request, Header, Message, MessageError};
+use crate::{
+    constants::MAX_MESSAGE_SIZE, transfer::Request, Header, HeaderError, Message, MessageError,
+};
 
+/// A [`Transport`] sends [`Request`]s and produces the received messages
+/// together with their wire length and the address they were received from.
 pub trait Transport:
-    Stream<Item = Result<Message, MessageError>> + Sink<Request, Error = Self::SinkError> + Unpin
+    Stream<Item = Result<(Message, usize, SocketAddr), TransportError>>
+    + Sink<Request, Error = Self::SinkError>
+    + Unpin
 {
     // Workaround for https://github.com/rust-lang/rust/issues/52662
     type SinkError: std::fmt::Debug + std::error::Error;
 }
 
+/// Errors produced by the receiving side of a [`Transport`].
+#[derive(Debug, Error)]
+pub enum TransportError {
+    #[error("header error: {0}")]
+    HeaderError(#[from] HeaderError),
+
+    #[error("message error: {0}")]
+    MessageError(#[from] MessageError),
+
+    #[error("io error: {0}")]
+    IoError(#[from] io::Error),
+
+    /// The connection to the target was closed or couldn't be established.
+    /// Requests sent via this connection will never receive a response.
+    #[error("connection to {0} closed")]
+    ConnectionClosed(SocketAddr),
+}
+
 #[derive(Debug, Error)]
 pub enum UdpDnsTransportError {
     #[error("io error")]
     IoError(#[from] std::io::Error),
+
+    #[error("failed to write message")]
+    WriteError,
 }
 
 pub struct UdpDnsTransport {
     /// This buffer contains messages to be sent until they are actually sent
     buffer: Vec<Request>,
+    buffer_size: usize,
+
+    /// Received datagrams are read into this buffer. Datagrams larger than
+    /// the buffer are truncated by the OS and fail to parse.
+    recv_buf: Vec<u8>,
+
     writer: WriteBuffer,
     socket: UdpSocket,
 }
@@ -39,31 +79,34 @@ impl Transport for UdpDnsTransport {
 }
 
 impl Stream for UdpDnsTransport {
-    // TODO (Techassi): Switch out this error type to an error which wraps MessageError and Stream related errors
-    type Item = Result<Message, MessageError>;
-
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let mut buffer = vec![0u8; 512];
-        let mut buf = ReadBuf::new(&mut buffer);
-
-        match self.socket.poll_recv(cx, &mut buf) {
-            Poll::Ready(res) => match res {
-                Ok(_) => {
-                    let mut buf = ReadBuffer::new(&buffer);
-
-                    let header = match Header::read::<BigEndian>(&mut buf) {
-                        Ok(header) => header,
-                        Err(err) => return Poll::Ready(Some(Err(err.into()))),
-                    };
-
-                    match Message::read::<BigEndian>(&mut buf, header) {
-                        Ok(msg) => Poll::Ready(Some(Ok(msg))),
-                        Err(err) => return Poll::Ready(Some(Err(err))),
-                    }
-                }
-                Err(_) => todo!(),
-            },
-            Poll::Pending => Poll::Pending,
+    type Item = Result<(Message, usize, SocketAddr), TransportError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let mut buf = ReadBuf::new(&mut this.recv_buf);
+
+        let source = match this.socket.poll_recv_from(cx, &mut buf) {
+            Poll::Ready(Ok(source)) => source,
+            Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        // Sockets bound to [::] receive IPv4 responses from IPv4-mapped IPv6
+        // addresses. These are mapped back to the IPv4 address the request
+        // was sent to.
+        let source = SocketAddr::new(source.ip().to_canonical(), source.port());
+
+        let len = buf.filled().len();
+        let mut buf = ReadBuffer::new(buf.filled());
+
+        let header = match Header::read::<BigEndian>(&mut buf) {
+            Ok(header) => header,
+            Err(err) => return Poll::Ready(Some(Err(err.into()))),
+        };
+
+        match Message::read::<BigEndian>(&mut buf, header) {
+            Ok(msg) => Poll::Ready(Some(Ok((msg, len, source)))),
+            Err(err) => Poll::Ready(Some(Err(err.into()))),
         }
     }
 }
@@ -71,12 +114,13 @@ impl Stream for UdpDnsTransport {
 impl Sink<Request> for UdpDnsTransport {
     type Error = UdpDnsTransportError;
 
-    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        if self.buffer.is_empty() {
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.buffer.len() < self.buffer_size {
             return Poll::Ready(Ok(()));
         }
 
-        Poll::Pending
+        // Try to make room by sending the buffered messages
+        self.as_mut().poll_flush(cx)
     }
 
     fn start_send(mut self: Pin<&mut Self>, item: Request) -> Result<(), Self::Error> {
@@ -86,47 +130,42 @@ impl Sink<Request> for UdpDnsTransport {
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        let mut bytes_sent = 0;
-
-        while let Some(request) = self.buffer.pop() {
-            request.message.write_be(&mut self.writer).unwrap();
-
-            let bytes = self.writer.owned_bytes();
-            let bytes_to_send = bytes.len();
-            self.writer.clear();
-
-            // while bytes_sent < bytes_to_send {
-            //     match self
-            //         .socket
-            //         .poll_send_to(cx, bytes.as_slice(), request.target_socket_addr)
-            //     {
-            //         Poll::Ready(result) => {
-            //             bytes_sent += result?;
-            //         }
-            //         // FIXME: This might be an endless loop if we never succeed to send the complete message
-            //         Poll::Pending => continue,
-            //     }
-            // }
-
-            // bytes_sent = 0;
-
-            match self
+        let this = &mut *self;
+        let mut error = None;
+
+        while !this.buffer.is_empty() {
+            let request = &this.buffer[0];
+
+            this.writer.clear();
+            if request.message.write_be(&mut this.writer).is_err() {
+                this.buffer.remove(0);
+                error = Some(UdpDnsTransportError::WriteError);
+                continue;
+            }
+
+            match this
                 .socket
-                .poll_send_to(cx, bytes.as_slice(), request.target_socket_addr)
+                .poll_send_to(cx, this.writer.bytes(), request.target_socket_addr)
             {
-                Poll::Ready(_) => (),
-                Poll::Pending => {
-                    self.buffer.push(request);
-                    return Poll::Pending;
-                }
+                Poll::Ready(Ok(_)) => {}
+                // The request can't be sent, e.g. because the network of the
+                // target is unreachable. It is dropped, the multiplexer times
+                // out the inflight request.
+                Poll::Ready(Err(err)) => error = Some(err.into()),
+                Poll::Pending => return Poll::Pending,
             }
+
+            this.buffer.remove(0);
         }
 
-        Poll::Ready(Ok(()))
+        match error {
+            Some(err) => Poll::Ready(Err(err)),
+            None => Poll::Ready(Ok(())),
+        }
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        todo!()
+        self.poll_flush(cx)
     }
 }
 
@@ -136,13 +175,354 @@ impl UdpDnsTransport {
     /// interpreted as DNS messages. This transport implements [`Sink`], which
     /// first saves massages to be sent in an internal buffer which can contain
     /// at max `buffer_size` messages.
+    ///
+    /// Received datagrams can be up to 65535 octets long. Use
+    /// [`UdpDnsTransport::with_recv_buffer_size`] to limit the size.
     pub fn new(socket: UdpSocket, buffer_size: usize) -> Self {
-        let writer = WriteBuffer::new();
-
         Self {
             buffer: Vec::with_capacity(buffer_size),
+            recv_buf: vec![0u8; MAX_MESSAGE_SIZE],
+            writer: WriteBuffer::new(),
+            buffer_size: buffer_size.max(1),
             socket,
-            writer,
+        }
+    }
+
+    /// Customize the size of the receive buffer. This should match the UDP
+    /// payload size advertised via EDNS, see
+    /// [RFC 6891 Section 6.2.5](https://datatracker.ietf.org/doc/html/rfc6891#section-6.2.5).
+    pub fn with_recv_buffer_size(&mut self, recv_buffer_size: usize) -> &mut Self {
+        self.recv_buf = vec![0u8; recv_buffer_size];
+        self
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TcpDnsTransportError {
+    #[error("io error")]
+    IoError(#[from] std::io::Error),
+
+    #[error("message too long for TCP framing ({0} octets)")]
+    MessageTooLong(usize),
+
+    #[error("failed to write message")]
+    WriteError,
+}
+
+type ConnectFuture = Pin<Box<dyn Future<Output = io::Result<TcpStream>> + Send>>;
+
+enum TcpConnectionState {
+    Connecting(ConnectFuture),
+    Connected(TcpStream),
+}
+
+/// A single TCP connection to one target. Outgoing frames are buffered until
+/// the connection is established and writable. Incoming data is buffered
+/// until a complete length-prefixed message was received.
+struct TcpConnection {
+    state: TcpConnectionState,
+
+    /// Length-prefixed frames which still need to be written
+    write_buf: Vec<u8>,
+
+    /// Received data which doesn't form a complete frame yet
+    read_buf: Vec<u8>,
+}
+
+impl TcpConnection {
+    fn new(target: SocketAddr) -> Self {
+        Self {
+            state: TcpConnectionState::Connecting(Box::pin(TcpStream::connect(target))),
+            write_buf: Vec::new(),
+            read_buf: Vec::new(),
+        }
+    }
+
+    /// Drives the connection attempt forward. Returns `Poll::Ready(Ok(()))`
+    /// once connected.
+    fn poll_connect(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let TcpConnectionState::Connecting(fut) = &mut self.state {
+            let stream = match fut.as_mut().poll(cx) {
+                Poll::Ready(Ok(stream)) => stream,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            self.state = TcpConnectionState::Connected(stream);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Writes all buffered frames. Returns `Poll::Ready(Ok(()))` once every
+    /// frame was written and flushed.
+    fn poll_write(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Poll::Ready(Err(err)) = self.poll_connect(cx) {
+            return Poll::Ready(Err(err));
+        }
+
+        let stream = match &mut self.state {
+            TcpConnectionState::Connected(stream) => stream,
+            TcpConnectionState::Connecting(_) => return Poll::Pending,
+        };
+
+        while !self.write_buf.is_empty() {
+            match Pin::new(&mut *stream).poll_write(cx, &self.write_buf) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                Poll::Ready(Ok(n)) => {
+                    self.write_buf.drain(..n);
+                }
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        Pin::new(stream).poll_flush(cx)
+    }
+
+    /// Reads the next complete message. Partial frames are kept in the read
+    /// buffer across polls. Returns `Poll::Ready(Ok(None))` once the remote
+    /// closed the connection.
+    fn poll_read_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Vec<u8>>>> {
+        loop {
+            if let Some(frame) = self.take_frame() {
+                return Poll::Ready(Ok(Some(frame)));
+            }
+
+            let stream = match &mut self.state {
+                TcpConnectionState::Connected(stream) => stream,
+                TcpConnectionState::Connecting(_) => return Poll::Pending,
+            };
+
+            let mut buffer = [0u8; 4096];
+            let mut buf = ReadBuf::new(&mut buffer);
+
+            match Pin::new(stream).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(_)) if buf.filled().is_empty() => return Poll::Ready(Ok(None)),
+                Poll::Ready(Ok(_)) => self.read_buf.extend_from_slice(buf.filled()),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    /// Removes the first complete frame (without the length prefix) from the
+    /// read buffer.
+    fn take_frame(&mut self) -> Option<Vec<u8>> {
+        if self.read_buf.len() < 2 {
+            return None;
+        }
+
+        let len = u16::from_be_bytes([self.read_buf[0], self.read_buf[1]]) as usize;
+        if self.read_buf.len() < len + 2 {
+            return None;
+        }
+
+        let frame = self.read_buf[2..len + 2].to_vec();
+        self.read_buf.drain(..len + 2);
+
+        Some(frame)
+    }
+}
+
+/// A [`TcpDnsTransport`] sends and receives DNS messages via TCP. Each
+/// message is prefixed with a two byte length field (RFC 1035 Section
+/// 4.2.2). Connections are opened lazily, one per target, and are reused for
+/// all following requests to the same target. Multiple requests are
+/// pipelined on one connection and responses can arrive out of order, which
+/// is fine as the multiplexer matches them by transaction ID (RFC 7766
+/// Section 6.2.1).
+///
+/// When the remote closes a connection, it is dropped and a new connection
+/// is opened by the next request to the same target.
+pub struct TcpDnsTransport {
+    connections: HashMap<SocketAddr, TcpConnection>,
+
+    /// The maximum number of frames buffered until [`Sink::poll_ready`]
+    /// reports backpressure. This limit is shared by all connections.
+    buffer_size: usize,
+
+    /// The number of frames currently buffered across all connections
+    buffered: usize,
+
+    /// Targets whose connection was closed. These are reported via the
+    /// stream, so that requests waiting for a response fail right away.
+    closed: VecDeque<SocketAddr>,
+
+    writer: WriteBuffer,
+}
+
+impl Transport for TcpDnsTransport {
+    type SinkError = TcpDnsTransportError;
+}
+
+impl Stream for TcpDnsTransport {
+    type Item = Result<(Message, usize, SocketAddr), TransportError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(target) = self.closed.pop_front() {
+            cx.waker().wake_by_ref();
+            return Poll::Ready(Some(Err(TransportError::ConnectionClosed(target))));
+        }
+
+        let mut closed = Vec::new();
+        let mut result = None;
+
+        for (target, connection) in self.connections.iter_mut() {
+            // Connections are established when flushing, but reading also
+            // has to wake up once the connection is ready.
+            if let Poll::Ready(Err(_)) = connection.poll_connect(cx) {
+                closed.push(*target);
+                continue;
+            }
+
+            match connection.poll_read_frame(cx) {
+                Poll::Ready(Ok(Some(frame))) => {
+                    result = Some((frame, *target));
+                    break;
+                }
+                Poll::Ready(Ok(None)) | Poll::Ready(Err(_)) => closed.push(*target),
+                Poll::Pending => {}
+            }
+        }
+
+        // Drop closed connections. They are reopened lazily on the next
+        // request to the same target.
+        for target in closed {
+            self.connections.remove(&target);
+            self.closed.push_back(target);
+        }
+
+        if result.is_none() && !self.closed.is_empty() {
+            cx.waker().wake_by_ref();
+        }
+
+        let (frame, source) = match result {
+            Some(result) => result,
+            None => return Poll::Pending,
+        };
+
+        // There might be more frames ready to be read
+        cx.waker().wake_by_ref();
+
+        let mut buf = ReadBuffer::new(&frame);
+
+        let header = match Header::read::<BigEndian>(&mut buf) {
+            Ok(header) => header,
+            Err(err) => return Poll::Ready(Some(Err(err.into()))),
+        };
+
+        match Message::read::<BigEndian>(&mut buf, header) {
+            Ok(msg) => Poll::Ready(Some(Ok((msg, frame.len(), source)))),
+            Err(err) => Poll::Ready(Some(Err(err.into()))),
+        }
+    }
+}
+
+impl Sink<Request> for TcpDnsTransport {
+    type Error = TcpDnsTransportError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.buffered < self.buffer_size {
+            return Poll::Ready(Ok(()));
+        }
+
+        // Try to make room by writing out the buffered frames
+        match self.as_mut().poll_flush(cx) {
+            Poll::Ready(Ok(_)) => Poll::Ready(Ok(())),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Request) -> Result<(), Self::Error> {
+        let this = &mut *self;
+
+        this.writer.clear();
+        if item.message.write_be(&mut this.writer).is_err() {
+            return Err(TcpDnsTransportError::WriteError);
+        }
+
+        let bytes = this.writer.bytes();
+        let len = u16::try_from(bytes.len())
+            .map_err(|_| TcpDnsTransportError::MessageTooLong(bytes.len()))?;
+
+        let connection = this
+            .connections
+            .entry(item.target_socket_addr)
+            .or_insert_with(|| TcpConnection::new(item.target_socket_addr));
+
+        connection.write_buf.extend_from_slice(&len.to_be_bytes());
+        connection.write_buf.extend_from_slice(bytes);
+        this.buffered += 1;
+
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut pending = false;
+        let mut error = None;
+        let mut closed = Vec::new();
+
+        for (target, connection) in self.connections.iter_mut() {
+            match connection.poll_write(cx) {
+                Poll::Ready(Ok(_)) => {}
+                Poll::Ready(Err(err)) => {
+                    closed.push(*target);
+                    error = Some(err);
+                }
+                Poll::Pending => pending = true,
+            }
+        }
+
+        for target in closed {
+            self.connections.remove(&target);
+            self.closed.push_back(target);
+        }
+
+        if pending {
+            return Poll::Pending;
+        }
+
+        self.buffered = 0;
+
+        match error {
+            Some(err) => Poll::Ready(Err(err.into())),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.as_mut().poll_flush(cx) {
+            Poll::Ready(Ok(_)) => {}
+            result => return result,
+        }
+
+        for connection in self.connections.values_mut() {
+            if let TcpConnectionState::Connected(stream) = &mut connection.state {
+                if let Poll::Pending = Pin::new(stream).poll_shutdown(cx) {
+                    return Poll::Pending;
+                }
+            }
+        }
+
+        self.connections.clear();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl TcpDnsTransport {
+    /// Creates a new TCP DNS transport. Connections to targets are opened
+    /// lazily when the first message to a target is sent. At most
+    /// `buffer_size` messages are buffered across all targets before they
+    /// have to be flushed.
+    pub fn new(buffer_size: usize) -> Self {
+        Self {
+            connections: HashMap::new(),
+            closed: VecDeque::new(),
+            writer: WriteBuffer::new(),
+            buffer_size,
+            buffered: 0,
         }
     }
 }
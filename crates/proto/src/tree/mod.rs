@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use thiserror::Error;
 
-use crate::{Label, Name, Record};
+use crate::{Label, Name, RType, Record};
 
 #[derive(Debug, Error)]
 pub enum TreeError {
@@ -106,6 +106,62 @@ impl Tree {
         self.find_node_by_index(index)
     }
 
+    /// Walks the tree from the root towards the domain `name` and returns
+    /// the closest encloser, the matching wildcard node and the zone cut
+    /// on the way. Labels are compared as stored, so names have to use the
+    /// same case as the inserted ones. See [`TreeLookup`] for details.
+    ///
+    /// ### Example
+    ///
+    /// ```ignore
+    /// let lookup = tree.lookup(&Name::try_from("www.example.com").unwrap());
+    ///
+    /// if !lookup.exists() {
+    ///     let wildcard = lookup.wildcard;
+    /// }
+    /// ```
+    pub fn lookup(&self, name: &Name) -> TreeLookup<'_> {
+        let mut current = self.root();
+        let mut depth = 0;
+        let mut zone_cut = None;
+
+        for label in name.labels_rev() {
+            if current.is_zone_cut() {
+                zone_cut = Some((name.ancestor(depth), current));
+            }
+
+            current = match current.nodes.get(&label) {
+                Some(index) => &self.nodes[*index],
+                None => break,
+            };
+            depth += 1;
+        }
+
+        let exists = depth == name.num_labels();
+
+        if exists && current.is_zone_cut() {
+            zone_cut = Some((name.clone(), current));
+        }
+
+        // Wildcards only match names which don't exist. See RFC 4592
+        // Section 3.3.1.
+        let wildcard = match exists {
+            true => None,
+            false => current
+                .nodes
+                .get(&Label::wildcard())
+                .map(|index| &self.nodes[*index]),
+        };
+
+        TreeLookup {
+            encloser: name.ancestor(depth),
+            encloser_node: current,
+            exists,
+            wildcard,
+            zone_cut,
+        }
+    }
+
     /// Finds a node by `index` and returns a reference to it.
     pub fn find_node_by_index(&self, index: usize) -> Option<&Node<Record>> {
         self.nodes.get(index)
@@ -116,6 +172,43 @@ impl Tree {
         self.nodes.get_mut(index)
     }
 
+    /// Returns all nodes with their domain name in canonical order as
+    /// described in [RFC 4034 Section 6.1](https://datatracker.ietf.org/doc/html/rfc4034#section-6.1).
+    /// Names are sorted by their labels starting with the most significant
+    /// one. Labels are compared as lowercase octet strings and parents come
+    /// before their children.
+    pub fn canonical_nodes(&self) -> Vec<(Name, &Node<Record>)> {
+        let mut nodes = Vec::new();
+        self.collect_canonical(0, &mut Vec::new(), &mut nodes);
+        nodes
+    }
+
+    fn collect_canonical<'a>(
+        &'a self,
+        index: usize,
+        labels: &mut Vec<Label>,
+        nodes: &mut Vec<(Name, &'a Node<Record>)>,
+    ) {
+        let node = &self.nodes[index];
+
+        // Labels are collected from the root downwards, names store them
+        // the other way around.
+        let name_labels: Vec<Label> = labels.iter().rev().cloned().collect();
+        nodes.push((
+            Name::try_from(name_labels.as_slice()).unwrap_or_default(),
+            node,
+        ));
+
+        let mut children: Vec<(&Label, &usize)> = node.nodes.iter().collect();
+        children.sort_by_key(|(label, _)| label.0.to_ascii_lowercase());
+
+        for (label, child) in children {
+            labels.push(label.clone());
+            self.collect_canonical(*child, labels, nodes);
+            labels.pop();
+        }
+    }
+
     fn add_new_node(&mut self, parent_index: usize) -> usize {
         let index = self.nodes.len();
 
@@ -142,6 +235,49 @@ impl Tree {
     }
 }
 
+/// The result of [`Tree::lookup`].
+#[derive(Debug)]
+pub struct TreeLookup<'a> {
+    /// The closest encloser, which is the deepest existing node on the path
+    /// to the name. This is the name itself if it exists. See
+    /// [RFC 4592 Section 3.3.1](https://datatracker.ietf.org/doc/html/rfc4592#section-3.3.1).
+    pub encloser: Name,
+    pub encloser_node: &'a Node<Record>,
+
+    /// The `*` child of the closest encloser. This is always `None` if the
+    /// name exists.
+    pub wildcard: Option<&'a Node<Record>>,
+
+    /// The deepest node on the path to the name (including the name itself)
+    /// holding NS records.
+    pub zone_cut: Option<(Name, &'a Node<Record>)>,
+
+    exists: bool,
+}
+
+impl<'a> TreeLookup<'a> {
+    /// Returns if the name has a node in the tree. The node can be an empty
+    /// non-terminal.
+    pub fn exists(&self) -> bool {
+        self.exists
+    }
+
+    /// Returns if the name exists, but only has children and no records of
+    /// its own. See
+    /// [RFC 8020 Section 2](https://datatracker.ietf.org/doc/html/rfc8020#section-2).
+    pub fn is_empty_non_terminal(&self) -> bool {
+        self.exists && !self.encloser_node.has_records() && self.encloser_node.has_children()
+    }
+
+    /// Returns the node of the name if it exists.
+    pub fn node(&self) -> Option<&'a Node<Record>> {
+        match self.exists {
+            true => Some(self.encloser_node),
+            false => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Node<T> {
     nodes: HashMap<Label, usize>,
@@ -155,7 +291,21 @@ impl<T> Node<T> {
         &self.records
     }
 
-    pub fn children(&self) {}
+    /// Returns the labels and indices of all direct children of this node.
+    /// Use [`Tree::find_node_by_index`] to resolve the indices.
+    pub fn children(&self) -> impl Iterator<Item = (&Label, usize)> {
+        self.nodes.iter().map(|(label, index)| (label, *index))
+    }
+
+    /// Returns the index of this node in the tree.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the index of the parent node or `None` if this is the root.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
 
     pub fn is_root(&self) -> bool {
         self.parent.is_none()
@@ -181,3 +331,11 @@ impl<T> Node<T> {
         self.records.push(record)
     }
 }
+
+impl Node<Record> {
+    /// Returns if the node holds NS records, which marks a zone cut (or the
+    /// apex of a zone).
+    pub fn is_zone_cut(&self) -> bool {
+        self.records.iter().any(|r| *r.header().ty() == RType::NS)
+    }
+}
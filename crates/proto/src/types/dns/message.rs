@@ -11,9 +11,9 @@ use snafu::{ResultExt, Snafu};
 use crate::{
     constants,
     types::{
-        dns::{Header, HeaderError, Question, QuestionError},
+        dns::{CompressionMap, Header, HeaderError, Question, QuestionError},
         rcode::Rcode,
-        rr::{RData, Record, RecordError, SOA},
+        rr::{RData, Record, RecordError, OPT, SOA},
     },
 };
 
@@ -86,17 +86,12 @@ impl Display for Message {
 impl Writeable for Message {
     type Error = MessageError;
 
+    /// Writes the message to `buf`. All domain names are compressed. Use
+    /// [`Message::write_canonical`] to write the message without any name
+    /// compression.
     fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
-        let n = bytes_written! {
-            self.header.write::<E>(buf).context(WriteHeaderSnafu)?;
-
-            self.question.write::<E>(buf).context(WriteQuestionSnafu)?;
-            self.answers.write::<E>(buf).context(WriteAnswersSnafu)?;
-            self.authorities.write::<E>(buf).context(WriteAuthoritiesSnafu)?;
-            self.additionals.write::<E>(buf).context(WriteAdditionalsSnafu)?
-        };
-
-        Ok(n)
+        let mut names = CompressionMap::new(buf);
+        self.write_compressed::<E>(buf, &mut names)
     }
 }
 
@@ -255,6 +250,16 @@ impl Message {
         self.header.rec_avail = avail;
     }
 
+    /// Returns if the message is an authoritative answer (AA bit).
+    pub fn is_authoritative(&self) -> bool {
+        self.header.authoritative
+    }
+
+    /// Set if the message is an authoritative answer (AA bit).
+    pub fn set_authoritative(&mut self, authoritative: bool) {
+        self.header.authoritative = authoritative;
+    }
+
     /// Returns QDCOUNT stored in the DNS message header.
     pub fn qdcount(&self) -> u16 {
         self.header.qdcount
@@ -337,11 +342,244 @@ impl Message {
         false
     }
 
+    /// Returns the OPT pseudo-RR in the additional section. A message
+    /// contains at most one OPT RR. See RFC 6891 Section 6.1.1.
+    pub fn opt(&self) -> Option<&OPT> {
+        self.additionals
+            .iter()
+            .rev()
+            .find_map(|record| match record.rdata() {
+                RData::OPT(opt) => Some(opt),
+                _ => None,
+            })
+    }
+
+    /// Sets the OPT pseudo-RR of the message. Any existing OPT RR is
+    /// replaced. This updates the ARCOUNT in the DNS header.
+    pub fn set_opt(&mut self, opt: OPT) {
+        self.remove_opt();
+        self.add_additional(opt.into_record());
+    }
+
+    /// Removes and returns the OPT pseudo-RR of the message. This updates the
+    /// ARCOUNT in the DNS header.
+    pub fn remove_opt(&mut self) -> Option<OPT> {
+        let index = self.additionals.iter().position(|r| r.is_edns())?;
+        let record = self.additionals.remove(index);
+        self.header.arcount -= 1;
+
+        match record.rdata() {
+            RData::OPT(opt) => Some(opt.clone()),
+            _ => None,
+        }
+    }
+
+    /// Modifies the OPT pseudo-RR via `f`. If the message doesn't contain an
+    /// OPT RR yet, a new one is added.
+    fn update_opt(&mut self, f: impl FnOnce(&mut OPT)) {
+        let mut opt = self
+            .remove_opt()
+            .unwrap_or_else(|| OPT::new(constants::MIN_MESSAGE_SIZE as u16));
+
+        f(&mut opt);
+        self.set_opt(opt)
+    }
+
+    /// Returns the UDP payload size advertised via EDNS. Values below 512
+    /// octets are treated as 512 octets. Returns `None` if the message
+    /// doesn't contain an OPT record. See RFC 6891 Section 6.2.3.
+    pub fn udp_payload_size(&self) -> Option<u16> {
+        self.opt().map(|opt| {
+            opt.header()
+                .sender_payload_size()
+                .max(constants::MIN_MESSAGE_SIZE as u16)
+        })
+    }
+
+    /// Sets the UDP payload size advertised via EDNS. This adds an OPT
+    /// record if required.
+    pub fn set_udp_payload_size(&mut self, size: u16) {
+        self.update_opt(|opt| opt.header_mut().set_sender_payload_size(size))
+    }
+
+    /// Returns the EDNS version of the message or `None` if the message
+    /// doesn't contain an OPT record.
+    pub fn edns_version(&self) -> Option<u8> {
+        self.opt().map(|opt| opt.header().version())
+    }
+
+    /// Sets the EDNS version. This adds an OPT record if required.
+    pub fn set_edns_version(&mut self, version: u8) {
+        self.update_opt(|opt| opt.header_mut().set_version(version))
+    }
+
+    /// Returns if the DNSSEC OK (DO) bit is set. This is always `false` if
+    /// the message doesn't contain an OPT record.
+    pub fn dnssec_ok(&self) -> bool {
+        self.opt().is_some_and(|opt| opt.header().dnssec_ok())
+    }
+
+    /// Sets the DNSSEC OK (DO) bit. This adds an OPT record if required.
+    pub fn set_dnssec_ok(&mut self, dnssec_ok: bool) {
+        self.update_opt(|opt| opt.header_mut().set_dnssec_ok(dnssec_ok))
+    }
+
+    /// Returns the extended 12-bit RCODE, which combines the RCODE in the DNS
+    /// header with the upper 8 bits stored in the OPT record. Returns the
+    /// plain RCODE if the message doesn't contain an OPT record.
+    pub fn extended_rcode(&self) -> Rcode {
+        let upper = match self.opt() {
+            Some(opt) => opt.header().upper_ext_rcode() as u16,
+            None => return self.header.rcode,
+        };
+
+        Rcode::from((upper << 4) | (u16::from(self.header.rcode) & 0xF))
+    }
+
+    /// Sets the extended 12-bit RCODE. The lower 4 bits are stored in the DNS
+    /// header. If the upper 8 bits are non-zero, they are stored in the OPT
+    /// record, which is added if required.
+    pub fn set_extended_rcode(&mut self, rcode: Rcode) {
+        let code = u16::from(rcode);
+        self.header.rcode = Rcode::from(code & 0xF);
+
+        let upper = (code >> 4) as u8;
+        if upper != 0 || self.opt().is_some() {
+            self.update_opt(|opt| opt.header_mut().set_upper_ext_rcode(upper))
+        }
+    }
+
     /// Sets the RCODE of the message
     pub fn set_rcode(&mut self, rcode: Rcode) {
         self.header.rcode = rcode
     }
 
+    /// Returns the RCODE of the message
+    pub fn rcode(&self) -> Rcode {
+        self.header.rcode
+    }
+
+    /// Returns if the message is truncated (TC bit). Clients should retry the
+    /// query via TCP. See RFC 7766 Section 5.
+    pub fn is_truncated(&self) -> bool {
+        self.header.truncated
+    }
+
+    /// Sets the TC bit of the message
+    pub fn set_truncated(&mut self, truncated: bool) {
+        self.header.truncated = truncated
+    }
+
+    /// Writes the message to `buf` and compresses all domain names by using
+    /// the provided [`CompressionMap`]. The map should be created right
+    /// before the message is written, as all offsets are relative to the
+    /// start of the message.
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, MessageError> {
+        let mut n = self.header.write::<E>(buf).context(WriteHeaderSnafu)?;
+
+        for question in &self.question {
+            n += question
+                .write_compressed::<E>(buf, names)
+                .context(WriteQuestionSnafu)?;
+        }
+
+        for answer in &self.answers {
+            n += answer
+                .write_compressed::<E>(buf, names)
+                .context(WriteAnswersSnafu)?;
+        }
+
+        for authority in &self.authorities {
+            n += authority
+                .write_compressed::<E>(buf, names)
+                .context(WriteAuthoritiesSnafu)?;
+        }
+
+        for additional in &self.additionals {
+            n += additional
+                .write_compressed::<E>(buf, names)
+                .context(WriteAdditionalsSnafu)?;
+        }
+
+        Ok(n)
+    }
+
+    /// Writes the message to `buf` without compressing any domain names.
+    /// This canonical form is required when computing hashes or signatures
+    /// (DNSSEC) over (parts of) the message.
+    pub fn write_canonical<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+    ) -> Result<usize, MessageError> {
+        let n = bytes_written! {
+            self.header.write::<E>(buf).context(WriteHeaderSnafu)?;
+
+            self.question.write::<E>(buf).context(WriteQuestionSnafu)?;
+            self.answers.write::<E>(buf).context(WriteAnswersSnafu)?;
+            self.authorities.write::<E>(buf).context(WriteAuthoritiesSnafu)?;
+            self.additionals.write::<E>(buf).context(WriteAdditionalsSnafu)?
+        };
+
+        Ok(n)
+    }
+
+    /// Truncates the message until the compressed wire format fits into
+    /// `max_size` octets. Additional records are dropped first, which
+    /// doesn't require setting the TC bit. OPT records are always kept.
+    /// After that, complete RRsets are removed from the end of the authority
+    /// and answer section and the TC bit is set. Returns if the TC bit was
+    /// set. See RFC 2181 Section 9.
+    pub fn truncate<E: Endianness>(&mut self, max_size: usize) -> Result<bool, MessageError> {
+        if self.wire_size::<E>()? <= max_size {
+            return Ok(false);
+        }
+
+        // Dropping additional records doesn't truncate the message, because
+        // they are not required to answer the query.
+        self.additionals.retain(|record| record.is_edns());
+        self.header.arcount = self.additionals.len() as u16;
+
+        let mut size = self.wire_size::<E>()?;
+        if size <= max_size {
+            return Ok(false);
+        }
+
+        // Compression makes a dropped RRset save at most its uncompressed
+        // size. The message is only written again once the estimate fits,
+        // to get the exact compressed size.
+        while size > max_size {
+            let dropped = if !self.authorities.is_empty() {
+                let dropped = drop_last_rrset(&mut self.authorities);
+                self.header.nscount = self.authorities.len() as u16;
+                dropped
+            } else if !self.answers.is_empty() {
+                let dropped = drop_last_rrset(&mut self.answers);
+                self.header.ancount = self.answers.len() as u16;
+                dropped
+            } else {
+                break;
+            };
+
+            size = size.saturating_sub(dropped);
+            if size <= max_size {
+                size = self.wire_size::<E>()?;
+            }
+        }
+
+        self.header.truncated = true;
+        Ok(true)
+    }
+
+    /// Returns the number of octets of the compressed wire format.
+    fn wire_size<E: Endianness>(&self) -> Result<usize, MessageError> {
+        let mut buf = WriteBuffer::new();
+        self.write::<E>(&mut buf)
+    }
+
     /// Read the complete DNS [`Message`] based on the already unpacked [`Header`].
     pub fn read<E: Endianness>(buf: &mut ReadBuffer, header: Header) -> Result<Self, MessageError> {
         let mut message = Self::new_with_header(header);
@@ -366,6 +604,33 @@ impl Message {
     }
 }
 
+/// Removes all records of the RRset the last record in `records` belongs to.
+/// Returns the uncompressed size of the removed records.
+fn drop_last_rrset(records: &mut Vec<Record>) -> usize {
+    let last = match records.last() {
+        Some(last) => last.header().clone(),
+        None => return 0,
+    };
+
+    let name = last.name().to_lowercase();
+    let mut dropped = 0;
+
+    records.retain(|record| {
+        let header = record.header();
+        let keep = !(header.ty() == last.ty()
+            && header.class() == last.class()
+            && header.name().to_lowercase() == name);
+
+        if !keep {
+            dropped += header.size() + record.size();
+        }
+
+        keep
+    });
+
+    dropped
+}
+
 fn read_questions<E: Endianness>(
     buf: &mut ReadBuffer,
     count: u16,
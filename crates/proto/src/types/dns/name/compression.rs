@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use binbuf::write::WriteBuffer;
+
+use crate::{constants::COMP_PTR_MASK, types::dns::Label};
+
+/// A [`CompressionMap`] keeps track of domain names (and their suffixes)
+/// already written to the wire during a single [`Message`][m] write pass.
+/// Later occurrences of the same suffix are replaced by a compression pointer
+/// to the first occurrence.
+///
+/// All offsets are relative to the start of the DNS message, which doesn't
+/// need to be the start of the [`WriteBuffer`] (e.g. when a TCP length prefix
+/// was written beforehand).
+///
+/// ### See
+///
+/// - <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4>
+///
+/// [m]: crate::types::dns::Message
+#[derive(Debug, Default)]
+pub struct CompressionMap {
+    /// Maps lowercased label sequences to their offset in the message.
+    offsets: HashMap<Vec<Label>, u16>,
+
+    /// The offset of the start of the message in the underlying buffer.
+    start: usize,
+
+    /// An additional offset added to positions in the buffer. This is used
+    /// when names are written into a temporary buffer (e.g. RDATA, which
+    /// needs to be written before RDLEN is known).
+    base: usize,
+}
+
+impl CompressionMap {
+    /// Creates a new [`CompressionMap`] for a message which starts at the
+    /// current end of `buf`.
+    pub fn new(buf: &WriteBuffer) -> Self {
+        Self {
+            offsets: HashMap::new(),
+            start: buf.len(),
+            base: 0,
+        }
+    }
+
+    /// Returns the offset of the write position in `buf` relative to the
+    /// start of the message.
+    pub fn position(&self, buf: &WriteBuffer) -> usize {
+        self.base + buf.len() - self.start
+    }
+
+    /// Rebases the map onto a temporary buffer whose first byte will end up
+    /// at message offset `base`. The previous `(start, base)` pair is
+    /// returned and has to be restored via [`CompressionMap::restore`] once
+    /// the temporary buffer was appended to the message buffer.
+    pub fn rebase(&mut self, base: usize) -> (usize, usize) {
+        let previous = (self.start, self.base);
+
+        self.start = 0;
+        self.base = base;
+
+        previous
+    }
+
+    /// Restores a `(start, base)` pair previously returned by
+    /// [`CompressionMap::rebase`].
+    pub fn restore(&mut self, previous: (usize, usize)) {
+        (self.start, self.base) = previous;
+    }
+
+    /// Looks up the offset of a previously written label sequence.
+    pub fn find(&self, labels: &[Label]) -> Option<u16> {
+        self.offsets.get(&Self::key(labels)).copied()
+    }
+
+    /// Remembers the message `offset` of a label sequence. Offsets which
+    /// cannot be expressed by a 14 bit compression pointer are ignored.
+    pub fn insert(&mut self, labels: &[Label], offset: usize) {
+        if offset > COMP_PTR_MASK as usize {
+            return;
+        }
+
+        self.offsets
+            .entry(Self::key(labels))
+            .or_insert(offset as u16);
+    }
+
+    /// Returns the number of remembered label sequences.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns if no label sequences were remembered yet.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Domain name comparisons are case-insensitive, so we always use the
+    /// lowercased labels as the key.
+    fn key(labels: &[Label]) -> Vec<Label> {
+        labels
+            .iter()
+            .map(|l| Label(l.0.to_ascii_lowercase()))
+            .collect()
+    }
+}
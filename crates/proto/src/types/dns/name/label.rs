@@ -68,6 +68,37 @@ impl Label {
         Self::default()
     }
 
+    /// Returns the wildcard label `*`. See
+    /// [RFC 4592 Section 2.1.1](https://datatracker.ietf.org/doc/html/rfc4592#section-2.1.1).
+    pub fn wildcard() -> Self {
+        Self(vec![b'*'])
+    }
+
+    pub fn is_wildcard(&self) -> bool {
+        self.0 == b"*"
+    }
+
+    /// Returns the label in presentation format as used in master files.
+    /// Characters with a special meaning are escaped as `\X`, all other
+    /// non-printable octets as `\DDD`. See
+    /// [RFC 4343 Section 2.1](https://datatracker.ietf.org/doc/html/rfc4343#section-2.1).
+    pub fn escaped(&self) -> String {
+        let mut s = String::with_capacity(self.0.len());
+
+        for &b in &self.0 {
+            match b {
+                b'.' | b'\\' | b'"' | b'(' | b')' | b';' | b'@' | b'$' => {
+                    s.push('\\');
+                    s.push(b as char);
+                }
+                0x21..=0x7e => s.push(b as char),
+                _ => s.push_str(&format!("\\{b:03}")),
+            }
+        }
+
+        s
+    }
+
     // TODO (Techassi): This ideally should not clone, but we need to introduce
     // lifetimes across Label, Name and types using Name, e.g. Question
     pub fn bytes(&self) -> Vec<u8> {
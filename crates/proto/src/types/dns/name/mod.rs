@@ -1,8 +1,8 @@
-use std::{fmt::Display, str::FromStr};
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
 use binbuf::{
     read::{ReadBuffer, ReadError, Readable},
-    write::{WriteBuffer, Writeable},
+    write::{WriteBuffer, WriteError, Writeable},
     Endianness,
 };
 use serde::Serialize;
@@ -10,7 +10,10 @@ use snafu::{ensure, ResultExt, Snafu};
 
 use crate::constants::{COMP_PTR, COMP_PTR_MASK, MAX_DOMAIN_LENGTH, MAX_LABEL_LENGTH};
 
+mod compression;
 mod label;
+
+pub use compression::*;
 pub use label::*;
 
 #[derive(Debug, Default)]
@@ -42,6 +45,9 @@ pub enum NameError {
     #[snafu(display("failed to read compression pointer"))]
     ReadPointer { source: ReadError },
 
+    #[snafu(display("failed to write compression pointer"))]
+    WritePointer { source: WriteError },
+
     #[snafu(display("domain name to long (< {})", MAX_DOMAIN_LENGTH))]
     DomainNameTooLong,
 }
@@ -50,9 +56,15 @@ pub enum NameError {
 pub enum NameParseError {
     #[snafu(display("input contains non-ASCII characters"))]
     NonAscii,
+
+    #[snafu(display("invalid domain name"))]
+    InvalidName { source: NameError },
+
+    #[snafu(display("invalid escape sequence"))]
+    InvalidEscape,
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Name {
     labels: Vec<Label>,
 }
@@ -162,7 +174,8 @@ impl Writeable for Name {
         let buffer_len_start = buf.len();
         let mut n = 0;
 
-        // TODO (Techassi): This does NOT handle compression. Add it
+        // Names are written uncompressed. Use Name::write_compressed when
+        // writing messages, which compresses names via a CompressionMap.
         for label in self.iter() {
             let label_len = label.len();
             ensure!(label_len <= MAX_LABEL_LENGTH.into(), WriteLabelTooLongSnafu);
@@ -183,6 +196,50 @@ impl Writeable for Name {
     }
 }
 
+impl Name {
+    /// Writes the domain name to `buf` and compresses it by using the
+    /// provided [`CompressionMap`]. The longest suffix of this name which was
+    /// already written to the message is replaced by a compression pointer.
+    /// All suffixes which are newly written are added to the map, so that
+    /// following names can point to them.
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, NameError> {
+        let mut n = 0;
+
+        for (index, label) in self.iter().enumerate() {
+            let suffix = &self.labels[index..];
+
+            // The remaining labels were already written, so we can point to
+            // them and we are done.
+            if let Some(offset) = names.find(suffix) {
+                let pointer = (COMP_PTR as u16) << 8 | offset;
+                n += pointer.write::<E>(buf).context(WritePointerSnafu)?;
+
+                return Ok(n);
+            }
+
+            let label_len = label.len();
+            ensure!(label_len <= MAX_LABEL_LENGTH.into(), WriteLabelTooLongSnafu);
+
+            let offset = names.position(buf);
+            names.insert(suffix, offset);
+
+            buf.push(label_len as u8);
+            n += buf.write(&mut label.bytes()) + 1;
+        }
+
+        // Terminating null byte
+        buf.push(0);
+        n += 1;
+
+        ensure!(n <= MAX_DOMAIN_LENGTH.into(), DomainNameTooLongSnafu);
+        Ok(n)
+    }
+}
+
 impl FromStr for Name {
     type Err = NameParseError;
 
@@ -297,6 +354,84 @@ impl Name {
         Ok(())
     }
 
+    /// Parses a domain name in presentation format as used in master files.
+    /// Names which don't end with an unescaped dot are relative to `origin`,
+    /// and `@` denotes the origin itself. Labels can contain `\X` and
+    /// `\DDD` escape sequences, see [`Label::escaped`].
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use portal::types::dns::Name;
+    ///
+    /// let origin = Name::try_from("example.com.").unwrap();
+    ///
+    /// let n = Name::from_relative("www", &origin).unwrap();
+    /// assert_eq!(n.as_dotted_string(), String::from("www.example.com."));
+    ///
+    /// let n = Name::from_relative("@", &origin).unwrap();
+    /// assert_eq!(n, origin);
+    /// ```
+    pub fn from_relative(input: &str, origin: &Name) -> Result<Self, NameParseError> {
+        if input == "@" {
+            return Ok(origin.clone());
+        }
+
+        ensure!(input.is_ascii(), NonAsciiSnafu);
+
+        let mut name = Self::default();
+        let mut label = Vec::new();
+        let mut absolute = false;
+        let mut iter = input.bytes();
+
+        while let Some(b) = iter.next() {
+            absolute = false;
+
+            match b {
+                b'.' => {
+                    if !label.is_empty() {
+                        name.add_label(Label(std::mem::take(&mut label)))
+                            .context(InvalidNameSnafu)?;
+                    }
+
+                    absolute = true;
+                }
+                b'\\' => match iter.next() {
+                    Some(d) if d.is_ascii_digit() => {
+                        let mut value = (d - b'0') as u16;
+
+                        for _ in 0..2 {
+                            match iter.next() {
+                                Some(d) if d.is_ascii_digit() => {
+                                    value = value * 10 + (d - b'0') as u16
+                                }
+                                _ => return InvalidEscapeSnafu.fail(),
+                            }
+                        }
+
+                        ensure!(value <= u8::MAX as u16, InvalidEscapeSnafu);
+                        label.push(value as u8);
+                    }
+                    Some(c) => label.push(c),
+                    None => return InvalidEscapeSnafu.fail(),
+                },
+                _ => label.push(b),
+            }
+        }
+
+        if !label.is_empty() {
+            name.add_label(Label(label)).context(InvalidNameSnafu)?;
+        }
+
+        if !absolute {
+            for label in origin.iter() {
+                name.add_label(label.clone()).context(InvalidNameSnafu)?;
+            }
+        }
+
+        Ok(name)
+    }
+
     /// Returns a reference to the underlying vector of labels with default
     /// ordering.
     pub fn labels(&self) -> &Vec<Label> {
@@ -402,7 +537,157 @@ impl Name {
         self.labels.is_empty()
     }
 
-    /// Returns the domain as a dotted string.
+    /// Returns a copy of the domain name with all ASCII letters converted to
+    /// lowercase. Domain name comparisons are case-insensitive, so this is
+    /// useful when names are used as keys.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use portal::types::dns::Name;
+    ///
+    /// let n = Name::try_from("WWW.Example.COM").unwrap();
+    /// assert_eq!(n.to_lowercase().as_dotted_string(), String::from("www.example.com."));
+    /// ```
+    pub fn to_lowercase(&self) -> Self {
+        Self {
+            labels: self
+                .labels
+                .iter()
+                .map(|l| Label(l.0.to_ascii_lowercase()))
+                .collect(),
+        }
+    }
+
+    /// Compares two domain names in canonical order as described in
+    /// [RFC 4034 Section 6.1](https://datatracker.ietf.org/doc/html/rfc4034#section-6.1).
+    /// Names are compared label by label starting with the most significant
+    /// one, labels are compared as lowercase octet strings.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use portal::types::dns::Name;
+    ///
+    /// let a = Name::try_from("Z.a.example").unwrap();
+    /// let b = Name::try_from("zABC.a.EXAMPLE").unwrap();
+    /// assert_eq!(a.cmp_canonical(&b), Ordering::Less);
+    /// ```
+    pub fn cmp_canonical(&self, other: &Self) -> Ordering {
+        let a = self.labels.iter().rev().map(|l| l.0.to_ascii_lowercase());
+        let b = other.labels.iter().rev().map(|l| l.0.to_ascii_lowercase());
+
+        a.cmp(b)
+    }
+
+    /// Returns if the domain name is equal to or below `other`. The
+    /// comparison is case-insensitive. Every name is a subdomain of the root.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use portal::types::dns::Name;
+    ///
+    /// let n = Name::try_from("www.Example.com").unwrap();
+    /// assert!(n.is_subdomain_of(&Name::try_from("example.com").unwrap()));
+    /// assert!(!n.is_subdomain_of(&Name::try_from("ample.com").unwrap()));
+    /// ```
+    pub fn is_subdomain_of(&self, other: &Self) -> bool {
+        if other.num_labels() > self.num_labels() {
+            return false;
+        }
+
+        self.labels
+            .iter()
+            .rev()
+            .zip(other.labels.iter().rev())
+            .all(|(a, b)| a.0.eq_ignore_ascii_case(&b.0))
+    }
+
+    /// Returns the ancestor of this domain name which consists of the last
+    /// `num_labels` labels. The name itself is returned if it doesn't have
+    /// more labels.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use portal::types::dns::Name;
+    ///
+    /// let n = Name::try_from("www.example.com").unwrap();
+    /// assert_eq!(n.ancestor(2).as_dotted_string(), String::from("example.com."));
+    /// assert!(n.ancestor(0).is_root());
+    /// ```
+    pub fn ancestor(&self, num_labels: usize) -> Self {
+        let skip = self.num_labels().saturating_sub(num_labels);
+
+        Self {
+            labels: self.labels[skip..].to_vec(),
+        }
+    }
+
+    /// Returns the parent of this domain name or `None` if this is the root.
+    pub fn parent(&self) -> Option<Self> {
+        if self.is_root() {
+            return None;
+        }
+
+        Some(self.ancestor(self.num_labels() - 1))
+    }
+
+    /// Returns the wildcard name directly below this domain name, e.g.
+    /// `*.example.com` for `example.com`.
+    pub fn to_wildcard(&self) -> Self {
+        let mut labels = Vec::with_capacity(self.labels.len() + 1);
+        labels.push(Label::wildcard());
+        labels.extend_from_slice(&self.labels);
+
+        Self { labels }
+    }
+
+    /// Returns if the first label is the wildcard label `*`.
+    pub fn is_wildcard(&self) -> bool {
+        self.labels.first().is_some_and(Label::is_wildcard)
+    }
+
+    /// Replaces the ancestor `suffix` of this domain name with `target`,
+    /// which is the substitution done for DNAME records. Returns `None` if
+    /// `suffix` is not a proper ancestor or if the result is too long. See
+    /// [RFC 6672 Section 2.2](https://datatracker.ietf.org/doc/html/rfc6672#section-2.2).
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use portal::types::dns::Name;
+    ///
+    /// let n = Name::try_from("www.example.com").unwrap();
+    /// let suffix = Name::try_from("example.com").unwrap();
+    /// let target = Name::try_from("example.net").unwrap();
+    ///
+    /// let replaced = n.replace_suffix(&suffix, &target).unwrap();
+    /// assert_eq!(replaced, Name::try_from("www.example.net").unwrap());
+    /// assert!(suffix.replace_suffix(&suffix, &target).is_none());
+    /// ```
+    pub fn replace_suffix(&self, suffix: &Self, target: &Self) -> Option<Self> {
+        if self.num_labels() <= suffix.num_labels() || !self.is_subdomain_of(suffix) {
+            return None;
+        }
+
+        let prefix = &self.labels[..self.num_labels() - suffix.num_labels()];
+
+        let mut labels = prefix.to_vec();
+        labels.extend_from_slice(&target.labels);
+
+        let name = Self { labels };
+        match name.size() <= MAX_DOMAIN_LENGTH.into() {
+            true => Some(name),
+            false => None,
+        }
+    }
+
+    /// Returns the domain as a dotted string. Labels are escaped, see
+    /// [`Label::escaped`], so the result can be parsed again via
+    /// [`Name::from_relative`].
     ///
     /// ### Example
     ///
@@ -419,7 +704,7 @@ impl Name {
 
         self.iter()
             .map(|l| {
-                let mut label = l.to_string();
+                let mut label = l.escaped();
                 label.push('.');
                 label
             })
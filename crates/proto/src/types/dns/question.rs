@@ -11,7 +11,7 @@ use snafu::{ResultExt, Snafu};
 use crate::{
     constants,
     types::{
-        dns::{Name, NameError, Query},
+        dns::{CompressionMap, Name, NameError, Query},
         rr::{Class, RType},
     },
 };
@@ -57,6 +57,16 @@ impl Display for Question {
     }
 }
 
+impl Question {
+    /// Returns if this question matches `other`. Domain names are compared
+    /// case-insensitive, see RFC 4343.
+    pub fn matches(&self, other: &Question) -> bool {
+        self.ty == other.ty
+            && self.class == other.class
+            && self.name.to_lowercase() == other.name.to_lowercase()
+    }
+}
+
 impl From<Query> for Question {
     fn from(q: Query) -> Self {
         Question {
@@ -98,6 +108,22 @@ impl Question {
         Self { name, ty, class }
     }
 
+    /// Writes the question to `buf` and compresses QNAME by using the
+    /// provided [`CompressionMap`].
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, QuestionError> {
+        let n = bytes_written! {
+            self.name.write_compressed::<E>(buf, names).context(WriteNameSnafu)?;
+            self.ty.write::<E>(buf).context(WriteTypeSnafu)?;
+            self.class.write::<E>(buf).context(WriteClassSnafu)?
+        };
+
+        Ok(n)
+    }
+
     /// Returns the size of this [`Question`] by adding up the length of the
     /// domain name and the fixed length (QTYPE and QCLASS).
     pub fn size(&self) -> usize {
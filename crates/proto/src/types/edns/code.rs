@@ -21,7 +21,7 @@ pub enum OptionCode {
     /// - 18 - 20291
     /// - 20293 - 26945
     /// - 26947 - 65000
-    UNASSIGNED,
+    UNASSIGNED(u16),
 
     /// Long-Lived Queries
     /// [[RFC 8764](https://datatracker.ietf.org/doc/html/rfc8764)]
@@ -114,7 +114,7 @@ impl From<u16> for OptionCode {
             26946 => OptionCode::DEVICEID,
             0 | 4 | u16::MAX => OptionCode::RESERVED(value),
             65001..=65534 => OptionCode::RESERVEDLOCAL(value),
-            _ => Self::UNASSIGNED,
+            _ => Self::UNASSIGNED(value),
         }
     }
 }
@@ -122,27 +122,27 @@ impl From<u16> for OptionCode {
 impl From<OptionCode> for u16 {
     fn from(value: OptionCode) -> Self {
         match value {
-            OptionCode::RESERVED(_) => todo!(),
-            OptionCode::RESERVEDLOCAL(_) => todo!(),
-            OptionCode::UNASSIGNED => todo!(),
-            OptionCode::LLQ => todo!(),
-            OptionCode::UL => todo!(),
-            OptionCode::NSID => todo!(),
-            OptionCode::DAU => todo!(),
-            OptionCode::DHU => todo!(),
-            OptionCode::N3U => todo!(),
-            OptionCode::ECS => todo!(),
-            OptionCode::EXPIRE => todo!(),
+            OptionCode::RESERVED(code) => code,
+            OptionCode::RESERVEDLOCAL(code) => code,
+            OptionCode::UNASSIGNED(code) => code,
+            OptionCode::LLQ => 1,
+            OptionCode::UL => 2,
+            OptionCode::NSID => 3,
+            OptionCode::DAU => 5,
+            OptionCode::DHU => 6,
+            OptionCode::N3U => 7,
+            OptionCode::ECS => 8,
+            OptionCode::EXPIRE => 9,
             OptionCode::COOKIE => 10,
-            OptionCode::TCPKEEPALIVE => todo!(),
-            OptionCode::PADDING => todo!(),
-            OptionCode::CHAIN => todo!(),
-            OptionCode::KEYTAG => todo!(),
-            OptionCode::EDE => todo!(),
-            OptionCode::CLIENTTAG => todo!(),
-            OptionCode::SERVERTAG => todo!(),
-            OptionCode::UMBRELLAIDENT => todo!(),
-            OptionCode::DEVICEID => todo!(),
+            OptionCode::TCPKEEPALIVE => 11,
+            OptionCode::PADDING => 12,
+            OptionCode::CHAIN => 13,
+            OptionCode::KEYTAG => 14,
+            OptionCode::EDE => 15,
+            OptionCode::CLIENTTAG => 16,
+            OptionCode::SERVERTAG => 17,
+            OptionCode::UMBRELLAIDENT => 20292,
+            OptionCode::DEVICEID => 26946,
         }
     }
 }
@@ -1,37 +1,53 @@
 use binbuf::{
-    read::{ReadBuffer, ReadError},
     write::{WriteBuffer, WriteError, Writeable},
     Endianness,
 };
 
-#[derive(Debug, Clone)]
+/// The DNS cookie option. The client cookie is always 8 octets long, the
+/// optional server cookie is between 8 and 32 octets long. See
+/// [RFC 7873](https://datatracker.ietf.org/doc/html/rfc7873#section-4).
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct COOKIE {
     client: Vec<u8>,
     server: Option<Vec<u8>>,
 }
 
 impl COOKIE {
-    pub fn read<E: Endianness>(buf: &mut ReadBuffer, len: u16) -> Result<Self, ReadError> {
+    /// Parses the cookie option from the raw option data. Returns `None` if
+    /// the length of the data is invalid.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
         // If the len is only 8 octets, we know that only the client cookie is
         // present, so we take the short path
-        if len == 8 {
-            let client = buf.read_vec(8)?;
-            return Ok(Self {
-                client,
+        if data.len() == 8 {
+            return Some(Self {
+                client: data.to_vec(),
                 server: None,
             });
         }
 
         // Len is longer than 8 octets, both client and server cookie are
         // present
-        let client = buf.read_vec(8)?;
-        let server = buf.read_vec((len - 8) as usize)?;
+        if !(16..=40).contains(&data.len()) {
+            return None;
+        }
 
-        Ok(Self {
-            client,
-            server: Some(server),
+        Some(Self {
+            client: data[..8].to_vec(),
+            server: Some(data[8..].to_vec()),
         })
     }
+
+    pub fn client(&self) -> &[u8] {
+        &self.client
+    }
+
+    pub fn server(&self) -> Option<&[u8]> {
+        self.server.as_deref()
+    }
+
+    pub fn size(&self) -> usize {
+        self.client.len() + self.server.as_ref().map_or(0, |s| s.len())
+    }
 }
 
 impl Writeable for COOKIE {
@@ -41,7 +57,7 @@ impl Writeable for COOKIE {
         let mut n = buf.write(&self.client);
 
         if let Some(server) = &self.server {
-            n *= buf.write(server);
+            n += buf.write(server);
         }
 
         Ok(n)
@@ -0,0 +1,72 @@
+use binbuf::{
+    write::{WriteBuffer, WriteError, Writeable},
+    Endianness,
+};
+
+/// The EDNS Client Subnet (ECS) option. See
+/// [RFC 7871](https://datatracker.ietf.org/doc/html/rfc7871#section-6).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ECS {
+    /// The address family, 1 for IPv4 and 2 for IPv6
+    family: u16,
+
+    /// The leftmost number of significant bits of the address
+    source_prefix_len: u8,
+
+    /// The leftmost number of bits of the address the response covers
+    scope_prefix_len: u8,
+
+    /// The address, truncated to the number of octets needed by the source
+    /// prefix length
+    address: Vec<u8>,
+}
+
+impl ECS {
+    /// Parses the ECS option from the raw option data. Returns `None` if the
+    /// data is too short.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < 4 {
+            return None;
+        }
+
+        Some(Self {
+            family: u16::from_be_bytes([data[0], data[1]]),
+            source_prefix_len: data[2],
+            scope_prefix_len: data[3],
+            address: data[4..].to_vec(),
+        })
+    }
+
+    pub fn family(&self) -> u16 {
+        self.family
+    }
+
+    pub fn source_prefix_len(&self) -> u8 {
+        self.source_prefix_len
+    }
+
+    pub fn scope_prefix_len(&self) -> u8 {
+        self.scope_prefix_len
+    }
+
+    pub fn address(&self) -> &[u8] {
+        &self.address
+    }
+
+    pub fn size(&self) -> usize {
+        4 + self.address.len()
+    }
+}
+
+impl Writeable for ECS {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let mut n = self.family.write::<E>(buf)?;
+        n += self.source_prefix_len.write::<E>(buf)?;
+        n += self.scope_prefix_len.write::<E>(buf)?;
+        n += buf.write(&self.address);
+
+        Ok(n)
+    }
+}
@@ -0,0 +1,81 @@
+use binbuf::{
+    write::{WriteBuffer, WriteError, Writeable},
+    Endianness,
+};
+
+/// The Extended DNS Error (EDE) option. See
+/// [RFC 8914](https://datatracker.ietf.org/doc/html/rfc8914#section-2).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EDE {
+    info_code: u16,
+
+    /// Optional UTF-8 text with additional information
+    extra_text: String,
+}
+
+impl EDE {
+    /// Validation resulted in Bogus for a reason not covered by the more
+    /// specific DNSSEC info codes.
+    pub const DNSSEC_BOGUS: u16 = 6;
+
+    /// The only available signatures have expired.
+    pub const SIGNATURE_EXPIRED: u16 = 7;
+
+    /// The only available signatures are not yet valid.
+    pub const SIGNATURE_NOT_YET_VALID: u16 = 8;
+
+    /// A DS record exists, but no supported matching DNSKEY could be found.
+    pub const DNSKEY_MISSING: u16 = 9;
+
+    /// Data which is expected to be signed is missing its RRSIGs.
+    pub const RRSIGS_MISSING: u16 = 10;
+
+    /// The response is missing the NSEC or NSEC3 records proving the
+    /// non-existence.
+    pub const NSEC_MISSING: u16 = 12;
+
+    pub fn new(info_code: u16, extra_text: String) -> Self {
+        Self {
+            info_code,
+            extra_text,
+        }
+    }
+
+    /// Parses the EDE option from the raw option data. Returns `None` if the
+    /// data is too short or the extra text is not valid UTF-8.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < 2 {
+            return None;
+        }
+
+        let extra_text = String::from_utf8(data[2..].to_vec()).ok()?;
+
+        Some(Self {
+            info_code: u16::from_be_bytes([data[0], data[1]]),
+            extra_text,
+        })
+    }
+
+    pub fn info_code(&self) -> u16 {
+        self.info_code
+    }
+
+    pub fn extra_text(&self) -> &str {
+        &self.extra_text
+    }
+
+    pub fn size(&self) -> usize {
+        2 + self.extra_text.len()
+    }
+}
+
+impl Writeable for EDE {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let mut n = self.info_code.write::<E>(buf)?;
+        n += buf.write(self.extra_text.as_bytes());
+
+        Ok(n)
+    }
+}
@@ -1,17 +1,50 @@
 use crate::types::edns::OptionCode;
 
 mod cookie;
+mod ecs;
+mod ede;
 
 use binbuf::{
     read::{ReadBuffer, ReadError},
     write::{WriteBuffer, WriteError, Writeable},
     Endianness,
 };
-use cookie::*;
+pub use cookie::*;
+pub use ecs::*;
+pub use ede::*;
 
-#[derive(Debug, Clone)]
+/// The typed data of an EDNS option. Options which are unknown or malformed
+/// are kept as raw data in [`OptionData::UNKNOWN`], which makes sure every
+/// option round-trips losslessly.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OptionData {
+    /// [RFC 7873](https://datatracker.ietf.org/doc/html/rfc7873)
     COOKIE(COOKIE),
+
+    /// [RFC 5001](https://datatracker.ietf.org/doc/html/rfc5001). The NSID
+    /// payload is opaque.
+    NSID(Vec<u8>),
+
+    /// [RFC 7871](https://datatracker.ietf.org/doc/html/rfc7871)
+    ECS(ECS),
+
+    /// [RFC 7314](https://datatracker.ietf.org/doc/html/rfc7314). Queries
+    /// carry no data, responses carry the SOA EXPIRE value.
+    EXPIRE(Option<u32>),
+
+    /// [RFC 7828](https://datatracker.ietf.org/doc/html/rfc7828). Queries
+    /// carry no data, responses carry the timeout in units of 100ms.
+    TCPKEEPALIVE(Option<u16>),
+
+    /// [RFC 7830](https://datatracker.ietf.org/doc/html/rfc7830). The
+    /// padding octets should be zero.
+    PADDING(Vec<u8>),
+
+    /// [RFC 8914](https://datatracker.ietf.org/doc/html/rfc8914)
+    EDE(EDE),
+
+    /// The raw data of any other option.
+    UNKNOWN(Vec<u8>),
 }
 
 impl OptionData {
@@ -20,28 +53,48 @@ impl OptionData {
         opt_code: OptionCode,
         len: u16,
     ) -> Result<Self, ReadError> {
-        match opt_code {
-            OptionCode::RESERVED(_) => todo!(),
-            OptionCode::RESERVEDLOCAL(_) => todo!(),
-            OptionCode::UNASSIGNED => todo!(),
-            OptionCode::LLQ => todo!(),
-            OptionCode::UL => todo!(),
-            OptionCode::NSID => todo!(),
-            OptionCode::DAU => todo!(),
-            OptionCode::DHU => todo!(),
-            OptionCode::N3U => todo!(),
-            OptionCode::ECS => todo!(),
-            OptionCode::EXPIRE => todo!(),
-            OptionCode::COOKIE => COOKIE::read::<E>(buf, len).map(Self::COOKIE),
-            OptionCode::TCPKEEPALIVE => todo!(),
-            OptionCode::PADDING => todo!(),
-            OptionCode::CHAIN => todo!(),
-            OptionCode::KEYTAG => todo!(),
-            OptionCode::EDE => todo!(),
-            OptionCode::CLIENTTAG => todo!(),
-            OptionCode::SERVERTAG => todo!(),
-            OptionCode::UMBRELLAIDENT => todo!(),
-            OptionCode::DEVICEID => todo!(),
+        let data = buf.read_vec(len as usize)?;
+        Ok(Self::from_bytes(opt_code, data))
+    }
+
+    /// Parses the raw option `data` based on the `opt_code`. Data which
+    /// doesn't match the expected format is kept as [`OptionData::UNKNOWN`].
+    pub fn from_bytes(opt_code: OptionCode, data: Vec<u8>) -> Self {
+        let parsed = match opt_code {
+            OptionCode::COOKIE => COOKIE::from_bytes(&data).map(Self::COOKIE),
+            OptionCode::NSID => Some(Self::NSID(data.clone())),
+            OptionCode::ECS => ECS::from_bytes(&data).map(Self::ECS),
+            OptionCode::EXPIRE => match data.len() {
+                0 => Some(Self::EXPIRE(None)),
+                4 => Some(Self::EXPIRE(Some(u32::from_be_bytes([
+                    data[0], data[1], data[2], data[3],
+                ])))),
+                _ => None,
+            },
+            OptionCode::TCPKEEPALIVE => match data.len() {
+                0 => Some(Self::TCPKEEPALIVE(None)),
+                2 => Some(Self::TCPKEEPALIVE(Some(u16::from_be_bytes([
+                    data[0], data[1],
+                ])))),
+                _ => None,
+            },
+            OptionCode::PADDING => Some(Self::PADDING(data.clone())),
+            OptionCode::EDE => EDE::from_bytes(&data).map(Self::EDE),
+            _ => None,
+        };
+
+        parsed.unwrap_or(Self::UNKNOWN(data))
+    }
+
+    /// Returns the length of the option data in octets.
+    pub fn size(&self) -> usize {
+        match self {
+            OptionData::COOKIE(c) => c.size(),
+            OptionData::NSID(d) | OptionData::PADDING(d) | OptionData::UNKNOWN(d) => d.len(),
+            OptionData::ECS(ecs) => ecs.size(),
+            OptionData::EXPIRE(e) => e.map_or(0, |_| 4),
+            OptionData::TCPKEEPALIVE(t) => t.map_or(0, |_| 2),
+            OptionData::EDE(ede) => ede.size(),
         }
     }
 }
@@ -52,6 +105,14 @@ impl Writeable for OptionData {
     fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
         match self {
             OptionData::COOKIE(c) => c.write::<E>(buf),
+            OptionData::NSID(d) | OptionData::PADDING(d) | OptionData::UNKNOWN(d) => {
+                Ok(buf.write(d))
+            }
+            OptionData::ECS(ecs) => ecs.write::<E>(buf),
+            OptionData::EXPIRE(Some(expire)) => expire.write::<E>(buf),
+            OptionData::TCPKEEPALIVE(Some(timeout)) => timeout.write::<E>(buf),
+            OptionData::EXPIRE(None) | OptionData::TCPKEEPALIVE(None) => Ok(0),
+            OptionData::EDE(ede) => ede.write::<E>(buf),
         }
     }
 }
@@ -1,8 +1,17 @@
-use crate::types::{dns::Name, rr::RHeader};
+use crate::{
+    constants::{EDNS_VERSION, MIN_MESSAGE_SIZE},
+    types::{
+        dns::Name,
+        rr::{Class, RHeader, RType},
+    },
+};
+
+/// The DO bit in the TTL field of the OPT RR
+const DNSSEC_OK_MASK: u32 = 0x8000;
 
 /// The [`EdnsHeader`] describes the adjustments of the RR header defined in
 /// section 4 of [RFC 2671](https://datatracker.ietf.org/doc/html/rfc2671#section-4).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EdnsHeader {
     /// This will always be empty (root).
     name: Name,
@@ -49,7 +58,12 @@ pub struct EdnsHeader {
     /// See [RFC 2671](https://www.rfc-editor.org/rfc/rfc2671#section-4.6)
     version: u8,
 
-    /// Zero padding
+    /// DNSSEC OK bit. Indicates that the sender is able to handle DNSSEC
+    /// security RRs. See
+    /// [RFC 3225](https://datatracker.ietf.org/doc/html/rfc3225#section-3).
+    dnssec_ok: bool,
+
+    /// Zero padding (the remaining 15 bits after the DO bit)
     ///
     /// ### Notes
     ///
@@ -58,14 +72,105 @@ pub struct EdnsHeader {
     zero: u16,
 }
 
+impl Default for EdnsHeader {
+    fn default() -> Self {
+        Self::new(MIN_MESSAGE_SIZE as u16)
+    }
+}
+
 impl From<&RHeader> for EdnsHeader {
+    /// The TTL field of the OPT RR is laid out as follows (RFC 6891 Section
+    /// 6.1.3):
+    ///
+    /// ```text
+    ///              +0 (MSB)                            +1 (LSB)
+    ///   +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+    /// 0: |         EXTENDED-RCODE        |            VERSION            |
+    ///   +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+    /// 2: | DO|                           Z                               |
+    ///   +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+    /// ```
     fn from(rheader: &RHeader) -> Self {
+        let ttl = rheader.ttl();
+
         Self {
             name: rheader.name().clone(),
             sender_payload_size: rheader.class().into(),
-            upper_ext_rcode: (rheader.ttl() & 0xFF) as u8,
-            version: ((rheader.ttl() << 8) & 0xFF) as u8,
-            zero: (rheader.ttl() << 16) as u16,
+            upper_ext_rcode: (ttl >> 24) as u8,
+            version: (ttl >> 16) as u8,
+            dnssec_ok: ttl & DNSSEC_OK_MASK != 0,
+            zero: (ttl & 0x7FFF) as u16,
+        }
+    }
+}
+
+impl EdnsHeader {
+    /// Creates a new EDNS header with the provided UDP payload size. The
+    /// version is set to the supported [`EDNS_VERSION`].
+    pub fn new(sender_payload_size: u16) -> Self {
+        Self {
+            name: Name::default(),
+            sender_payload_size,
+            upper_ext_rcode: 0,
+            version: EDNS_VERSION,
+            dnssec_ok: false,
+            zero: 0,
         }
     }
+
+    /// Returns the sender's UDP payload size.
+    pub fn sender_payload_size(&self) -> u16 {
+        self.sender_payload_size
+    }
+
+    pub fn set_sender_payload_size(&mut self, size: u16) {
+        self.sender_payload_size = size
+    }
+
+    /// Returns the upper 8 bits of the extended 12-bit RCODE.
+    pub fn upper_ext_rcode(&self) -> u8 {
+        self.upper_ext_rcode
+    }
+
+    pub fn set_upper_ext_rcode(&mut self, rcode: u8) {
+        self.upper_ext_rcode = rcode
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn set_version(&mut self, version: u8) {
+        self.version = version
+    }
+
+    pub fn dnssec_ok(&self) -> bool {
+        self.dnssec_ok
+    }
+
+    pub fn set_dnssec_ok(&mut self, dnssec_ok: bool) {
+        self.dnssec_ok = dnssec_ok
+    }
+
+    /// Returns the TTL of the OPT RR which encodes the extended RCODE, the
+    /// version and the flags.
+    pub fn ttl(&self) -> u32 {
+        let mut ttl = ((self.upper_ext_rcode as u32) << 24) | ((self.version as u32) << 16);
+
+        if self.dnssec_ok {
+            ttl |= DNSSEC_OK_MASK;
+        }
+
+        ttl | (self.zero & 0x7FFF) as u32
+    }
+
+    /// Returns the RR header of the OPT pseudo-RR.
+    pub fn to_rheader(&self) -> RHeader {
+        let mut header = RHeader::new();
+        header.set_name(self.name.clone());
+        header.set_ty(RType::OPT);
+        header.set_class(Class::from(self.sender_payload_size));
+        header.set_ttl(self.ttl());
+        header
+    }
 }
@@ -13,12 +13,12 @@ pub use code::*;
 pub use data::*;
 pub use header::*;
 
-#[derive(Debug, Clone)]
+/// A single EDNS option. See
+/// [RFC 6891](https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.2).
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Option {
-    // This is redundant data. We already store the code in the map
     code: OptionCode,
     data: OptionData,
-    len: u16,
 }
 
 impl Readable for Option {
@@ -29,7 +29,7 @@ impl Readable for Option {
         let len = u16::read::<E>(buf)?;
         let data = OptionData::read::<E>(buf, code, len)?;
 
-        Ok(Option { code, data, len })
+        Ok(Option { code, data })
     }
 }
 
@@ -39,7 +39,7 @@ impl Writeable for Option {
     fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
         let n = bytes_written! {
             self.code.write::<E>(buf)?;
-            self.len.write::<E>(buf)?;
+            (self.data.size() as u16).write::<E>(buf)?;
             self.data.write::<E>(buf)?
         };
 
@@ -48,11 +48,21 @@ impl Writeable for Option {
 }
 
 impl Option {
+    pub fn new(code: OptionCode, data: OptionData) -> Self {
+        Self { code, data }
+    }
+
     pub fn code(&self) -> OptionCode {
         self.code
     }
 
+    pub fn data(&self) -> &OptionData {
+        &self.data
+    }
+
+    /// Returns the length of the option data in octets. This doesn't include
+    /// the option code and length fields.
     pub fn size(&self) -> u16 {
-        self.len
+        self.data.size() as u16
     }
 }
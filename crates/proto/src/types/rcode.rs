@@ -6,7 +6,7 @@ use std::fmt::Display;
 ///
 /// Response code - this 4 bit field is set as part of responses.
 /// See [RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.1).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Rcode {
     /// (0) No error condition.
     NoError,
@@ -53,6 +53,12 @@ pub enum Rcode {
     ///
     /// [h]: crate::types::edns::Header
     Reserved,
+
+    /// (16) Bad OPT Version - The responder does not implement the EDNS
+    /// version of the request. This extended RCODE can only be transmitted
+    /// in combination with an OPT record.
+    /// See [RFC 6891](https://datatracker.ietf.org/doc/html/rfc6891#section-9).
+    BadVers,
 }
 
 impl Display for Rcode {
@@ -65,6 +71,7 @@ impl Display for Rcode {
             Rcode::NotImpl => write!(f, "NOTIMPLEMENTED"),
             Rcode::Refused => write!(f, "REFUSED"),
             Rcode::Reserved => write!(f, "RESERVED"),
+            Rcode::BadVers => write!(f, "BADVERS"),
         }
     }
 }
@@ -78,6 +85,7 @@ impl From<u16> for Rcode {
             3 => Self::NameError,
             4 => Self::NotImpl,
             5 => Self::Refused,
+            16 => Self::BadVers,
             _ => Self::Reserved,
         }
     }
@@ -93,6 +101,7 @@ impl From<Rcode> for u16 {
             Rcode::NotImpl => 4,
             Rcode::Refused => 5,
             Rcode::Reserved => 65535,
+            Rcode::BadVers => 16,
         }
     }
 }
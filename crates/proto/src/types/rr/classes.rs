@@ -11,7 +11,7 @@ use crate::ProtocolError;
 
 /// [`Class`] describes resource record class codes.
 /// See https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.4
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum Class {
     /// The Internet
     IN,
@@ -68,7 +68,7 @@ impl Display for Class {
             Class::CH => write!(f, "CH"),
             Class::HS => write!(f, "HS"),
             Class::ANY => write!(f, "ANY"),
-            Class::UNKNOWN(c) => write!(f, "UNKNOWN({c})"),
+            Class::UNKNOWN(c) => write!(f, "CLASS{c}"),
         }
     }
 }
@@ -83,7 +83,11 @@ impl FromStr for Class {
             "CH" => Ok(Self::CH),
             "HS" => Ok(Self::HS),
             "ANY" => Ok(Self::ANY),
-            _ => Err(ProtocolError::ClassParseError),
+            // The generic class notation of RFC 3597 Section 5
+            c => match c.strip_prefix("CLASS").and_then(|c| c.parse::<u16>().ok()) {
+                Some(code) => Ok(Self::from(code)),
+                None => Err(ProtocolError::ClassParseError),
+            },
         }
     }
 }
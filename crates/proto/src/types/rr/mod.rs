@@ -1,12 +1,15 @@
-use std::fmt::Display;
+use std::{cmp::Ordering, fmt::Display};
 
 use binbuf::{
-    macros::bytes_written, read::ReadBuffer, write::WriteBuffer, Endianness, Readable, Writeable,
+    macros::bytes_written,
+    read::ReadBuffer,
+    write::{WriteBuffer, WriteError},
+    Endianness, Readable, Writeable,
 };
 use serde::{ser::SerializeStruct, Serialize};
-use snafu::{ResultExt, Snafu};
+use snafu::{ensure, ResultExt, Snafu};
 
-use crate::types::dns::Name;
+use crate::types::dns::{CompressionMap, Name};
 
 mod classes;
 mod rdata;
@@ -31,6 +34,12 @@ pub enum RecordError {
 
     #[snafu(display("failed to write RDATA"))]
     WriteData { source: RDataError },
+
+    #[snafu(display("failed to write RDLEN"))]
+    WriteRdlen { source: WriteError },
+
+    #[snafu(display("RDATA too long ({len} > {})", u16::MAX))]
+    RDataTooLong { len: usize },
 }
 
 /// ### Resource Records Definition (RFC 1034)
@@ -125,6 +134,12 @@ impl Record {
         self
     }
 
+    /// Set the resource record header TTL.
+    pub fn set_header_ttl(&mut self, ttl: u32) -> &mut Self {
+        self.header.set_ttl(ttl);
+        self
+    }
+
     pub fn header(&self) -> &RHeader {
         &self.header
     }
@@ -139,6 +154,38 @@ impl Record {
         &self.data
     }
 
+    /// Writes the record to `buf` and compresses the owner name and all
+    /// domain names in RDATA by using the provided [`CompressionMap`]. The
+    /// RDLEN stored in the header is ignored and instead the length of the
+    /// compressed RDATA is written.
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, RecordError> {
+        let mut n = self
+            .header
+            .write_compressed::<E>(buf, names)
+            .context(WriteHeaderSnafu)?;
+
+        // The RDATA will start right after the two RDLEN octets. Write it
+        // into a temporary buffer first, because we only know RDLEN after
+        // compressing all contained names.
+        let mut rdata_buf = WriteBuffer::new();
+        let previous = names.rebase(names.position(buf) + 2);
+        let result = self.data.write_compressed::<E>(&mut rdata_buf, names);
+        names.restore(previous);
+        result.context(WriteDataSnafu)?;
+
+        let rdlen = rdata_buf.len();
+        ensure!(rdlen <= u16::MAX as usize, RDataTooLongSnafu { len: rdlen });
+
+        n += (rdlen as u16).write::<E>(buf).context(WriteRdlenSnafu)?;
+        n += buf.write(rdata_buf.bytes());
+
+        Ok(n)
+    }
+
     pub fn normalize_rdlen(&mut self) -> &mut Self {
         self.header.set_rdlen(self.size() as u16);
         self
@@ -148,6 +195,23 @@ impl Record {
         self.data.size()
     }
 
+    /// Compares two records in canonical order. Records are sorted by their
+    /// owner name (see [`Name::cmp_canonical`]), class, type and finally by
+    /// their canonical RDATA. See
+    /// [RFC 4034 Section 6.3](https://datatracker.ietf.org/doc/html/rfc4034#section-6.3).
+    pub fn cmp_canonical(&self, other: &Self) -> Ordering {
+        self.header
+            .name()
+            .cmp_canonical(other.header.name())
+            .then_with(|| u16::from(self.header.class()).cmp(&u16::from(other.header.class())))
+            .then_with(|| u16::from(*self.header.ty()).cmp(&u16::from(*other.header.ty())))
+            .then_with(|| {
+                let a = self.data.canonical_bytes().unwrap_or_default();
+                let b = other.data.canonical_bytes().unwrap_or_default();
+                a.cmp(&b)
+            })
+    }
+
     pub fn is_edns(&self) -> bool {
         *self.header.ty() == RType::OPT
     }
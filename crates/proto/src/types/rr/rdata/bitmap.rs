@@ -0,0 +1,159 @@
+use std::fmt::Display;
+
+use binbuf::{Endianness, ReadBuffer, Readable, WriteBuffer, WriteError, Writeable};
+use snafu::{ensure, ResultExt};
+
+use crate::types::rr::{
+    rdata::{BufferSnafu, InvalidTypeBitMapsSnafu, RDataError},
+    RDataParseError, RType,
+};
+
+/// The Type Bit Maps field of NSEC and NSEC3 records lists the record types
+/// present at a name. The types are split into windows of 256 types, each
+/// window is encoded as its number, the bitmap length and a bitmap of up to
+/// 32 octets. Trailing zero octets are omitted.
+///
+/// ```text
+/// ( Window Block # | Bitmap Length | Bitmap )+
+/// ```
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc4034#section-4.1.2
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeBitMaps {
+    /// The types sorted by their numeric value, without duplicates.
+    types: Vec<RType>,
+}
+
+impl Display for TypeBitMaps {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let types: Vec<String> = self.types.iter().map(|ty| ty.to_string()).collect();
+        write!(f, "{}", types.join(" "))
+    }
+}
+
+impl Writeable for TypeBitMaps {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let mut n = 0;
+
+        for (window, mut bitmap) in self.windows() {
+            n += window.write::<E>(buf)?;
+            n += (bitmap.len() as u8).write::<E>(buf)?;
+            n += buf.write(&mut bitmap);
+        }
+
+        Ok(n)
+    }
+}
+
+impl TypeBitMaps {
+    pub fn new(types: impl IntoIterator<Item = RType>) -> Self {
+        let mut types: Vec<RType> = types.into_iter().collect();
+
+        types.sort_by_key(|ty| u16::from(*ty));
+        types.dedup();
+
+        Self { types }
+    }
+
+    /// Reads `len` octets of type bitmaps. The window numbers have to be in
+    /// strictly increasing order and each bitmap has to be 1 to 32 octets
+    /// long.
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, len: usize) -> Result<Self, RDataError> {
+        let start = buf.offset();
+        let mut last_window: Option<u8> = None;
+        let mut types = Vec::new();
+
+        while buf.offset() - start < len {
+            let window = u8::read::<E>(buf).context(BufferSnafu)?;
+            let bitmap_len = u8::read::<E>(buf).context(BufferSnafu)?;
+
+            ensure!(
+                last_window.map_or(true, |last| last < window),
+                InvalidTypeBitMapsSnafu {
+                    reason: format!("window {window} not in strictly increasing order"),
+                }
+            );
+
+            ensure!(
+                (1..=32).contains(&bitmap_len),
+                InvalidTypeBitMapsSnafu {
+                    reason: format!("invalid bitmap length {bitmap_len}"),
+                }
+            );
+
+            let bitmap = buf.read_vec(bitmap_len as usize).context(BufferSnafu)?;
+
+            for (i, octet) in bitmap.iter().enumerate() {
+                for bit in 0..8 {
+                    if octet & (0x80 >> bit) != 0 {
+                        let code = (window as u16) << 8 | (i * 8 + bit) as u16;
+                        types.push(RType::from(code));
+                    }
+                }
+            }
+
+            last_window = Some(window);
+        }
+
+        Ok(Self { types })
+    }
+
+    /// Parses a list of type mnemonics, e.g. `A MX RRSIG NSEC TYPE1234`.
+    pub fn try_from_parts(ty: RType, parts: &[&str]) -> Result<Self, RDataParseError> {
+        let types = parts
+            .iter()
+            .map(|part| {
+                RType::try_from(*part)
+                    .map_err(|_| RDataParseError::new(ty, format!("unknown type '{part}'")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(types))
+    }
+
+    pub fn types(&self) -> &Vec<RType> {
+        &self.types
+    }
+
+    /// Returns if the type `ty` is present.
+    pub fn contains(&self, ty: RType) -> bool {
+        self.types.contains(&ty)
+    }
+
+    /// Returns the size of the type bitmaps in wire format.
+    pub fn size(&self) -> usize {
+        self.windows()
+            .iter()
+            .map(|(_, bitmap)| 2 + bitmap.len())
+            .sum()
+    }
+
+    /// Returns the windows with their bitmaps without trailing zero octets.
+    fn windows(&self) -> Vec<(u8, Vec<u8>)> {
+        let mut windows: Vec<(u8, Vec<u8>)> = Vec::new();
+
+        for ty in &self.types {
+            let code = u16::from(*ty);
+            let (window, low) = ((code >> 8) as u8, (code & 0xff) as usize);
+
+            if windows.last().map(|(w, _)| *w) != Some(window) {
+                windows.push((window, Vec::new()));
+            }
+
+            // The types are sorted, so the current window is always the last
+            if let Some((_, bitmap)) = windows.last_mut() {
+                if bitmap.len() <= low / 8 {
+                    bitmap.resize(low / 8 + 1, 0);
+                }
+
+                bitmap[low / 8] |= 0x80 >> (low % 8);
+            }
+        }
+
+        windows
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadResult, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::{
+    constants::MAX_CHAR_STRING_LENGTH,
+    types::rr::{
+        rdata::{expect_parts, format_char_string, parse_escaped, parse_number},
+        RDataParseError, RType,
+    },
+};
+
+/// ```text
+/// +0-1-2-3-4-5-6-7-|0-1-2-3-4-5-6-7-|
+/// | Flags          | Tag Length = n |
+/// +----------------|----------------+...+---------------+
+/// | Tag char 0     | Tag char 1     |...| Tag char n-1  |
+/// +----------------|----------------+...+---------------+
+/// +----------------|----------------+.....+----------------+
+/// | Value byte 0   | Value byte 1   |.....| Value byte m-1 |
+/// +----------------|----------------+.....+----------------+
+/// ```
+///
+/// The value fills the remaining RDATA.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc8659#section-4.1
+#[derive(Debug, Clone)]
+pub struct CAA {
+    flags: u8,
+    tag: Vec<u8>,
+    value: Vec<u8>,
+}
+
+impl Display for CAA {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.flags,
+            String::from_utf8_lossy(&self.tag),
+            format_char_string(&self.value)
+        )
+    }
+}
+
+impl Writeable for CAA {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.flags.write::<E>(buf)?;
+            buf.write_char_string(&self.tag, Some(MAX_CHAR_STRING_LENGTH))?;
+            buf.write(&mut self.value.clone())
+        };
+
+        Ok(n)
+    }
+}
+
+impl CAA {
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> ReadResult<Self> {
+        let start = buf.offset();
+
+        let flags = u8::read::<E>(buf)?;
+        let tag = buf.read_char_string(Some(MAX_CHAR_STRING_LENGTH))?.to_vec();
+
+        let remaining = (rdlen as usize).saturating_sub(buf.offset() - start);
+        let value = buf.read_vec(remaining)?;
+
+        Ok(Self { flags, tag, value })
+    }
+
+    /// Parses the FLAGS, TAG and VALUE fields in presentation format.
+    pub fn try_from_parts(parts: &[&str]) -> Result<Self, RDataParseError> {
+        let parts = expect_parts(RType::CAA, parts, 3)?;
+
+        let tag = parts[1];
+        if tag.is_empty() || !tag.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(RDataParseError::new(
+                RType::CAA,
+                format!("invalid tag '{tag}'"),
+            ));
+        }
+
+        Ok(Self {
+            flags: parse_number(RType::CAA, parts[0])?,
+            tag: tag.as_bytes().to_vec(),
+            value: parse_escaped(RType::CAA, parts[2])?,
+        })
+    }
+
+    /// Returns if the issuer critical flag is set.
+    pub fn is_critical(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    pub fn tag(&self) -> &[u8] {
+        &self.tag
+    }
+
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Returns the size of the [`CAA`] record.
+    pub fn size(&self) -> usize {
+        // FLAGS, the tag length octet, the TAG and the VALUE
+        2 + self.tag.len() + self.value.len()
+    }
+}
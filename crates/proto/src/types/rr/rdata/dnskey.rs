@@ -0,0 +1,182 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadResult, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::types::rr::{
+    rdata::{format_base64, parse_base64, parse_number},
+    RDataParseError, RType,
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |              Flags            |    Protocol   |   Algorithm   |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                                                               /
+/// /                            Public Key                         /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// CDNSKEY records use the same format.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc4034#section-2.1
+/// - https://datatracker.ietf.org/doc/html/rfc7344#section-3.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DNSKEY {
+    flags: u16,
+    protocol: u8,
+    algorithm: u8,
+    public_key: Vec<u8>,
+}
+
+impl Display for DNSKEY {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.flags,
+            self.protocol,
+            self.algorithm,
+            format_base64(&self.public_key)
+        )
+    }
+}
+
+impl Writeable for DNSKEY {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.flags.write::<E>(buf)?;
+            self.protocol.write::<E>(buf)?;
+            self.algorithm.write::<E>(buf)?;
+            buf.write(&mut self.public_key.clone())
+        };
+
+        Ok(n)
+    }
+}
+
+impl DNSKEY {
+    /// The key is a DNS zone key (bit 7).
+    pub const FLAG_ZONE: u16 = 0x0100;
+
+    /// The key has been revoked (bit 8). See
+    /// [RFC 5011 Section 3](https://datatracker.ietf.org/doc/html/rfc5011#section-3).
+    pub const FLAG_REVOKE: u16 = 0x0080;
+
+    /// The key is a secure entry point, usually a key signing key (bit 15).
+    pub const FLAG_SEP: u16 = 0x0001;
+
+    pub fn new(flags: u16, protocol: u8, algorithm: u8, public_key: Vec<u8>) -> Self {
+        Self {
+            flags,
+            protocol,
+            algorithm,
+            public_key,
+        }
+    }
+
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> ReadResult<Self> {
+        let flags = u16::read::<E>(buf)?;
+        let protocol = u8::read::<E>(buf)?;
+        let algorithm = u8::read::<E>(buf)?;
+        let public_key = buf.read_vec((rdlen as usize).saturating_sub(4))?;
+
+        Ok(Self {
+            flags,
+            protocol,
+            algorithm,
+            public_key,
+        })
+    }
+
+    /// Parses the flags, protocol, algorithm and public key in presentation
+    /// format. The base64 encoded public key can be split into multiple
+    /// parts. `ty` is either DNSKEY or CDNSKEY.
+    pub fn try_from_parts(ty: RType, parts: &[&str]) -> Result<Self, RDataParseError> {
+        if parts.len() < 4 {
+            return Err(RDataParseError::new(
+                ty,
+                format!("expected at least 4 fields, got {}", parts.len()),
+            ));
+        }
+
+        Ok(Self {
+            flags: parse_number(ty, parts[0])?,
+            protocol: parse_number(ty, parts[1])?,
+            algorithm: parse_number(ty, parts[2])?,
+            public_key: parse_base64(ty, &parts[3..])?,
+        })
+    }
+
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    pub fn protocol(&self) -> u8 {
+        self.protocol
+    }
+
+    pub fn algorithm(&self) -> u8 {
+        self.algorithm
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    pub fn is_zone_key(&self) -> bool {
+        self.flags & Self::FLAG_ZONE != 0
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.flags & Self::FLAG_REVOKE != 0
+    }
+
+    pub fn is_secure_entry_point(&self) -> bool {
+        self.flags & Self::FLAG_SEP != 0
+    }
+
+    /// Calculates the key tag which is used to select the matching key for
+    /// RRSIG and DS records. See
+    /// [RFC 4034 Appendix B](https://datatracker.ietf.org/doc/html/rfc4034#appendix-B).
+    pub fn key_tag(&self) -> u16 {
+        let mut rdata = Vec::with_capacity(self.size());
+        rdata.extend_from_slice(&self.flags.to_be_bytes());
+        rdata.push(self.protocol);
+        rdata.push(self.algorithm);
+        rdata.extend_from_slice(&self.public_key);
+
+        // RSA/MD5 keys use the most significant 16 bits of the least
+        // significant 24 bits of the public key modulus.
+        if self.algorithm == 1 {
+            return match rdata.len() {
+                len if len >= 7 => u16::from_be_bytes([rdata[len - 3], rdata[len - 2]]),
+                _ => 0,
+            };
+        }
+
+        let mut ac: u32 = 0;
+
+        for (i, &octet) in rdata.iter().enumerate() {
+            ac += match i & 1 {
+                0 => (octet as u32) << 8,
+                _ => octet as u32,
+            };
+        }
+
+        ac += (ac >> 16) & 0xffff;
+        (ac & 0xffff) as u16
+    }
+
+    /// Returns the size of the [`DNSKEY`] record.
+    pub fn size(&self) -> usize {
+        4 + self.public_key.len()
+    }
+}
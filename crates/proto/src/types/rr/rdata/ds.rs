@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadResult, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::types::rr::{
+    rdata::{format_hex, parse_hex, parse_number},
+    RDataParseError, RType,
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |           Key Tag             |  Algorithm    |  Digest Type  |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                                                               /
+/// /                            Digest                             /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// CDS records use the same format.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc4034#section-5.1
+/// - https://datatracker.ietf.org/doc/html/rfc7344#section-3.1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DS {
+    key_tag: u16,
+    algorithm: u8,
+    digest_type: u8,
+    digest: Vec<u8>,
+}
+
+impl Display for DS {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.key_tag,
+            self.algorithm,
+            self.digest_type,
+            format_hex(&self.digest)
+        )
+    }
+}
+
+impl Writeable for DS {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.key_tag.write::<E>(buf)?;
+            self.algorithm.write::<E>(buf)?;
+            self.digest_type.write::<E>(buf)?;
+            buf.write(&mut self.digest.clone())
+        };
+
+        Ok(n)
+    }
+}
+
+impl DS {
+    pub fn new(key_tag: u16, algorithm: u8, digest_type: u8, digest: Vec<u8>) -> Self {
+        Self {
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        }
+    }
+
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> ReadResult<Self> {
+        let key_tag = u16::read::<E>(buf)?;
+        let algorithm = u8::read::<E>(buf)?;
+        let digest_type = u8::read::<E>(buf)?;
+        let digest = buf.read_vec((rdlen as usize).saturating_sub(4))?;
+
+        Ok(Self {
+            key_tag,
+            algorithm,
+            digest_type,
+            digest,
+        })
+    }
+
+    /// Parses the key tag, algorithm, digest type and digest in presentation
+    /// format. The hexadecimal digest can be split into multiple parts. `ty`
+    /// is either DS or CDS.
+    pub fn try_from_parts(ty: RType, parts: &[&str]) -> Result<Self, RDataParseError> {
+        if parts.len() < 4 {
+            return Err(RDataParseError::new(
+                ty,
+                format!("expected at least 4 fields, got {}", parts.len()),
+            ));
+        }
+
+        Ok(Self {
+            key_tag: parse_number(ty, parts[0])?,
+            algorithm: parse_number(ty, parts[1])?,
+            digest_type: parse_number(ty, parts[2])?,
+            digest: parse_hex(ty, &parts[3..])?,
+        })
+    }
+
+    pub fn key_tag(&self) -> u16 {
+        self.key_tag
+    }
+
+    pub fn algorithm(&self) -> u8 {
+        self.algorithm
+    }
+
+    pub fn digest_type(&self) -> u8 {
+        self.digest_type
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Returns the size of the [`DS`] record.
+    pub fn size(&self) -> usize {
+        4 + self.digest.len()
+    }
+}
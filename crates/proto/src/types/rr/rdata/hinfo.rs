@@ -6,7 +6,13 @@ use binbuf::{
     Endianness,
 };
 
-use crate::constants;
+use crate::{
+    constants,
+    types::rr::{
+        rdata::{format_char_string, parse_char_string},
+        RDataParseError, RType,
+    },
+};
 
 #[derive(Debug, Clone)]
 pub struct HINFO {
@@ -16,7 +22,12 @@ pub struct HINFO {
 
 impl Display for HINFO {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "CPU: {:?} OS: {:?}", self.cpu, self.os)
+        write!(
+            f,
+            "{} {}",
+            format_char_string(&self.cpu),
+            format_char_string(&self.os)
+        )
     }
 }
 
@@ -45,7 +56,16 @@ impl Writeable for HINFO {
 }
 
 impl HINFO {
+    /// Parses the CPU and OS <character-string>s in presentation format.
+    pub fn try_from_parts(parts: &[&str]) -> Result<Self, RDataParseError> {
+        Ok(Self {
+            cpu: parse_char_string(RType::HINFO, parts[0])?,
+            os: parse_char_string(RType::HINFO, parts[1])?,
+        })
+    }
+
     pub fn size(&self) -> usize {
-        self.cpu.len() + self.os.len()
+        // Both <character-string>s are prefixed with a length octet
+        self.cpu.len() + self.os.len() + 2
     }
 }
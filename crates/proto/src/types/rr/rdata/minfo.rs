@@ -1,8 +1,15 @@
 use std::fmt::Display;
 
-use binbuf::{Readable, Writeable};
+use binbuf::{Endianness, Readable, WriteBuffer, Writeable};
+use snafu::ResultExt;
 
-use crate::types::dns::Name;
+use crate::types::{
+    dns::{CompressionMap, Name},
+    rr::{
+        rdata::{parse_name, NameSnafu, RDataError},
+        RDataParseError, RType,
+    },
+};
 
 #[derive(Debug, Clone, Readable, Writeable)]
 pub struct MINFO {
@@ -12,11 +19,46 @@ pub struct MINFO {
 
 impl Display for MINFO {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "RMAILBOX: {} EMAILBOX: {}", self.rmailbx, self.emailbx)
+        write!(f, "{} {}", self.rmailbx, self.emailbx)
     }
 }
 
 impl MINFO {
+    /// Parses the RMAILBX and EMAILBX fields in presentation format.
+    pub fn try_from_parts(parts: &[&str], origin: &Name) -> Result<Self, RDataParseError> {
+        Ok(Self {
+            rmailbx: parse_name(RType::MINFO, parts[0], origin)?,
+            emailbx: parse_name(RType::MINFO, parts[1], origin)?,
+        })
+    }
+
+    /// Writes the [`MINFO`] record and compresses RMAILBX and EMAILBX by
+    /// using the provided [`CompressionMap`].
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, RDataError> {
+        let n = self
+            .rmailbx
+            .write_compressed::<E>(buf, names)
+            .context(NameSnafu)?;
+        let m = self
+            .emailbx
+            .write_compressed::<E>(buf, names)
+            .context(NameSnafu)?;
+
+        Ok(n + m)
+    }
+
+    /// Returns a copy with the domain names converted to lowercase.
+    pub fn to_lowercase(&self) -> Self {
+        Self {
+            rmailbx: self.rmailbx.to_lowercase(),
+            emailbx: self.emailbx.to_lowercase(),
+        }
+    }
+
     pub fn size(&self) -> usize {
         self.rmailbx.size() + self.emailbx.size()
     }
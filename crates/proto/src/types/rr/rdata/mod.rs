@@ -4,33 +4,69 @@ use std::{
 };
 
 use binbuf::{
+    prelude::BigEndian,
     read::{ReadBuffer, ReadError},
-    write::{WriteBuffer, Writeable},
+    write::{WriteBuffer, WriteError, Writeable},
     Endianness,
 };
 use serde::Serialize;
-use snafu::Snafu;
-
-use crate::types::{
-    dns::{Name, NameError},
-    rr::{RHeader, RType},
+use snafu::{ResultExt, Snafu};
+
+use crate::{
+    types::{
+        dns::{CompressionMap, Name, NameError},
+        rr::{RHeader, RType},
+    },
+    zone::lexer::{Lexer, Token, TokenKind},
 };
 
+mod bitmap;
+mod caa;
+mod dnskey;
+mod ds;
 mod hinfo;
 mod minfo;
 mod mx;
+mod naptr;
+mod nsec;
+mod nsec3;
+mod nsec3param;
 mod null;
 mod opt;
+mod parse;
+mod rrsig;
 mod soa;
+mod srv;
+mod sshfp;
+mod svcb;
+mod tlsa;
 mod txt;
+mod unknown;
+mod uri;
 
+pub use bitmap::*;
+pub use caa::*;
+pub use dnskey::*;
+pub use ds::*;
 pub use hinfo::*;
 pub use minfo::*;
 pub use mx::*;
+pub use naptr::*;
+pub use nsec::*;
+pub use nsec3::*;
+pub use nsec3param::*;
 pub use null::*;
 pub use opt::*;
+pub(crate) use parse::*;
+pub use rrsig::*;
 pub use soa::*;
+pub use srv::*;
+pub use sshfp::*;
+pub use svcb::*;
+pub use tlsa::*;
 pub use txt::*;
+pub use unknown::*;
+pub use uri::*;
 
 pub struct RDataParseError {
     msg: String,
@@ -63,6 +99,15 @@ pub enum RDataError {
 
     #[snafu(display("Buffer error"))]
     BufferError { source: ReadError },
+
+    #[snafu(display("Buffer write error"))]
+    BufferWriteError { source: WriteError },
+
+    #[snafu(display("Invalid SvcParam: {reason}"))]
+    InvalidSvcParam { reason: String },
+
+    #[snafu(display("Invalid type bit maps: {reason}"))]
+    InvalidTypeBitMaps { reason: String },
 }
 
 #[derive(Debug, Clone)]
@@ -330,12 +375,80 @@ pub enum RData {
     /// A 128 bit IPv6 address is encoded in the data portion of an AAAA
     /// resource record in network byte order (high-order byte first).
     AAAA(Ipv6Addr),
+
+    /// The location of services. See [RFC 2782](https://datatracker.ietf.org/doc/html/rfc2782).
+    SRV(SRV),
+
+    /// Rewrite rules for URIs. See [RFC 3403](https://datatracker.ietf.org/doc/html/rfc3403).
+    NAPTR(NAPTR),
+
+    /// Redirects all names below the owner to the same names below the
+    /// target. Unlike CNAME, the owner name itself is not redirected. See
+    /// [RFC 6672](https://datatracker.ietf.org/doc/html/rfc6672).
+    DNAME(Name),
+
+    /// SSH public key fingerprints. See [RFC 4255](https://datatracker.ietf.org/doc/html/rfc4255).
+    SSHFP(SSHFP),
+
+    /// Delegation signer, refers to a DNSKEY of the delegated zone. See
+    /// [RFC 4034 Section 5](https://datatracker.ietf.org/doc/html/rfc4034#section-5).
+    DS(DS),
+
+    /// Signature over an RRset. See
+    /// [RFC 4034 Section 3](https://datatracker.ietf.org/doc/html/rfc4034#section-3).
+    RRSIG(RRSIG),
+
+    /// Authenticated denial of existence, links to the next name of the
+    /// zone. See [RFC 4034 Section 4](https://datatracker.ietf.org/doc/html/rfc4034#section-4).
+    NSEC(NSEC),
+
+    /// Public key of a zone. See
+    /// [RFC 4034 Section 2](https://datatracker.ietf.org/doc/html/rfc4034#section-2).
+    DNSKEY(DNSKEY),
+
+    /// Authenticated denial of existence with hashed owner names. See
+    /// [RFC 5155 Section 3](https://datatracker.ietf.org/doc/html/rfc5155#section-3).
+    NSEC3(NSEC3),
+
+    /// Parameters used to calculate hashed owner names. See
+    /// [RFC 5155 Section 4](https://datatracker.ietf.org/doc/html/rfc5155#section-4).
+    NSEC3PARAM(NSEC3PARAM),
+
     OPT(OPT),
+
+    /// TLS certificate associations (DANE). See [RFC 6698](https://datatracker.ietf.org/doc/html/rfc6698).
+    TLSA(TLSA),
+
+    /// Child copy of DS records. See
+    /// [RFC 7344](https://datatracker.ietf.org/doc/html/rfc7344).
+    CDS(DS),
+
+    /// Child copy of DNSKEY records. See
+    /// [RFC 7344](https://datatracker.ietf.org/doc/html/rfc7344).
+    CDNSKEY(DNSKEY),
+
+    /// General purpose service bindings. See
+    /// [RFC 9460](https://datatracker.ietf.org/doc/html/rfc9460).
+    SVCB(SVCB),
+
+    /// Service bindings for HTTP origins, using the same format as SVCB. See
+    /// [RFC 9460 Section 9](https://datatracker.ietf.org/doc/html/rfc9460#section-9).
+    HTTPS(SVCB),
+
+    /// Mappings from hostnames to URIs. See [RFC 7553](https://datatracker.ietf.org/doc/html/rfc7553).
+    URI(URI),
+
+    /// Certification authorities allowed to issue certificates. See
+    /// [RFC 8659](https://datatracker.ietf.org/doc/html/rfc8659).
+    CAA(CAA),
     AXFR,
     MAILB,
     MAILA,
     ANY,
-    UNKNOWN,
+
+    /// RDATA of a type which is not modelled, kept as opaque octets. See
+    /// [RFC 3597](https://datatracker.ietf.org/doc/html/rfc3597).
+    UNKNOWN(UNKNOWN),
 }
 
 impl Default for RData {
@@ -359,12 +472,23 @@ impl Display for RData {
             RData::MX(mx) => write!(f, "{mx}"),
             RData::TXT(txt) => write!(f, "{txt}"),
             RData::AAAA(aaaa) => write!(f, "{aaaa}"),
+            RData::SRV(srv) => write!(f, "{srv}"),
+            RData::NAPTR(naptr) => write!(f, "{naptr}"),
+            RData::DNAME(dname) => write!(f, "{dname}"),
+            RData::SSHFP(sshfp) => write!(f, "{sshfp}"),
             RData::OPT(opt) => write!(f, "{opt}"),
-            RData::AXFR => todo!(),
-            RData::MAILB => todo!(),
-            RData::MAILA => todo!(),
-            RData::ANY => todo!(),
-            RData::UNKNOWN => todo!(),
+            RData::TLSA(tlsa) => write!(f, "{tlsa}"),
+            RData::DS(ds) | RData::CDS(ds) => write!(f, "{ds}"),
+            RData::RRSIG(rrsig) => write!(f, "{rrsig}"),
+            RData::NSEC(nsec) => write!(f, "{nsec}"),
+            RData::DNSKEY(dnskey) | RData::CDNSKEY(dnskey) => write!(f, "{dnskey}"),
+            RData::NSEC3(nsec3) => write!(f, "{nsec3}"),
+            RData::NSEC3PARAM(param) => write!(f, "{param}"),
+            RData::SVCB(svcb) | RData::HTTPS(svcb) => write!(f, "{svcb}"),
+            RData::URI(uri) => write!(f, "{uri}"),
+            RData::CAA(caa) => write!(f, "{caa}"),
+            RData::AXFR | RData::MAILB | RData::MAILA | RData::ANY => Ok(()),
+            RData::UNKNOWN(unknown) => write!(f, "{unknown}"),
         }
     }
 }
@@ -385,12 +509,23 @@ impl Writeable for RData {
             RData::MX(mx) => mx.write::<E>(buf)?,
             RData::TXT(txt) => txt.write::<E>(buf)?,
             RData::AAAA(aaaa) => aaaa.write::<E>(buf)?,
+            RData::SRV(srv) => srv.write::<E>(buf)?,
+            RData::NAPTR(naptr) => naptr.write::<E>(buf)?,
+            RData::DNAME(dname) => dname.write::<E>(buf)?,
+            RData::SSHFP(sshfp) => sshfp.write::<E>(buf)?,
             RData::OPT(opt) => opt.write::<E>(buf)?,
-            RData::AXFR => todo!(),
-            RData::MAILB => todo!(),
-            RData::MAILA => todo!(),
-            RData::ANY => todo!(),
-            RData::UNKNOWN => todo!(),
+            RData::TLSA(tlsa) => tlsa.write::<E>(buf)?,
+            RData::DS(ds) | RData::CDS(ds) => ds.write::<E>(buf)?,
+            RData::RRSIG(rrsig) => rrsig.write::<E>(buf)?,
+            RData::NSEC(nsec) => nsec.write::<E>(buf)?,
+            RData::DNSKEY(dnskey) | RData::CDNSKEY(dnskey) => dnskey.write::<E>(buf)?,
+            RData::NSEC3(nsec3) => nsec3.write::<E>(buf)?,
+            RData::NSEC3PARAM(param) => param.write::<E>(buf)?,
+            RData::SVCB(svcb) | RData::HTTPS(svcb) => svcb.write::<E>(buf)?,
+            RData::URI(uri) => uri.write::<E>(buf)?,
+            RData::CAA(caa) => caa.write::<E>(buf)?,
+            RData::AXFR | RData::MAILB | RData::MAILA | RData::ANY => 0,
+            RData::UNKNOWN(unknown) => unknown.write::<E>(buf)?,
         };
 
         Ok(n)
@@ -422,12 +557,29 @@ impl RData {
             RType::MX => Self::MX(MX::read::<E>(buf)?),
             RType::TXT => Self::TXT(TXT::read::<E>(buf, header.rdlen())?),
             RType::AAAA => Self::AAAA(Ipv6Addr::read::<E>(buf)?),
+            RType::SRV => Self::SRV(SRV::read::<E>(buf)?),
+            RType::NAPTR => Self::NAPTR(NAPTR::read::<E>(buf)?),
+            RType::DNAME => Self::DNAME(Name::read::<E>(buf)?),
+            RType::SSHFP => Self::SSHFP(SSHFP::read::<E>(buf, header.rdlen())?),
             RType::OPT => Self::OPT(OPT::read::<E>(buf, header)?),
-            RType::AXFR => todo!(),
-            RType::MAILB => todo!(),
-            RType::MAILA => todo!(),
-            RType::ANY => todo!(),
-            RType::UNKNOWN(_) => todo!(),
+            RType::TLSA => Self::TLSA(TLSA::read::<E>(buf, header.rdlen())?),
+            RType::DS => Self::DS(DS::read::<E>(buf, header.rdlen())?),
+            RType::CDS => Self::CDS(DS::read::<E>(buf, header.rdlen())?),
+            RType::RRSIG => Self::RRSIG(RRSIG::read::<E>(buf, header.rdlen())?),
+            RType::NSEC => Self::NSEC(NSEC::read::<E>(buf, header.rdlen())?),
+            RType::DNSKEY => Self::DNSKEY(DNSKEY::read::<E>(buf, header.rdlen())?),
+            RType::CDNSKEY => Self::CDNSKEY(DNSKEY::read::<E>(buf, header.rdlen())?),
+            RType::NSEC3 => Self::NSEC3(NSEC3::read::<E>(buf, header.rdlen())?),
+            RType::NSEC3PARAM => Self::NSEC3PARAM(NSEC3PARAM::read::<E>(buf)?),
+            RType::SVCB => Self::SVCB(SVCB::read::<E>(buf, header.rdlen())?),
+            RType::HTTPS => Self::HTTPS(SVCB::read::<E>(buf, header.rdlen())?),
+            RType::URI => Self::URI(URI::read::<E>(buf, header.rdlen())?),
+            RType::CAA => Self::CAA(CAA::read::<E>(buf, header.rdlen())?),
+            // Query types never appear in records. If they do anyway, they
+            // are handled like unknown types.
+            RType::AXFR | RType::MAILB | RType::MAILA | RType::ANY | RType::UNKNOWN(_) => {
+                Self::UNKNOWN(UNKNOWN::read::<E>(buf, header.ty().into(), header.rdlen())?)
+            }
         };
 
         // Check that we read the correct number of octets defined by RDLEN
@@ -444,41 +596,210 @@ impl RData {
         Ok(rdata)
     }
 
-    /// Tries to parse `rdata` as [`RData`].
+    /// Writes the RDATA to `buf` and compresses the domain names of NS,
+    /// CNAME, PTR, SOA, MX and MINFO records by using the provided
+    /// [`CompressionMap`]. All other types are written as is.
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, RDataError> {
+        match self {
+            RData::NS(name) | RData::CNAME(name) | RData::PTR(name) => {
+                name.write_compressed::<E>(buf, names).context(NameSnafu)
+            }
+            RData::SOA(soa) => soa.write_compressed::<E>(buf, names),
+            RData::MX(mx) => mx.write_compressed::<E>(buf, names),
+            RData::MINFO(minfo) => minfo.write_compressed::<E>(buf, names),
+            _ => self.write::<E>(buf),
+        }
+    }
+
+    /// Tries to parse `rdata` in presentation format as [`RData`]. Domain
+    /// names have to be fully qualified.
     pub fn try_from_str(ty: RType, rdata: &str) -> Result<Self, RDataParseError> {
+        let mut tokens = Vec::new();
+
+        for token in Lexer::new(rdata) {
+            let token = token.map_err(|err| RDataParseError::new(ty, err.to_string()))?;
+
+            if token.text().is_some() {
+                tokens.push(token);
+            }
+        }
+
+        Self::try_from_tokens(ty, &tokens, &Name::default())
+    }
+
+    /// Tries to parse the lexed `tokens` of RDATA in presentation format.
+    /// Unlike [`RData::try_from_parts`], only an unquoted `\#` starts the
+    /// generic encoding of RFC 3597. A quoted `"\#"` is a regular string,
+    /// e.g. of TXT records.
+    pub(crate) fn try_from_tokens(
+        ty: RType,
+        tokens: &[Token],
+        origin: &Name,
+    ) -> Result<Self, RDataParseError> {
+        let parts: Vec<&str> = tokens.iter().filter_map(Token::text).collect();
+
+        match tokens.first().map(|token| &token.kind) {
+            Some(TokenKind::Word(word)) if word == "\\#" => Self::try_from_generic(ty, &parts[1..]),
+            _ => Self::try_from_presentation(ty, &parts, origin),
+        }
+    }
+
+    /// Tries to parse the whitespace separated `parts` of RDATA in
+    /// presentation format, e.g. as found in master files. Relative domain
+    /// names are completed with `origin`. Every type can also use the
+    /// generic `\# <length> <hex>` encoding of RFC 3597.
+    pub fn try_from_parts(
+        ty: RType,
+        parts: &[&str],
+        origin: &Name,
+    ) -> Result<Self, RDataParseError> {
+        if parts.first() == Some(&"\\#") {
+            return Self::try_from_generic(ty, &parts[1..]);
+        }
+
+        Self::try_from_presentation(ty, parts, origin)
+    }
+
+    /// Parses the type specific presentation format of RDATA.
+    fn try_from_presentation(
+        ty: RType,
+        parts: &[&str],
+        origin: &Name,
+    ) -> Result<Self, RDataParseError> {
         match ty {
-            RType::A => match rdata.parse::<Ipv4Addr>() {
+            RType::A => match expect_parts(ty, parts, 1)?[0].parse::<Ipv4Addr>() {
                 Ok(ip) => Ok(Self::A(ip)),
                 Err(err) => Err(RDataParseError::new(ty, err.to_string())),
             },
-            RType::NS => match Name::try_from(rdata) {
-                Ok(name) => Ok(Self::NS(name)),
-                Err(err) => Err(RDataParseError::new(ty, err.to_string())),
-            },
-            RType::CNAME => match Name::try_from(rdata) {
-                Ok(name) => Ok(Self::CNAME(name)),
-                Err(err) => Err(RDataParseError::new(ty, err.to_string())),
-            },
-            RType::SOA => todo!(),
-            RType::NULL => todo!(),
-            RType::PTR => todo!(),
-            RType::HINFO => todo!(),
-            RType::MINFO => todo!(),
-            RType::MX => todo!(),
-            RType::TXT => todo!(),
-            RType::AAAA => match rdata.parse::<Ipv6Addr>() {
+            RType::NS => Ok(Self::NS(parse_name(
+                ty,
+                expect_parts(ty, parts, 1)?[0],
+                origin,
+            )?)),
+            RType::CNAME => Ok(Self::CNAME(parse_name(
+                ty,
+                expect_parts(ty, parts, 1)?[0],
+                origin,
+            )?)),
+            RType::SOA => Ok(Self::SOA(SOA::try_from_parts(
+                expect_parts(ty, parts, 7)?,
+                origin,
+            )?)),
+            RType::PTR => Ok(Self::PTR(parse_name(
+                ty,
+                expect_parts(ty, parts, 1)?[0],
+                origin,
+            )?)),
+            RType::HINFO => Ok(Self::HINFO(HINFO::try_from_parts(expect_parts(
+                ty, parts, 2,
+            )?)?)),
+            RType::MINFO => Ok(Self::MINFO(MINFO::try_from_parts(
+                expect_parts(ty, parts, 2)?,
+                origin,
+            )?)),
+            RType::MX => Ok(Self::MX(MX::try_from_parts(
+                expect_parts(ty, parts, 2)?,
+                origin,
+            )?)),
+            RType::TXT => Ok(Self::TXT(TXT::try_from_parts(parts)?)),
+            RType::AAAA => match expect_parts(ty, parts, 1)?[0].parse::<Ipv6Addr>() {
                 Ok(ip) => Ok(Self::AAAA(ip)),
                 Err(err) => Err(RDataParseError::new(ty, err.to_string())),
             },
-            RType::OPT => todo!(),
-            RType::AXFR => todo!(),
-            RType::MAILB => todo!(),
-            RType::MAILA => todo!(),
-            RType::ANY => todo!(),
-            RType::UNKNOWN(_) => todo!(),
+            RType::SRV => Ok(Self::SRV(SRV::try_from_parts(parts, origin)?)),
+            RType::NAPTR => Ok(Self::NAPTR(NAPTR::try_from_parts(parts, origin)?)),
+            RType::DNAME => Ok(Self::DNAME(parse_name(
+                ty,
+                expect_parts(ty, parts, 1)?[0],
+                origin,
+            )?)),
+            RType::SSHFP => Ok(Self::SSHFP(SSHFP::try_from_parts(parts)?)),
+            RType::TLSA => Ok(Self::TLSA(TLSA::try_from_parts(parts)?)),
+            RType::DS => Ok(Self::DS(DS::try_from_parts(ty, parts)?)),
+            RType::CDS => Ok(Self::CDS(DS::try_from_parts(ty, parts)?)),
+            RType::RRSIG => Ok(Self::RRSIG(RRSIG::try_from_parts(parts, origin)?)),
+            RType::NSEC => Ok(Self::NSEC(NSEC::try_from_parts(parts, origin)?)),
+            RType::DNSKEY => Ok(Self::DNSKEY(DNSKEY::try_from_parts(ty, parts)?)),
+            RType::CDNSKEY => Ok(Self::CDNSKEY(DNSKEY::try_from_parts(ty, parts)?)),
+            RType::NSEC3 => Ok(Self::NSEC3(NSEC3::try_from_parts(parts)?)),
+            RType::NSEC3PARAM => Ok(Self::NSEC3PARAM(NSEC3PARAM::try_from_parts(parts)?)),
+            RType::SVCB => Ok(Self::SVCB(SVCB::try_from_parts(ty, parts, origin)?)),
+            RType::HTTPS => Ok(Self::HTTPS(SVCB::try_from_parts(ty, parts, origin)?)),
+            RType::URI => Ok(Self::URI(URI::try_from_parts(parts)?)),
+            RType::CAA => Ok(Self::CAA(CAA::try_from_parts(parts)?)),
+            // NULL records have no presentation format besides the generic
+            // one (RFC 1035 Section 3.3.10).
+            RType::NULL | RType::UNKNOWN(_) => Err(RDataParseError::new(
+                ty,
+                format!(
+                    "expected generic RDATA '\\# <length> <hex>', got '{}'",
+                    parts.join(" ")
+                ),
+            )),
+            RType::OPT | RType::AXFR | RType::MAILB | RType::MAILA | RType::ANY => Err(
+                RDataParseError::new(ty, "pseudo type has no presentation format".into()),
+            ),
+        }
+    }
+
+    /// Parses the RFC 3597 generic RDATA encoding. The data of known types
+    /// is decoded from its wire format.
+    fn try_from_generic(ty: RType, parts: &[&str]) -> Result<Self, RDataParseError> {
+        let data = parse_generic(ty, parts)?;
+
+        match ty {
+            RType::NULL => Ok(Self::NULL(NULL::from(data))),
+            RType::UNKNOWN(code) => Ok(Self::UNKNOWN(UNKNOWN::new(code, data))),
+            RType::OPT | RType::AXFR | RType::MAILB | RType::MAILA | RType::ANY => Err(
+                RDataParseError::new(ty, "pseudo type has no presentation format".into()),
+            ),
+            _ => {
+                let mut header = RHeader::default();
+                header.set_ty(ty);
+                header.set_rdlen(data.len() as u16);
+
+                let mut buf = ReadBuffer::new(&data);
+                Self::read::<BigEndian>(&mut buf, &header)
+                    .map_err(|err| RDataParseError::new(ty, err.to_string()))
+            }
         }
     }
 
+    /// Returns the canonical form of the RDATA, which has all domain names
+    /// of NS, CNAME, SOA, PTR, MINFO, MX, SRV, NAPTR, DNAME and RRSIG records
+    /// converted to lowercase. Names are never compressed in the canonical
+    /// form. See [RFC 4034 Section 6.2](https://datatracker.ietf.org/doc/html/rfc4034#section-6.2)
+    /// and [RFC 6840 Section 5.1](https://datatracker.ietf.org/doc/html/rfc6840#section-5.1).
+    pub fn to_canonical(&self) -> Self {
+        match self {
+            RData::NS(name) => RData::NS(name.to_lowercase()),
+            RData::CNAME(name) => RData::CNAME(name.to_lowercase()),
+            RData::SOA(soa) => RData::SOA(soa.to_lowercase()),
+            RData::PTR(name) => RData::PTR(name.to_lowercase()),
+            RData::MINFO(minfo) => RData::MINFO(minfo.to_lowercase()),
+            RData::MX(mx) => RData::MX(mx.to_lowercase()),
+            RData::SRV(srv) => RData::SRV(srv.to_lowercase()),
+            RData::NAPTR(naptr) => RData::NAPTR(naptr.to_lowercase()),
+            RData::DNAME(name) => RData::DNAME(name.to_lowercase()),
+            RData::RRSIG(rrsig) => RData::RRSIG(rrsig.to_lowercase()),
+            rdata => rdata.clone(),
+        }
+    }
+
+    /// Returns the canonical wire format of the RDATA, see
+    /// [`RData::to_canonical`]. RRs of an RRset are sorted by comparing
+    /// these octets. See [RFC 4034 Section 6.3](https://datatracker.ietf.org/doc/html/rfc4034#section-6.3).
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, RDataError> {
+        let mut buf = WriteBuffer::new();
+        self.to_canonical().write::<BigEndian>(&mut buf)?;
+
+        Ok(buf.owned_bytes())
+    }
+
     /// Returns the size of RDATA
     pub fn size(&self) -> usize {
         match self {
@@ -486,19 +807,30 @@ impl RData {
             RData::NS(ns) => ns.size(),
             RData::CNAME(cname) => cname.size(),
             RData::SOA(soa) => soa.size(),
-            RData::NULL(_) => 0,
+            RData::NULL(null) => null.size(),
             RData::PTR(ptr) => ptr.size(),
             RData::HINFO(hinfo) => hinfo.size(),
             RData::MINFO(minfo) => minfo.size(),
             RData::MX(mx) => mx.size(),
             RData::TXT(txt) => txt.size(),
             RData::AAAA(_) => 16,
-            RData::OPT(_) => todo!(),
-            RData::AXFR => todo!(),
-            RData::MAILB => todo!(),
-            RData::MAILA => todo!(),
-            RData::ANY => todo!(),
-            RData::UNKNOWN => todo!(),
+            RData::SRV(srv) => srv.size(),
+            RData::NAPTR(naptr) => naptr.size(),
+            RData::DNAME(dname) => dname.size(),
+            RData::SSHFP(sshfp) => sshfp.size(),
+            RData::OPT(opt) => opt.size(),
+            RData::TLSA(tlsa) => tlsa.size(),
+            RData::DS(ds) | RData::CDS(ds) => ds.size(),
+            RData::RRSIG(rrsig) => rrsig.size(),
+            RData::NSEC(nsec) => nsec.size(),
+            RData::DNSKEY(dnskey) | RData::CDNSKEY(dnskey) => dnskey.size(),
+            RData::NSEC3(nsec3) => nsec3.size(),
+            RData::NSEC3PARAM(param) => param.size(),
+            RData::SVCB(svcb) | RData::HTTPS(svcb) => svcb.size(),
+            RData::URI(uri) => uri.size(),
+            RData::CAA(caa) => caa.size(),
+            RData::AXFR | RData::MAILB | RData::MAILA | RData::ANY => 0,
+            RData::UNKNOWN(unknown) => unknown.size(),
         }
     }
 }
@@ -1,8 +1,15 @@
 use std::fmt::Display;
 
-use binbuf::{Readable, Writeable};
+use binbuf::{Endianness, Readable, WriteBuffer, Writeable};
+use snafu::ResultExt;
 
-use crate::types::dns::Name;
+use crate::types::{
+    dns::{CompressionMap, Name},
+    rr::{
+        rdata::{parse_name, parse_number, BufferWriteSnafu, NameSnafu, RDataError},
+        RDataParseError, RType,
+    },
+};
 
 #[derive(Debug, Clone, Readable, Writeable)]
 pub struct MX {
@@ -12,11 +19,43 @@ pub struct MX {
 
 impl Display for MX {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "PREF: {} EX: {}", self.preference, self.exchange)
+        write!(f, "{} {}", self.preference, self.exchange)
     }
 }
 
 impl MX {
+    /// Parses the PREFERENCE and EXCHANGE fields in presentation format.
+    pub fn try_from_parts(parts: &[&str], origin: &Name) -> Result<Self, RDataParseError> {
+        Ok(Self {
+            preference: parse_number(RType::MX, parts[0])?,
+            exchange: parse_name(RType::MX, parts[1], origin)?,
+        })
+    }
+
+    /// Writes the [`MX`] record and compresses EXCHANGE by using the
+    /// provided [`CompressionMap`].
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, RDataError> {
+        let n = self.preference.write::<E>(buf).context(BufferWriteSnafu)?;
+        let m = self
+            .exchange
+            .write_compressed::<E>(buf, names)
+            .context(NameSnafu)?;
+
+        Ok(n + m)
+    }
+
+    /// Returns a copy with the domain name converted to lowercase.
+    pub fn to_lowercase(&self) -> Self {
+        Self {
+            exchange: self.exchange.to_lowercase(),
+            ..self.clone()
+        }
+    }
+
     /// Returns the size of the [`MX`] record.
     pub fn size(&self) -> usize {
         // Returns the sum of EXCHANGE's len and 2 for PREFERENCE u16.
@@ -0,0 +1,161 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadError, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::{
+    constants::MAX_CHAR_STRING_LENGTH,
+    types::{
+        dns::Name,
+        rr::{
+            rdata::{
+                expect_parts, format_char_string, parse_char_string, parse_name, parse_number,
+            },
+            RDataParseError, RType,
+        },
+    },
+};
+
+/// ```text
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                     ORDER                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                   PREFERENCE                  |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                     FLAGS                     /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                   SERVICES                    /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                    REGEXP                     /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                  REPLACEMENT                  /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// FLAGS, SERVICES and REGEXP are <character-string>s. The REPLACEMENT is
+/// never compressed.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc3403#section-4.1
+#[derive(Debug, Clone)]
+pub struct NAPTR {
+    order: u16,
+    preference: u16,
+    flags: Vec<u8>,
+    services: Vec<u8>,
+    regexp: Vec<u8>,
+    replacement: Name,
+}
+
+impl Display for NAPTR {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            self.order,
+            self.preference,
+            format_char_string(&self.flags),
+            format_char_string(&self.services),
+            format_char_string(&self.regexp),
+            self.replacement
+        )
+    }
+}
+
+impl Readable for NAPTR {
+    type Error = ReadError;
+
+    fn read<E: Endianness>(buf: &mut ReadBuffer) -> Result<Self, Self::Error> {
+        let order = u16::read::<E>(buf)?;
+        let preference = u16::read::<E>(buf)?;
+        let flags = buf.read_char_string(Some(MAX_CHAR_STRING_LENGTH))?.to_vec();
+        let services = buf.read_char_string(Some(MAX_CHAR_STRING_LENGTH))?.to_vec();
+        let regexp = buf.read_char_string(Some(MAX_CHAR_STRING_LENGTH))?.to_vec();
+        let replacement = Name::read::<E>(buf)?;
+
+        Ok(Self {
+            order,
+            preference,
+            flags,
+            services,
+            regexp,
+            replacement,
+        })
+    }
+}
+
+impl Writeable for NAPTR {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.order.write::<E>(buf)?;
+            self.preference.write::<E>(buf)?;
+            buf.write_char_string(&self.flags, Some(MAX_CHAR_STRING_LENGTH))?;
+            buf.write_char_string(&self.services, Some(MAX_CHAR_STRING_LENGTH))?;
+            buf.write_char_string(&self.regexp, Some(MAX_CHAR_STRING_LENGTH))?;
+            self.replacement.write::<E>(buf)?
+        };
+
+        Ok(n)
+    }
+}
+
+impl NAPTR {
+    /// Parses the ORDER, PREFERENCE, FLAGS, SERVICES, REGEXP and REPLACEMENT
+    /// fields in presentation format.
+    pub fn try_from_parts(parts: &[&str], origin: &Name) -> Result<Self, RDataParseError> {
+        let parts = expect_parts(RType::NAPTR, parts, 6)?;
+
+        Ok(Self {
+            order: parse_number(RType::NAPTR, parts[0])?,
+            preference: parse_number(RType::NAPTR, parts[1])?,
+            flags: parse_char_string(RType::NAPTR, parts[2])?,
+            services: parse_char_string(RType::NAPTR, parts[3])?,
+            regexp: parse_char_string(RType::NAPTR, parts[4])?,
+            replacement: parse_name(RType::NAPTR, parts[5], origin)?,
+        })
+    }
+
+    pub fn order(&self) -> u16 {
+        self.order
+    }
+
+    pub fn preference(&self) -> u16 {
+        self.preference
+    }
+
+    pub fn flags(&self) -> &[u8] {
+        &self.flags
+    }
+
+    pub fn services(&self) -> &[u8] {
+        &self.services
+    }
+
+    pub fn regexp(&self) -> &[u8] {
+        &self.regexp
+    }
+
+    pub fn replacement(&self) -> &Name {
+        &self.replacement
+    }
+
+    /// Returns a copy with the domain name converted to lowercase.
+    pub fn to_lowercase(&self) -> Self {
+        Self {
+            replacement: self.replacement.to_lowercase(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the size of the [`NAPTR`] record.
+    pub fn size(&self) -> usize {
+        // Two u16 fields, three <character-string>s with their length octet
+        // and the REPLACEMENT
+        4 + self.flags.len() + self.services.len() + self.regexp.len() + 3 + self.replacement.size()
+    }
+}
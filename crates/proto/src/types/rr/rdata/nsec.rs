@@ -0,0 +1,95 @@
+use std::fmt::Display;
+
+use binbuf::{bytes_written, Endianness, ReadBuffer, Readable, WriteBuffer, WriteError, Writeable};
+use snafu::ResultExt;
+
+use crate::types::{
+    dns::Name,
+    rr::{
+        rdata::{parse_name, NameSnafu, RDataError, TypeBitMaps},
+        RDataParseError, RType,
+    },
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                      Next Domain Name                         /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                       Type Bit Maps                           /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// The Next Domain Name is never compressed.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc4034#section-4.1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NSEC {
+    next: Name,
+    types: TypeBitMaps,
+}
+
+impl Display for NSEC {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.next, self.types)
+    }
+}
+
+impl Writeable for NSEC {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.next.write::<E>(buf)?;
+            self.types.write::<E>(buf)?
+        };
+
+        Ok(n)
+    }
+}
+
+impl NSEC {
+    pub fn new(next: Name, types: TypeBitMaps) -> Self {
+        Self { next, types }
+    }
+
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> Result<Self, RDataError> {
+        let start = buf.offset();
+
+        let next = Name::read::<E>(buf).context(NameSnafu)?;
+        let read = buf.offset() - start;
+        let types = TypeBitMaps::read::<E>(buf, (rdlen as usize).saturating_sub(read))?;
+
+        Ok(Self { next, types })
+    }
+
+    /// Parses the next domain name followed by the list of types in
+    /// presentation format.
+    pub fn try_from_parts(parts: &[&str], origin: &Name) -> Result<Self, RDataParseError> {
+        let ty = RType::NSEC;
+
+        if parts.is_empty() {
+            return Err(RDataParseError::new(ty, "expected next domain name".into()));
+        }
+
+        Ok(Self {
+            next: parse_name(ty, parts[0], origin)?,
+            types: TypeBitMaps::try_from_parts(ty, &parts[1..])?,
+        })
+    }
+
+    pub fn next(&self) -> &Name {
+        &self.next
+    }
+
+    pub fn types(&self) -> &TypeBitMaps {
+        &self.types
+    }
+
+    /// Returns the size of the [`NSEC`] record.
+    pub fn size(&self) -> usize {
+        self.next.size() + self.types.size()
+    }
+}
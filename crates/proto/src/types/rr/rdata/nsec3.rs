@@ -0,0 +1,203 @@
+use std::fmt::Display;
+
+use binbuf::{bytes_written, Endianness, ReadBuffer, Readable, WriteBuffer, WriteError, Writeable};
+use snafu::ResultExt;
+
+use crate::types::{
+    dns::Name,
+    rr::{
+        rdata::{
+            format_base32hex, format_salt, parse_base32hex, parse_number, parse_salt, BufferSnafu,
+            RDataError, TypeBitMaps,
+        },
+        RDataParseError, RType,
+    },
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |   Hash Alg.   |     Flags     |          Iterations           |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  Salt Length  |                     Salt                      /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  Hash Length  |             Next Hashed Owner Name            /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                         Type Bit Maps                         /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// The salt is written as hexadecimal string (`-` if empty) and the next
+/// hashed owner name as base32hex string in presentation format.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc5155#section-3.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NSEC3 {
+    hash_algorithm: u8,
+    flags: u8,
+    iterations: u16,
+    salt: Vec<u8>,
+    next_hashed: Vec<u8>,
+    types: TypeBitMaps,
+}
+
+impl Display for NSEC3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            format_salt(&self.salt),
+            format_base32hex(&self.next_hashed)
+        )?;
+
+        if !self.types.types().is_empty() {
+            write!(f, " {}", self.types)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Writeable for NSEC3 {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.hash_algorithm.write::<E>(buf)?;
+            self.flags.write::<E>(buf)?;
+            self.iterations.write::<E>(buf)?;
+            (self.salt.len() as u8).write::<E>(buf)?;
+            buf.write(&mut self.salt.clone());
+            (self.next_hashed.len() as u8).write::<E>(buf)?;
+            buf.write(&mut self.next_hashed.clone());
+            self.types.write::<E>(buf)?
+        };
+
+        Ok(n)
+    }
+}
+
+impl NSEC3 {
+    /// The NSEC3 record may cover unsigned delegations.
+    pub const FLAG_OPT_OUT: u8 = 0x01;
+
+    pub fn new(
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: Vec<u8>,
+        next_hashed: Vec<u8>,
+        types: TypeBitMaps,
+    ) -> Self {
+        Self {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed,
+            types,
+        }
+    }
+
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> Result<Self, RDataError> {
+        let start = buf.offset();
+
+        let hash_algorithm = u8::read::<E>(buf).context(BufferSnafu)?;
+        let flags = u8::read::<E>(buf).context(BufferSnafu)?;
+        let iterations = u16::read::<E>(buf).context(BufferSnafu)?;
+
+        let salt_len = u8::read::<E>(buf).context(BufferSnafu)?;
+        let salt = buf.read_vec(salt_len as usize).context(BufferSnafu)?;
+
+        let hash_len = u8::read::<E>(buf).context(BufferSnafu)?;
+        let next_hashed = buf.read_vec(hash_len as usize).context(BufferSnafu)?;
+
+        let read = buf.offset() - start;
+        let types = TypeBitMaps::read::<E>(buf, (rdlen as usize).saturating_sub(read))?;
+
+        Ok(Self {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed,
+            types,
+        })
+    }
+
+    /// Parses the hash algorithm, flags, iterations, salt, next hashed owner
+    /// name and the list of types in presentation format.
+    pub fn try_from_parts(parts: &[&str]) -> Result<Self, RDataParseError> {
+        let ty = RType::NSEC3;
+
+        if parts.len() < 5 {
+            return Err(RDataParseError::new(
+                ty,
+                format!("expected at least 5 fields, got {}", parts.len()),
+            ));
+        }
+
+        let next_hashed = parse_base32hex(ty, parts[4])?;
+
+        if next_hashed.is_empty() || next_hashed.len() > u8::MAX as usize {
+            return Err(RDataParseError::new(
+                ty,
+                format!("invalid next hashed owner name '{}'", parts[4]),
+            ));
+        }
+
+        Ok(Self {
+            hash_algorithm: parse_number(ty, parts[0])?,
+            flags: parse_number(ty, parts[1])?,
+            iterations: parse_number(ty, parts[2])?,
+            salt: parse_salt(ty, parts[3])?,
+            next_hashed,
+            types: TypeBitMaps::try_from_parts(ty, &parts[5..])?,
+        })
+    }
+
+    pub fn hash_algorithm(&self) -> u8 {
+        self.hash_algorithm
+    }
+
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    pub fn is_opt_out(&self) -> bool {
+        self.flags & Self::FLAG_OPT_OUT != 0
+    }
+
+    pub fn iterations(&self) -> u16 {
+        self.iterations
+    }
+
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    pub fn next_hashed(&self) -> &[u8] {
+        &self.next_hashed
+    }
+
+    pub fn types(&self) -> &TypeBitMaps {
+        &self.types
+    }
+
+    /// Returns the hash encoded as base32hex in the first label of the NSEC3
+    /// owner name. Returns `None` if the label is not valid base32hex.
+    pub fn owner_hash(owner: &Name) -> Option<Vec<u8>> {
+        let label = owner.labels().first()?;
+        parse_base32hex(RType::NSEC3, &label.to_string()).ok()
+    }
+
+    /// Returns the size of the [`NSEC3`] record.
+    pub fn size(&self) -> usize {
+        6 + self.salt.len() + self.next_hashed.len() + self.types.size()
+    }
+}
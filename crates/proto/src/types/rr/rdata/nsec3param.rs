@@ -0,0 +1,112 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadResult, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::types::rr::{
+    rdata::{expect_parts, format_salt, parse_number, parse_salt},
+    RDataParseError, RType,
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |   Hash Alg.   |     Flags     |          Iterations           |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  Salt Length  |                     Salt                      /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc5155#section-4.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NSEC3PARAM {
+    hash_algorithm: u8,
+    flags: u8,
+    iterations: u16,
+    salt: Vec<u8>,
+}
+
+impl Display for NSEC3PARAM {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            format_salt(&self.salt)
+        )
+    }
+}
+
+impl Writeable for NSEC3PARAM {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.hash_algorithm.write::<E>(buf)?;
+            self.flags.write::<E>(buf)?;
+            self.iterations.write::<E>(buf)?;
+            (self.salt.len() as u8).write::<E>(buf)?;
+            buf.write(&mut self.salt.clone())
+        };
+
+        Ok(n)
+    }
+}
+
+impl NSEC3PARAM {
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer) -> ReadResult<Self> {
+        let hash_algorithm = u8::read::<E>(buf)?;
+        let flags = u8::read::<E>(buf)?;
+        let iterations = u16::read::<E>(buf)?;
+
+        let salt_len = u8::read::<E>(buf)?;
+        let salt = buf.read_vec(salt_len as usize)?;
+
+        Ok(Self {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+        })
+    }
+
+    /// Parses the hash algorithm, flags, iterations and salt in presentation
+    /// format.
+    pub fn try_from_parts(parts: &[&str]) -> Result<Self, RDataParseError> {
+        let ty = RType::NSEC3PARAM;
+        let parts = expect_parts(ty, parts, 4)?;
+
+        Ok(Self {
+            hash_algorithm: parse_number(ty, parts[0])?,
+            flags: parse_number(ty, parts[1])?,
+            iterations: parse_number(ty, parts[2])?,
+            salt: parse_salt(ty, parts[3])?,
+        })
+    }
+
+    pub fn hash_algorithm(&self) -> u8 {
+        self.hash_algorithm
+    }
+
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    pub fn iterations(&self) -> u16 {
+        self.iterations
+    }
+
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    /// Returns the size of the [`NSEC3PARAM`] record.
+    pub fn size(&self) -> usize {
+        5 + self.salt.len()
+    }
+}
@@ -6,6 +6,8 @@ use binbuf::{
     Endianness, Writeable,
 };
 
+use crate::types::rr::rdata::format_generic;
+
 #[derive(Debug, Default, Clone)]
 pub struct NULL {
     data: Vec<u8>,
@@ -13,7 +15,7 @@ pub struct NULL {
 
 impl Display for NULL {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.data)
+        write!(f, "{}", format_generic(&self.data))
     }
 }
 
@@ -25,6 +27,12 @@ impl Writeable for NULL {
     }
 }
 
+impl From<Vec<u8>> for NULL {
+    fn from(data: Vec<u8>) -> Self {
+        Self { data }
+    }
+}
+
 impl NULL {
     pub fn new() -> Self {
         Self::default()
@@ -38,4 +46,9 @@ impl NULL {
 
         Ok(Self::new())
     }
+
+    /// Returns the size of the RDATA.
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
 }
@@ -1,16 +1,21 @@
-use std::{collections::HashMap, fmt::Display};
+use std::fmt::Display;
 
 use binbuf::{read::ReadBuffer, Endianness, ReadResult, WriteBuffer, WriteError, Writeable};
 
 use crate::types::{
     edns::{EdnsHeader, Option, OptionCode},
-    rr::RHeader,
+    rr::{RData, RHeader, Record},
 };
 
-#[derive(Debug, Clone)]
+/// The OPT pseudo-RR. The EDNS header is stored in the CLASS and TTL fields
+/// of the RR header, the options are stored in the RDATA. See
+/// [RFC 6891](https://datatracker.ietf.org/doc/html/rfc6891#section-6).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct OPT {
     header: EdnsHeader,
-    options: HashMap<OptionCode, Option>,
+
+    /// The options in wire order. Options can appear multiple times.
+    options: Vec<Option>,
 }
 
 impl Display for OPT {
@@ -20,6 +25,15 @@ impl Display for OPT {
 }
 
 impl OPT {
+    /// Creates a new OPT pseudo-RR without any options, which advertises the
+    /// provided UDP payload size.
+    pub fn new(sender_payload_size: u16) -> Self {
+        Self {
+            header: EdnsHeader::new(sender_payload_size),
+            options: Vec::new(),
+        }
+    }
+
     pub fn read<E: Endianness>(buf: &mut ReadBuffer, rheader: &RHeader) -> ReadResult<Self> {
         // First we create the EDNS header
         let header = EdnsHeader::from(rheader);
@@ -27,16 +41,52 @@ impl OPT {
         // Setup unpacking of EDNS options
         let start_len = buf.len();
         let rdlen = rheader.rdlen() as usize;
-        let mut options = HashMap::new();
+        let mut options = Vec::new();
 
         // Unpack options until rdlen is exhausted
         while start_len - buf.len() < rdlen {
-            let option = Option::read::<E>(buf)?;
-            options.insert(option.code(), option);
+            options.push(Option::read::<E>(buf)?);
         }
 
         Ok(Self { header, options })
     }
+
+    pub fn header(&self) -> &EdnsHeader {
+        &self.header
+    }
+
+    pub fn header_mut(&mut self) -> &mut EdnsHeader {
+        &mut self.header
+    }
+
+    pub fn options(&self) -> &Vec<Option> {
+        &self.options
+    }
+
+    /// Returns the first option with the provided `code`.
+    pub fn option(&self, code: OptionCode) -> std::option::Option<&Option> {
+        self.options.iter().find(|o| o.code() == code)
+    }
+
+    pub fn add_option(&mut self, option: Option) {
+        self.options.push(option)
+    }
+
+    /// Returns the size of the RDATA, which contains all options. Each
+    /// option has a fixed length of 4 octets for OPTION-CODE and
+    /// OPTION-LENGTH.
+    pub fn size(&self) -> usize {
+        self.options.iter().map(|o| 4 + o.size() as usize).sum()
+    }
+
+    /// Converts this OPT into a complete [`Record`]. The RR header is built
+    /// from the EDNS header.
+    pub fn into_record(self) -> Record {
+        let mut record = Record::new_with_header(self.header.to_rheader());
+        record.set_rdata(RData::OPT(self));
+        record.normalize_rdlen();
+        record
+    }
 }
 
 impl Writeable for OPT {
@@ -45,7 +95,7 @@ impl Writeable for OPT {
     fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
         let mut n = 0;
 
-        for option in self.options.values() {
+        for option in &self.options {
             n += option.write::<E>(buf)?;
         }
 
@@ -0,0 +1,444 @@
+//! Helpers to parse and format RDATA fields in presentation format as used
+//! in master files. See [RFC 1035 Section 5.1](https://datatracker.ietf.org/doc/html/rfc1035#section-5.1)
+//! and [RFC 3597 Section 5](https://datatracker.ietf.org/doc/html/rfc3597#section-5).
+
+use std::str::FromStr;
+
+use crate::{
+    constants::MAX_CHAR_STRING_LENGTH,
+    types::{
+        dns::Name,
+        rr::{RDataParseError, RType},
+    },
+};
+
+/// Ensures that the RDATA of type `ty` consists of exactly `count` parts.
+pub(crate) fn expect_parts<'a, 'b>(
+    ty: RType,
+    parts: &'a [&'b str],
+    count: usize,
+) -> Result<&'a [&'b str], RDataParseError> {
+    if parts.len() != count {
+        return Err(RDataParseError::new(
+            ty,
+            format!("expected {count} field(s), got {}", parts.len()),
+        ));
+    }
+
+    Ok(parts)
+}
+
+/// Parses a (possibly relative) domain name in presentation format.
+pub(crate) fn parse_name(ty: RType, text: &str, origin: &Name) -> Result<Name, RDataParseError> {
+    Name::from_relative(text, origin)
+        .map_err(|err| RDataParseError::new(ty, format!("invalid domain name '{text}': {err}")))
+}
+
+/// Parses a decimal number, e.g. the MX preference.
+pub(crate) fn parse_number<T: FromStr>(ty: RType, text: &str) -> Result<T, RDataParseError> {
+    text.parse()
+        .map_err(|_| RDataParseError::new(ty, format!("invalid number '{text}'")))
+}
+
+/// Parses a time value in seconds. Besides plain seconds, the commonly used
+/// unit suffixes `s`, `m`, `h`, `d` and `w` are supported, e.g. `1h30m`.
+pub(crate) fn parse_duration(text: &str) -> Option<u32> {
+    if let Ok(secs) = text.parse::<u32>() {
+        return Some(secs);
+    }
+
+    let mut secs: u32 = 0;
+    let mut value: Option<u32> = None;
+
+    for c in text.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            value = Some(value.unwrap_or(0).checked_mul(10)?.checked_add(digit)?);
+            continue;
+        }
+
+        let factor = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+
+        secs = secs.checked_add(value.take()?.checked_mul(factor)?)?;
+    }
+
+    // Trailing digits without a unit are not allowed
+    match value {
+        Some(_) => None,
+        None => Some(secs),
+    }
+}
+
+/// Parses a <character-string>. The surrounding quotes are already removed
+/// by the lexer. See [`parse_escaped`] for the supported escapes.
+pub(crate) fn parse_char_string(ty: RType, text: &str) -> Result<Vec<u8>, RDataParseError> {
+    let bytes = parse_escaped(ty, text)?;
+
+    if bytes.len() > MAX_CHAR_STRING_LENGTH as usize {
+        return Err(RDataParseError::new(
+            ty,
+            format!("character-string too long in '{text}'"),
+        ));
+    }
+
+    Ok(bytes)
+}
+
+/// Parses text which can contain escape sequences. `\X` escapes the
+/// character X and `\DDD` denotes the octet with the decimal value DDD.
+pub(crate) fn parse_escaped(ty: RType, text: &str) -> Result<Vec<u8>, RDataParseError> {
+    let invalid = |msg: &str| RDataParseError::new(ty, format!("{msg} in '{text}'"));
+
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+
+    while let Some(b) = iter.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+
+        match iter.next() {
+            Some(d) if d.is_ascii_digit() => {
+                let mut value = (d - b'0') as u16;
+
+                for _ in 0..2 {
+                    match iter.next() {
+                        Some(d) if d.is_ascii_digit() => value = value * 10 + (d - b'0') as u16,
+                        _ => return Err(invalid("invalid \\DDD escape")),
+                    }
+                }
+
+                match u8::try_from(value) {
+                    Ok(value) => bytes.push(value),
+                    Err(_) => return Err(invalid("invalid \\DDD escape")),
+                }
+            }
+            Some(c) => bytes.push(c),
+            None => return Err(invalid("dangling escape")),
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Parses hexadecimal data which can be split into multiple parts, e.g. the
+/// fingerprint of SSHFP records.
+pub(crate) fn parse_hex(ty: RType, parts: &[&str]) -> Result<Vec<u8>, RDataParseError> {
+    let hex = parts.concat();
+
+    decode_hex(&hex).ok_or_else(|| RDataParseError::new(ty, format!("invalid hex data '{hex}'")))
+}
+
+/// Parses base64 encoded data which can be split into multiple parts, e.g.
+/// the public key of DNSKEY records. See
+/// [RFC 4648 Section 4](https://datatracker.ietf.org/doc/html/rfc4648#section-4).
+pub(crate) fn parse_base64(ty: RType, parts: &[&str]) -> Result<Vec<u8>, RDataParseError> {
+    let text = parts.concat();
+
+    decode_base64(&text)
+        .ok_or_else(|| RDataParseError::new(ty, format!("invalid base64 data '{text}'")))
+}
+
+/// Parses base32hex encoded data without padding, e.g. the next hashed
+/// owner name of NSEC3 records. See
+/// [RFC 4648 Section 7](https://datatracker.ietf.org/doc/html/rfc4648#section-7).
+pub(crate) fn parse_base32hex(ty: RType, text: &str) -> Result<Vec<u8>, RDataParseError> {
+    decode_base32hex(text)
+        .ok_or_else(|| RDataParseError::new(ty, format!("invalid base32hex data '{text}'")))
+}
+
+/// Parses the salt of NSEC3 and NSEC3PARAM records as hexadecimal string.
+/// A single `-` denotes an empty salt.
+pub(crate) fn parse_salt(ty: RType, text: &str) -> Result<Vec<u8>, RDataParseError> {
+    if text == "-" {
+        return Ok(Vec::new());
+    }
+
+    let salt = parse_hex(ty, &[text])?;
+
+    if salt.len() > u8::MAX as usize {
+        return Err(RDataParseError::new(ty, format!("salt too long '{text}'")));
+    }
+
+    Ok(salt)
+}
+
+/// Parses the signature expiration and inception of RRSIG records. The
+/// timestamp is either in the form YYYYMMDDHHmmSS (UTC) or a plain number of
+/// seconds since 1 January 1970. See
+/// [RFC 4034 Section 3.2](https://datatracker.ietf.org/doc/html/rfc4034#section-3.2).
+pub(crate) fn parse_timestamp(ty: RType, text: &str) -> Result<u32, RDataParseError> {
+    let invalid = || RDataParseError::new(ty, format!("invalid timestamp '{text}'"));
+
+    if text.len() != 14 {
+        return text.parse().map_err(|_| invalid());
+    }
+
+    if !text.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let field = |range: std::ops::Range<usize>| text[range].parse::<i64>().unwrap_or_default();
+    let (year, month, day) = (field(0..4), field(4..6), field(6..8));
+    let (hour, minute, second) = (field(8..10), field(10..12), field(12..14));
+
+    let days = days_from_civil(year, month, day);
+
+    // Reject dates like 20230231 which don't round-trip
+    if civil_from_days(days) != (year, month, day) || hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
+    }
+
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+    u32::try_from(secs).map_err(|_| invalid())
+}
+
+/// Parses the RFC 3597 generic RDATA encoding `\# <length> <hex>...` with
+/// the leading `\#` already removed. The hexadecimal data can be split into
+/// multiple parts.
+pub(crate) fn parse_generic(ty: RType, parts: &[&str]) -> Result<Vec<u8>, RDataParseError> {
+    let (len, data) = match parts.split_first() {
+        Some((len, hex)) => (parse_number::<u16>(ty, len)? as usize, parse_hex(ty, hex)?),
+        None => return Err(RDataParseError::new(ty, "missing RDATA length".into())),
+    };
+
+    if data.len() != len {
+        return Err(RDataParseError::new(
+            ty,
+            format!(
+                "RDATA length {len} doesn't match data length {}",
+                data.len()
+            ),
+        ));
+    }
+
+    Ok(data)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect()
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.as_bytes();
+
+    if text.len() % 4 != 0 {
+        return None;
+    }
+
+    let padding = text.iter().rev().take_while(|&&c| c == b'=').count();
+    if padding > 2 {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(text.len() / 4 * 3);
+
+    for (i, chunk) in text.chunks(4).enumerate() {
+        let last = i == text.len() / 4 - 1;
+        let mut value: u32 = 0;
+
+        for (j, &c) in chunk.iter().enumerate() {
+            let sextet = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                // Padding is only allowed at the end
+                b'=' if last && j >= 4 - padding => 0,
+                _ => return None,
+            };
+
+            value = value << 6 | sextet as u32;
+        }
+
+        let bytes = value.to_be_bytes();
+        let len = if last { 3 - padding } else { 3 };
+        data.extend_from_slice(&bytes[1..1 + len]);
+    }
+
+    Some(data)
+}
+
+fn decode_base32hex(text: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in text.bytes() {
+        let value = match c.to_ascii_uppercase() {
+            c @ b'0'..=b'9' => c - b'0',
+            c @ b'A'..=b'V' => c - b'A' + 10,
+            _ => return None,
+        };
+
+        buffer = buffer << 5 | value as u32;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            data.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    // The remaining bits are padding and have to be zero
+    if bits >= 5 || buffer != 0 {
+        return None;
+    }
+
+    Some(data)
+}
+
+/// Converts a date of the proleptic Gregorian calendar into the number of
+/// days since 1970-01-01.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+/// Converts the number of days since 1970-01-01 into a (year, month, day)
+/// date of the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// Formats a <character-string> as quoted string. Quotes and backslashes are
+/// escaped, non-printable octets use the `\DDD` form.
+pub(crate) fn format_char_string(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() + 2);
+    s.push('"');
+
+    for &b in bytes {
+        match b {
+            b'"' | b'\\' => {
+                s.push('\\');
+                s.push(b as char);
+            }
+            0x20..=0x7e => s.push(b as char),
+            _ => s.push_str(&format!("\\{b:03}")),
+        }
+    }
+
+    s.push('"');
+    s
+}
+
+/// Formats `data` with the RFC 3597 generic RDATA encoding
+/// `\# <length> <hex>`.
+pub(crate) fn format_generic(data: &[u8]) -> String {
+    match data.len() {
+        0 => String::from("\\# 0"),
+        len => format!("\\# {len} {}", format_hex(data)),
+    }
+}
+
+/// Formats `data` as uppercase hexadecimal string.
+pub(crate) fn format_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+/// Formats `data` as base64 string with padding.
+pub(crate) fn format_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut s = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let mut bytes = [0u8; 3];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+
+        let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(ALPHABET[(value >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+
+    s
+}
+
+/// Formats `data` as lowercase base32hex string without padding.
+pub(crate) fn format_base32hex(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+    let mut s = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for &b in data {
+        buffer = buffer << 8 | b as u32;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            s.push(ALPHABET[(buffer >> bits & 0x1f) as usize] as char);
+        }
+
+        buffer &= (1 << bits) - 1;
+    }
+
+    if bits > 0 {
+        s.push(ALPHABET[(buffer << (5 - bits) & 0x1f) as usize] as char);
+    }
+
+    s
+}
+
+/// Formats the salt of NSEC3 and NSEC3PARAM records, see [`parse_salt`].
+pub(crate) fn format_salt(salt: &[u8]) -> String {
+    match salt.len() {
+        0 => String::from("-"),
+        _ => format_hex(salt),
+    }
+}
+
+/// Formats a timestamp of RRSIG records in the form YYYYMMDDHHmmSS (UTC).
+pub(crate) fn format_timestamp(timestamp: u32) -> String {
+    let secs = timestamp as i64;
+    let (year, month, day) = civil_from_days(secs / 86400);
+    let secs = secs % 86400;
+
+    format!(
+        "{year:04}{month:02}{day:02}{:02}{:02}{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
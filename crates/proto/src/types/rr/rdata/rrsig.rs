@@ -0,0 +1,248 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadResult, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::types::{
+    dns::Name,
+    rr::{
+        rdata::{
+            format_base64, format_timestamp, parse_base64, parse_name, parse_number,
+            parse_timestamp,
+        },
+        RDataParseError, RType,
+    },
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |        Type Covered           |  Algorithm    |     Labels    |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                         Original TTL                          |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                      Signature Expiration                     |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                      Signature Inception                      |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |            Key Tag            |                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+         Signer's Name         /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                                                               /
+/// /                            Signature                          /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// The Signer's Name is never compressed.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc4034#section-3.1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRSIG {
+    type_covered: RType,
+    algorithm: u8,
+    labels: u8,
+    original_ttl: u32,
+    expiration: u32,
+    inception: u32,
+    key_tag: u16,
+    signer: Name,
+    signature: Vec<u8>,
+}
+
+impl Display for RRSIG {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {} {}",
+            self.type_covered,
+            self.algorithm,
+            self.labels,
+            self.original_ttl,
+            format_timestamp(self.expiration),
+            format_timestamp(self.inception),
+            self.key_tag,
+            self.signer,
+            format_base64(&self.signature)
+        )
+    }
+}
+
+impl Writeable for RRSIG {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.write_signed_fields::<E>(buf)?;
+            buf.write(&mut self.signature.clone())
+        };
+
+        Ok(n)
+    }
+}
+
+impl RRSIG {
+    /// Creates a new [`RRSIG`] without a signature. The signature is
+    /// calculated over the data returned by [`RRSIG::write_signed_fields`]
+    /// and the covered RRset and set via [`RRSIG::set_signature`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        type_covered: RType,
+        algorithm: u8,
+        labels: u8,
+        original_ttl: u32,
+        expiration: u32,
+        inception: u32,
+        key_tag: u16,
+        signer: Name,
+    ) -> Self {
+        Self {
+            type_covered,
+            algorithm,
+            labels,
+            original_ttl,
+            expiration,
+            inception,
+            key_tag,
+            signer,
+            signature: Vec::new(),
+        }
+    }
+
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> ReadResult<Self> {
+        let start = buf.offset();
+
+        let type_covered = RType::from(u16::read::<E>(buf)?);
+        let algorithm = u8::read::<E>(buf)?;
+        let labels = u8::read::<E>(buf)?;
+        let original_ttl = u32::read::<E>(buf)?;
+        let expiration = u32::read::<E>(buf)?;
+        let inception = u32::read::<E>(buf)?;
+        let key_tag = u16::read::<E>(buf)?;
+        let signer = Name::read::<E>(buf)?;
+
+        let read = buf.offset() - start;
+        let signature = buf.read_vec((rdlen as usize).saturating_sub(read))?;
+
+        Ok(Self {
+            type_covered,
+            algorithm,
+            labels,
+            original_ttl,
+            expiration,
+            inception,
+            key_tag,
+            signer,
+            signature,
+        })
+    }
+
+    /// Parses the RRSIG fields in presentation format. The expiration and
+    /// inception can either be a YYYYMMDDHHmmSS timestamp or a number of
+    /// seconds. The base64 encoded signature can be split into multiple
+    /// parts.
+    pub fn try_from_parts(parts: &[&str], origin: &Name) -> Result<Self, RDataParseError> {
+        let ty = RType::RRSIG;
+
+        if parts.len() < 9 {
+            return Err(RDataParseError::new(
+                ty,
+                format!("expected at least 9 fields, got {}", parts.len()),
+            ));
+        }
+
+        let type_covered = RType::try_from(parts[0])
+            .map_err(|_| RDataParseError::new(ty, format!("unknown type '{}'", parts[0])))?;
+
+        Ok(Self {
+            type_covered,
+            algorithm: parse_number(ty, parts[1])?,
+            labels: parse_number(ty, parts[2])?,
+            original_ttl: parse_number(ty, parts[3])?,
+            expiration: parse_timestamp(ty, parts[4])?,
+            inception: parse_timestamp(ty, parts[5])?,
+            key_tag: parse_number(ty, parts[6])?,
+            signer: parse_name(ty, parts[7], origin)?,
+            signature: parse_base64(ty, &parts[8..])?,
+        })
+    }
+
+    /// Writes all fields except the signature itself. The signer name is
+    /// written as is, see [`RRSIG::to_lowercase`] for the canonical form.
+    /// This is the RRSIG part of the data covered by the signature, see
+    /// [RFC 4034 Section 3.1.8.1](https://datatracker.ietf.org/doc/html/rfc4034#section-3.1.8.1).
+    pub fn write_signed_fields<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+    ) -> Result<usize, WriteError> {
+        let n = bytes_written! {
+            u16::from(self.type_covered).write::<E>(buf)?;
+            self.algorithm.write::<E>(buf)?;
+            self.labels.write::<E>(buf)?;
+            self.original_ttl.write::<E>(buf)?;
+            self.expiration.write::<E>(buf)?;
+            self.inception.write::<E>(buf)?;
+            self.key_tag.write::<E>(buf)?;
+            self.signer.write::<E>(buf)?
+        };
+
+        Ok(n)
+    }
+
+    pub fn type_covered(&self) -> RType {
+        self.type_covered
+    }
+
+    pub fn algorithm(&self) -> u8 {
+        self.algorithm
+    }
+
+    pub fn labels(&self) -> u8 {
+        self.labels
+    }
+
+    pub fn original_ttl(&self) -> u32 {
+        self.original_ttl
+    }
+
+    pub fn expiration(&self) -> u32 {
+        self.expiration
+    }
+
+    pub fn inception(&self) -> u32 {
+        self.inception
+    }
+
+    pub fn key_tag(&self) -> u16 {
+        self.key_tag
+    }
+
+    pub fn signer(&self) -> &Name {
+        &self.signer
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    pub fn set_signature(&mut self, signature: Vec<u8>) {
+        self.signature = signature
+    }
+
+    /// Returns a copy with the signer name converted to lowercase.
+    pub fn to_lowercase(&self) -> Self {
+        Self {
+            signer: self.signer.to_lowercase(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the size of the [`RRSIG`] record.
+    pub fn size(&self) -> usize {
+        18 + self.signer.size() + self.signature.len()
+    }
+}
@@ -4,7 +4,15 @@ use binbuf::{
     bytes_written, Endianness, ReadBuffer, ReadError, Readable, WriteBuffer, WriteError, Writeable,
 };
 
-use crate::types::dns::Name;
+use snafu::ResultExt;
+
+use crate::types::{
+    dns::{CompressionMap, Name},
+    rr::{
+        rdata::{parse_duration, parse_name, BufferWriteSnafu, NameSnafu, RDataError},
+        RDataParseError, RType,
+    },
+};
 
 #[derive(Debug, Clone)]
 pub struct SOA {
@@ -63,7 +71,7 @@ impl Display for SOA {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "SOA {} {} (\n  {}; serial\n  {}; refresh\n  {}; retry\n  {}; expire\n  {}; minimum\n)",
+            "{} {} {} {} {} {} {}",
             self.mname,
             self.rname,
             self.serial,
@@ -76,6 +84,74 @@ impl Display for SOA {
 }
 
 impl SOA {
+    /// Parses the seven SOA fields in presentation format. The timer values
+    /// can use unit suffixes, e.g. `1h`.
+    pub fn try_from_parts(parts: &[&str], origin: &Name) -> Result<Self, RDataParseError> {
+        let mname = parse_name(RType::SOA, parts[0], origin)?;
+        let rname = parse_name(RType::SOA, parts[1], origin)?;
+
+        let mut values = [0u32; 5];
+        for (value, text) in values.iter_mut().zip(&parts[2..]) {
+            *value = match parse_duration(text) {
+                Some(v) => v,
+                None => {
+                    return Err(RDataParseError::new(
+                        RType::SOA,
+                        format!("invalid number '{text}'"),
+                    ))
+                }
+            };
+        }
+
+        let [serial, refresh, retry, expire, minimum] = values;
+
+        Ok(Self {
+            mname,
+            rname,
+            serial,
+            refresh,
+            retry,
+            expire,
+            minimum,
+        })
+    }
+
+    /// Writes the [`SOA`] record and compresses MNAME and RNAME by using
+    /// the provided [`CompressionMap`].
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, RDataError> {
+        let mut n = self
+            .mname
+            .write_compressed::<E>(buf, names)
+            .context(NameSnafu)?;
+        n += self
+            .rname
+            .write_compressed::<E>(buf, names)
+            .context(NameSnafu)?;
+
+        n += bytes_written! {
+            self.serial.write::<E>(buf).context(BufferWriteSnafu)?;
+            self.refresh.write::<E>(buf).context(BufferWriteSnafu)?;
+            self.retry.write::<E>(buf).context(BufferWriteSnafu)?;
+            self.expire.write::<E>(buf).context(BufferWriteSnafu)?;
+            self.minimum.write::<E>(buf).context(BufferWriteSnafu)?
+        };
+
+        Ok(n)
+    }
+
+    /// Returns a copy with the domain names converted to lowercase.
+    pub fn to_lowercase(&self) -> Self {
+        Self {
+            mname: self.mname.to_lowercase(),
+            rname: self.rname.to_lowercase(),
+            ..self.clone()
+        }
+    }
+
     /// Returns the size of the [`SOA`] record.
     pub fn size(&self) -> usize {
         // Returns the sum of MNAME's len, RNAME's len and a fixed length. The
@@ -86,4 +162,14 @@ impl SOA {
     pub fn get_mname(&self) -> &Name {
         &self.mname
     }
+
+    pub fn get_serial(&self) -> u32 {
+        self.serial
+    }
+
+    /// Returns the MINIMUM field. Since RFC 2308 this is the TTL used when
+    /// caching negative answers.
+    pub fn get_minimum(&self) -> u32 {
+        self.minimum
+    }
 }
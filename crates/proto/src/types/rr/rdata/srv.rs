@@ -0,0 +1,100 @@
+use std::fmt::Display;
+
+use binbuf::{Readable, Writeable};
+
+use crate::types::{
+    dns::Name,
+    rr::{
+        rdata::{expect_parts, parse_name, parse_number},
+        RDataParseError, RType,
+    },
+};
+
+/// ```text
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                   PRIORITY                    |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                    WEIGHT                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                     PORT                      |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                    TARGET                     /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// The TARGET is never compressed.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc2782
+#[derive(Debug, Clone, Readable, Writeable)]
+pub struct SRV {
+    priority: u16,
+    weight: u16,
+    port: u16,
+    target: Name,
+}
+
+impl Display for SRV {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.priority, self.weight, self.port, self.target
+        )
+    }
+}
+
+impl SRV {
+    pub fn new(priority: u16, weight: u16, port: u16, target: Name) -> Self {
+        Self {
+            priority,
+            weight,
+            port,
+            target,
+        }
+    }
+
+    /// Parses the PRIORITY, WEIGHT, PORT and TARGET fields in presentation
+    /// format.
+    pub fn try_from_parts(parts: &[&str], origin: &Name) -> Result<Self, RDataParseError> {
+        let parts = expect_parts(RType::SRV, parts, 4)?;
+
+        Ok(Self {
+            priority: parse_number(RType::SRV, parts[0])?,
+            weight: parse_number(RType::SRV, parts[1])?,
+            port: parse_number(RType::SRV, parts[2])?,
+            target: parse_name(RType::SRV, parts[3], origin)?,
+        })
+    }
+
+    pub fn priority(&self) -> u16 {
+        self.priority
+    }
+
+    pub fn weight(&self) -> u16 {
+        self.weight
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn target(&self) -> &Name {
+        &self.target
+    }
+
+    /// Returns a copy with the domain name converted to lowercase.
+    pub fn to_lowercase(&self) -> Self {
+        Self {
+            target: self.target.to_lowercase(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the size of the [`SRV`] record.
+    pub fn size(&self) -> usize {
+        // Three u16 fields and the TARGET
+        self.target.size() + 6
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadResult, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::types::rr::{
+    rdata::{format_hex, parse_hex, parse_number},
+    RDataParseError, RType,
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |   algorithm   |    fp type    |                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               /
+/// /                                                               /
+/// /                          fingerprint                          /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc4255#section-3.1
+#[derive(Debug, Clone)]
+pub struct SSHFP {
+    algorithm: u8,
+    fp_type: u8,
+    fingerprint: Vec<u8>,
+}
+
+impl Display for SSHFP {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.algorithm,
+            self.fp_type,
+            format_hex(&self.fingerprint)
+        )
+    }
+}
+
+impl Writeable for SSHFP {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.algorithm.write::<E>(buf)?;
+            self.fp_type.write::<E>(buf)?;
+            buf.write(&mut self.fingerprint.clone())
+        };
+
+        Ok(n)
+    }
+}
+
+impl SSHFP {
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> ReadResult<Self> {
+        let algorithm = u8::read::<E>(buf)?;
+        let fp_type = u8::read::<E>(buf)?;
+        let fingerprint = buf.read_vec((rdlen as usize).saturating_sub(2))?;
+
+        Ok(Self {
+            algorithm,
+            fp_type,
+            fingerprint,
+        })
+    }
+
+    /// Parses the algorithm, fingerprint type and fingerprint in
+    /// presentation format. The hexadecimal fingerprint can be split into
+    /// multiple parts.
+    pub fn try_from_parts(parts: &[&str]) -> Result<Self, RDataParseError> {
+        if parts.len() < 3 {
+            return Err(RDataParseError::new(
+                RType::SSHFP,
+                format!("expected at least 3 fields, got {}", parts.len()),
+            ));
+        }
+
+        Ok(Self {
+            algorithm: parse_number(RType::SSHFP, parts[0])?,
+            fp_type: parse_number(RType::SSHFP, parts[1])?,
+            fingerprint: parse_hex(RType::SSHFP, &parts[2..])?,
+        })
+    }
+
+    pub fn algorithm(&self) -> u8 {
+        self.algorithm
+    }
+
+    pub fn fp_type(&self) -> u8 {
+        self.fp_type
+    }
+
+    pub fn fingerprint(&self) -> &[u8] {
+        &self.fingerprint
+    }
+
+    /// Returns the size of the [`SSHFP`] record.
+    pub fn size(&self) -> usize {
+        2 + self.fingerprint.len()
+    }
+}
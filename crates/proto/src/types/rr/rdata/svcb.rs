@@ -0,0 +1,594 @@
+use std::{
+    fmt::Display,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use binbuf::{Endianness, ReadBuffer, Readable, WriteBuffer, WriteError, Writeable};
+use snafu::{ensure, ResultExt};
+
+use crate::types::{
+    dns::Name,
+    rr::{
+        rdata::{
+            format_base64, format_char_string, parse_base64, parse_escaped, parse_name,
+            parse_number, BufferSnafu, InvalidSvcParamSnafu, NameSnafu, RDataError,
+        },
+        RDataParseError, RType,
+    },
+};
+
+/// The key of a [`SvcParam`]. See
+/// [RFC 9460 Section 14.3.2](https://datatracker.ietf.org/doc/html/rfc9460#section-14.3.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SvcParamKey {
+    Mandatory,
+    Alpn,
+    NoDefaultAlpn,
+    Port,
+    Ipv4Hint,
+    Ech,
+    Ipv6Hint,
+    Unknown(u16),
+}
+
+impl From<u16> for SvcParamKey {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Self::Mandatory,
+            1 => Self::Alpn,
+            2 => Self::NoDefaultAlpn,
+            3 => Self::Port,
+            4 => Self::Ipv4Hint,
+            5 => Self::Ech,
+            6 => Self::Ipv6Hint,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<SvcParamKey> for u16 {
+    fn from(value: SvcParamKey) -> Self {
+        match value {
+            SvcParamKey::Mandatory => 0,
+            SvcParamKey::Alpn => 1,
+            SvcParamKey::NoDefaultAlpn => 2,
+            SvcParamKey::Port => 3,
+            SvcParamKey::Ipv4Hint => 4,
+            SvcParamKey::Ech => 5,
+            SvcParamKey::Ipv6Hint => 6,
+            SvcParamKey::Unknown(key) => key,
+        }
+    }
+}
+
+impl Display for SvcParamKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvcParamKey::Mandatory => write!(f, "mandatory"),
+            SvcParamKey::Alpn => write!(f, "alpn"),
+            SvcParamKey::NoDefaultAlpn => write!(f, "no-default-alpn"),
+            SvcParamKey::Port => write!(f, "port"),
+            SvcParamKey::Ipv4Hint => write!(f, "ipv4hint"),
+            SvcParamKey::Ech => write!(f, "ech"),
+            SvcParamKey::Ipv6Hint => write!(f, "ipv6hint"),
+            SvcParamKey::Unknown(key) => write!(f, "key{key}"),
+        }
+    }
+}
+
+impl FromStr for SvcParamKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mandatory" => Ok(Self::Mandatory),
+            "alpn" => Ok(Self::Alpn),
+            "no-default-alpn" => Ok(Self::NoDefaultAlpn),
+            "port" => Ok(Self::Port),
+            "ipv4hint" => Ok(Self::Ipv4Hint),
+            "ech" => Ok(Self::Ech),
+            "ipv6hint" => Ok(Self::Ipv6Hint),
+            // The generic keyNNNNN notation, key65535 is reserved
+            _ => match s.strip_prefix("key").and_then(|n| n.parse::<u16>().ok()) {
+                Some(key) if key != u16::MAX => Ok(Self::from(key)),
+                _ => Err(format!("invalid key '{s}'")),
+            },
+        }
+    }
+}
+
+/// A single service parameter of [`SVCB`] and HTTPS records. See
+/// [RFC 9460 Section 7](https://datatracker.ietf.org/doc/html/rfc9460#section-7).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SvcParam {
+    /// Keys which clients must understand to use the record.
+    Mandatory(Vec<SvcParamKey>),
+
+    /// Additional supported protocols (ALPN IDs).
+    Alpn(Vec<Vec<u8>>),
+
+    /// The default protocol is not supported.
+    NoDefaultAlpn,
+
+    /// The TCP or UDP port of the service.
+    Port(u16),
+
+    /// IPv4 addresses which can be used to reach the service.
+    Ipv4Hint(Vec<Ipv4Addr>),
+
+    /// An encrypted ClientHello config list.
+    Ech(Vec<u8>),
+
+    /// IPv6 addresses which can be used to reach the service.
+    Ipv6Hint(Vec<Ipv6Addr>),
+
+    /// A parameter with an unknown key, kept as opaque value.
+    Unknown(u16, Vec<u8>),
+}
+
+impl Display for SvcParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let key = self.key();
+
+        match self {
+            SvcParam::Mandatory(keys) => write!(f, "{key}={}", join(keys)),
+            SvcParam::Alpn(ids) => {
+                // Commas and backslashes in ALPN IDs are escaped in the
+                // value-list, which itself is a <character-string>.
+                let list: Vec<Vec<u8>> = ids
+                    .iter()
+                    .map(|id| {
+                        id.iter()
+                            .flat_map(|&b| match b {
+                                b',' | b'\\' => vec![b'\\', b],
+                                _ => vec![b],
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                write!(f, "{key}={}", format_char_string(&list.join(&b','[..])))
+            }
+            SvcParam::NoDefaultAlpn => write!(f, "{key}"),
+            SvcParam::Port(port) => write!(f, "{key}={port}"),
+            SvcParam::Ipv4Hint(addrs) => write!(f, "{key}={}", join(addrs)),
+            SvcParam::Ech(config) => write!(f, "{key}={}", format_base64(config)),
+            SvcParam::Ipv6Hint(addrs) => write!(f, "{key}={}", join(addrs)),
+            SvcParam::Unknown(_, value) => write!(f, "{key}={}", format_char_string(value)),
+        }
+    }
+}
+
+impl SvcParam {
+    pub fn key(&self) -> SvcParamKey {
+        match self {
+            SvcParam::Mandatory(_) => SvcParamKey::Mandatory,
+            SvcParam::Alpn(_) => SvcParamKey::Alpn,
+            SvcParam::NoDefaultAlpn => SvcParamKey::NoDefaultAlpn,
+            SvcParam::Port(_) => SvcParamKey::Port,
+            SvcParam::Ipv4Hint(_) => SvcParamKey::Ipv4Hint,
+            SvcParam::Ech(_) => SvcParamKey::Ech,
+            SvcParam::Ipv6Hint(_) => SvcParamKey::Ipv6Hint,
+            SvcParam::Unknown(key, _) => SvcParamKey::Unknown(*key),
+        }
+    }
+
+    /// Returns the value in wire format.
+    fn value(&self) -> Vec<u8> {
+        match self {
+            SvcParam::Mandatory(keys) => keys
+                .iter()
+                .flat_map(|key| u16::from(*key).to_be_bytes())
+                .collect(),
+            SvcParam::Alpn(ids) => ids
+                .iter()
+                .flat_map(|id| {
+                    let mut bytes = vec![id.len() as u8];
+                    bytes.extend_from_slice(id);
+                    bytes
+                })
+                .collect(),
+            SvcParam::NoDefaultAlpn => Vec::new(),
+            SvcParam::Port(port) => port.to_be_bytes().to_vec(),
+            SvcParam::Ipv4Hint(addrs) => addrs.iter().flat_map(|a| a.octets()).collect(),
+            SvcParam::Ech(config) => config.clone(),
+            SvcParam::Ipv6Hint(addrs) => addrs.iter().flat_map(|a| a.octets()).collect(),
+            SvcParam::Unknown(_, value) => value.clone(),
+        }
+    }
+
+    /// Returns the size of the value in wire format.
+    fn value_size(&self) -> usize {
+        match self {
+            SvcParam::Mandatory(keys) => keys.len() * 2,
+            SvcParam::Alpn(ids) => ids.iter().map(|id| id.len() + 1).sum(),
+            SvcParam::NoDefaultAlpn => 0,
+            SvcParam::Port(_) => 2,
+            SvcParam::Ipv4Hint(addrs) => addrs.len() * 4,
+            SvcParam::Ech(config) => config.len(),
+            SvcParam::Ipv6Hint(addrs) => addrs.len() * 16,
+            SvcParam::Unknown(_, value) => value.len(),
+        }
+    }
+
+    /// Decodes the wire format `value` of the parameter with `key`.
+    fn from_wire(key: u16, value: &[u8]) -> Result<Self, String> {
+        let key = SvcParamKey::from(key);
+        let invalid = || format!("invalid value for {key}");
+
+        match key {
+            SvcParamKey::Mandatory => {
+                if value.is_empty() || value.len() % 2 != 0 {
+                    return Err(invalid());
+                }
+
+                let keys: Vec<u16> = value
+                    .chunks(2)
+                    .map(|k| u16::from_be_bytes([k[0], k[1]]))
+                    .collect();
+
+                // The keys have to be in strictly increasing order
+                if keys.windows(2).any(|w| w[0] >= w[1]) {
+                    return Err(format!("keys of {key} not in strictly increasing order"));
+                }
+
+                Ok(Self::Mandatory(keys.into_iter().map(Into::into).collect()))
+            }
+            SvcParamKey::Alpn => {
+                let mut ids = Vec::new();
+                let mut rest = value;
+
+                while let Some((&len, tail)) = rest.split_first() {
+                    let len = len as usize;
+
+                    if len == 0 || tail.len() < len {
+                        return Err(invalid());
+                    }
+
+                    ids.push(tail[..len].to_vec());
+                    rest = &tail[len..];
+                }
+
+                if ids.is_empty() {
+                    return Err(invalid());
+                }
+
+                Ok(Self::Alpn(ids))
+            }
+            SvcParamKey::NoDefaultAlpn if value.is_empty() => Ok(Self::NoDefaultAlpn),
+            SvcParamKey::Port if value.len() == 2 => {
+                Ok(Self::Port(u16::from_be_bytes([value[0], value[1]])))
+            }
+            SvcParamKey::Ipv4Hint if !value.is_empty() && value.len() % 4 == 0 => {
+                Ok(Self::Ipv4Hint(
+                    value
+                        .chunks(4)
+                        .map(|a| Ipv4Addr::new(a[0], a[1], a[2], a[3]))
+                        .collect(),
+                ))
+            }
+            SvcParamKey::Ech => Ok(Self::Ech(value.to_vec())),
+            SvcParamKey::Ipv6Hint if !value.is_empty() && value.len() % 16 == 0 => {
+                Ok(Self::Ipv6Hint(
+                    value
+                        .chunks(16)
+                        .map(|a| {
+                            let octets: [u8; 16] = a.try_into().unwrap_or_default();
+                            Ipv6Addr::from(octets)
+                        })
+                        .collect(),
+                ))
+            }
+            SvcParamKey::Unknown(key) => Ok(Self::Unknown(key, value.to_vec())),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Parses the parameter `key` with the optional `value` in presentation
+    /// format.
+    fn from_presentation(ty: RType, key: &str, value: Option<&str>) -> Result<Self, String> {
+        let key: SvcParamKey = key.parse()?;
+        let invalid = |value: &str| format!("invalid value '{value}' for {key}");
+
+        let value = match (key, value) {
+            (SvcParamKey::NoDefaultAlpn, None) => return Ok(Self::NoDefaultAlpn),
+            (SvcParamKey::NoDefaultAlpn, Some(_)) => return Err(format!("{key} takes no value")),
+            (SvcParamKey::Unknown(key), None) => return Ok(Self::Unknown(key, Vec::new())),
+            (_, None) => return Err(format!("missing value for {key}")),
+            (_, Some(value)) => value,
+        };
+
+        // Decode the <character-string> first, the comma-separated lists
+        // use their own escaping on top.
+        let bytes = parse_escaped(ty, value).map_err(|err| err.to_string())?;
+        let text = String::from_utf8_lossy(&bytes);
+
+        match key {
+            SvcParamKey::Mandatory => {
+                let mut keys = split_list(&bytes)
+                    .iter()
+                    .map(|k| String::from_utf8_lossy(k).parse::<SvcParamKey>())
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                keys.sort_by_key(|k| u16::from(*k));
+
+                if keys.windows(2).any(|w| w[0] == w[1]) {
+                    return Err(format!("duplicate key in {key}"));
+                }
+
+                Ok(Self::Mandatory(keys))
+            }
+            SvcParamKey::Alpn => {
+                let ids = split_list(&bytes);
+
+                if ids
+                    .iter()
+                    .any(|id| id.is_empty() || id.len() > u8::MAX as usize)
+                {
+                    return Err(invalid(value));
+                }
+
+                Ok(Self::Alpn(ids))
+            }
+            SvcParamKey::Port => text.parse().map(Self::Port).map_err(|_| invalid(value)),
+            SvcParamKey::Ipv4Hint => text
+                .split(',')
+                .map(|a| a.parse::<Ipv4Addr>())
+                .collect::<Result<_, _>>()
+                .map(Self::Ipv4Hint)
+                .map_err(|_| invalid(value)),
+            SvcParamKey::Ech => parse_base64(ty, &[text.as_ref()])
+                .map(Self::Ech)
+                .map_err(|err| err.to_string()),
+            SvcParamKey::Ipv6Hint => text
+                .split(',')
+                .map(|a| a.parse::<Ipv6Addr>())
+                .collect::<Result<_, _>>()
+                .map(Self::Ipv6Hint)
+                .map_err(|_| invalid(value)),
+            SvcParamKey::Unknown(key) => Ok(Self::Unknown(key, bytes)),
+            SvcParamKey::NoDefaultAlpn => unreachable!(),
+        }
+    }
+}
+
+/// ```text
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                  SvcPriority                  |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                  TargetName                   /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                  SvcParams                    /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// HTTPS records use the same format. The TargetName is never compressed
+/// and the SvcParams are sorted by their key in strictly increasing order.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc9460#section-2.2
+#[derive(Debug, Clone)]
+pub struct SVCB {
+    priority: u16,
+    target: Name,
+    params: Vec<SvcParam>,
+}
+
+impl Display for SVCB {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.priority, self.target)?;
+
+        for param in &self.params {
+            write!(f, " {param}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Writeable for SVCB {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let mut n = self.priority.write::<E>(buf)?;
+        n += self.target.write::<E>(buf)?;
+
+        for param in &self.params {
+            let mut value = param.value();
+
+            n += u16::from(param.key()).write::<E>(buf)?;
+            n += (value.len() as u16).write::<E>(buf)?;
+            n += buf.write(&mut value);
+        }
+
+        Ok(n)
+    }
+}
+
+impl SVCB {
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> Result<Self, RDataError> {
+        let start = buf.offset();
+
+        let priority = u16::read::<E>(buf).context(BufferSnafu)?;
+        let target = Name::read::<E>(buf).context(NameSnafu)?;
+        let mut params: Vec<SvcParam> = Vec::new();
+
+        while buf.offset() - start < rdlen as usize {
+            let key = u16::read::<E>(buf).context(BufferSnafu)?;
+            let len = u16::read::<E>(buf).context(BufferSnafu)?;
+            let value = buf.read_vec(len as usize).context(BufferSnafu)?;
+
+            if let Some(last) = params.last() {
+                ensure!(
+                    u16::from(last.key()) < key,
+                    InvalidSvcParamSnafu {
+                        reason: format!("key {key} not in strictly increasing order"),
+                    }
+                );
+            }
+
+            let param = SvcParam::from_wire(key, &value)
+                .map_err(|reason| RDataError::InvalidSvcParam { reason })?;
+            params.push(param);
+        }
+
+        Ok(Self {
+            priority,
+            target,
+            params,
+        })
+    }
+
+    /// Parses the SvcPriority, TargetName and SvcParams in presentation
+    /// format. The parameters can appear in any order, but every key can
+    /// only be used once. `ty` is either SVCB or HTTPS.
+    pub fn try_from_parts(
+        ty: RType,
+        parts: &[&str],
+        origin: &Name,
+    ) -> Result<Self, RDataParseError> {
+        if parts.len() < 2 {
+            return Err(RDataParseError::new(
+                ty,
+                format!("expected at least 2 fields, got {}", parts.len()),
+            ));
+        }
+
+        let priority = parse_number(ty, parts[0])?;
+        let target = parse_name(ty, parts[1], origin)?;
+
+        let mut params = Vec::new();
+        let mut rest = &parts[2..];
+
+        while let Some((part, tail)) = rest.split_first() {
+            rest = tail;
+
+            let param = match part.split_once('=') {
+                // Quoted values are separate parts, e.g. alpn="h2,h3"
+                Some((key, "")) => match rest.split_first() {
+                    Some((value, tail)) => {
+                        rest = tail;
+                        SvcParam::from_presentation(ty, key, Some(value))
+                    }
+                    None => SvcParam::from_presentation(ty, key, None),
+                },
+                Some((key, value)) => SvcParam::from_presentation(ty, key, Some(value)),
+                None => SvcParam::from_presentation(ty, part, None),
+            };
+
+            params.push(param.map_err(|err| RDataParseError::new(ty, err))?);
+        }
+
+        let svcb =
+            Self::new(priority, target, params).map_err(|err| RDataParseError::new(ty, err))?;
+        Ok(svcb)
+    }
+
+    /// Creates a new [`SVCB`] record. The `params` are sorted by their key.
+    /// This fails if a key is used multiple times or the keys listed in the
+    /// mandatory parameter are missing. See
+    /// [RFC 9460 Section 8](https://datatracker.ietf.org/doc/html/rfc9460#section-8).
+    pub fn new(priority: u16, target: Name, mut params: Vec<SvcParam>) -> Result<Self, String> {
+        params.sort_by_key(|p| u16::from(p.key()));
+
+        if let Some(w) = params.windows(2).find(|w| w[0].key() == w[1].key()) {
+            return Err(format!("duplicate key {}", w[0].key()));
+        }
+
+        if let Some(SvcParam::Mandatory(keys)) = params.first() {
+            for key in keys {
+                if *key == SvcParamKey::Mandatory {
+                    return Err(String::from("mandatory must not list itself"));
+                }
+
+                if !params.iter().any(|p| p.key() == *key) {
+                    return Err(format!("mandatory key {key} is missing"));
+                }
+            }
+        }
+
+        Ok(Self {
+            priority,
+            target,
+            params,
+        })
+    }
+
+    pub fn priority(&self) -> u16 {
+        self.priority
+    }
+
+    pub fn target(&self) -> &Name {
+        &self.target
+    }
+
+    pub fn params(&self) -> &Vec<SvcParam> {
+        &self.params
+    }
+
+    pub fn param(&self, key: SvcParamKey) -> Option<&SvcParam> {
+        self.params.iter().find(|p| p.key() == key)
+    }
+
+    /// Returns if the record is in AliasMode (priority 0).
+    pub fn is_alias_mode(&self) -> bool {
+        self.priority == 0
+    }
+
+    /// Returns the effective target name of the record with the `owner`
+    /// name. A target of "." refers to the owner in ServiceMode, and means
+    /// the service is not available in AliasMode.
+    pub fn target_name<'a>(&'a self, owner: &'a Name) -> Option<&'a Name> {
+        match (self.target.is_root(), self.is_alias_mode()) {
+            (true, true) => None,
+            (true, false) => Some(owner),
+            (false, _) => Some(&self.target),
+        }
+    }
+
+    /// Returns the size of the [`SVCB`] record.
+    pub fn size(&self) -> usize {
+        // SvcPriority, TargetName and for each parameter the key, the value
+        // length and the value
+        2 + self.target.size()
+            + self
+                .params
+                .iter()
+                .map(|p| 4 + p.value_size())
+                .sum::<usize>()
+    }
+}
+
+fn join<T: Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Splits a comma-separated value-list. Commas and backslashes in the items
+/// are escaped with a backslash. See
+/// [RFC 9460 Appendix A.1](https://datatracker.ietf.org/doc/html/rfc9460#appendix-A.1).
+fn split_list(bytes: &[u8]) -> Vec<Vec<u8>> {
+    let mut items = vec![Vec::new()];
+    let mut iter = bytes.iter();
+
+    while let Some(&b) = iter.next() {
+        match b {
+            b'\\' => {
+                if let Some(&c) = iter.next() {
+                    items.last_mut().unwrap().push(c)
+                }
+            }
+            b',' => items.push(Vec::new()),
+            _ => items.last_mut().unwrap().push(b),
+        }
+    }
+
+    items
+}
@@ -0,0 +1,116 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadResult, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::types::rr::{
+    rdata::{format_hex, parse_hex, parse_number},
+    RDataParseError, RType,
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  Cert. Usage  |   Selector    | Matching Type |               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+               /
+/// /                                                               /
+/// /                 Certificate Association Data                  /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc6698#section-2.1
+#[derive(Debug, Clone)]
+pub struct TLSA {
+    usage: u8,
+    selector: u8,
+    matching_type: u8,
+    data: Vec<u8>,
+}
+
+impl Display for TLSA {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.usage,
+            self.selector,
+            self.matching_type,
+            format_hex(&self.data)
+        )
+    }
+}
+
+impl Writeable for TLSA {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.usage.write::<E>(buf)?;
+            self.selector.write::<E>(buf)?;
+            self.matching_type.write::<E>(buf)?;
+            buf.write(&mut self.data.clone())
+        };
+
+        Ok(n)
+    }
+}
+
+impl TLSA {
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> ReadResult<Self> {
+        let usage = u8::read::<E>(buf)?;
+        let selector = u8::read::<E>(buf)?;
+        let matching_type = u8::read::<E>(buf)?;
+        let data = buf.read_vec((rdlen as usize).saturating_sub(3))?;
+
+        Ok(Self {
+            usage,
+            selector,
+            matching_type,
+            data,
+        })
+    }
+
+    /// Parses the certificate usage, selector, matching type and the
+    /// certificate association data in presentation format. The hexadecimal
+    /// data can be split into multiple parts.
+    pub fn try_from_parts(parts: &[&str]) -> Result<Self, RDataParseError> {
+        if parts.len() < 4 {
+            return Err(RDataParseError::new(
+                RType::TLSA,
+                format!("expected at least 4 fields, got {}", parts.len()),
+            ));
+        }
+
+        Ok(Self {
+            usage: parse_number(RType::TLSA, parts[0])?,
+            selector: parse_number(RType::TLSA, parts[1])?,
+            matching_type: parse_number(RType::TLSA, parts[2])?,
+            data: parse_hex(RType::TLSA, &parts[3..])?,
+        })
+    }
+
+    pub fn usage(&self) -> u8 {
+        self.usage
+    }
+
+    pub fn selector(&self) -> u8 {
+        self.selector
+    }
+
+    pub fn matching_type(&self) -> u8 {
+        self.matching_type
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the size of the [`TLSA`] record.
+    pub fn size(&self) -> usize {
+        3 + self.data.len()
+    }
+}
@@ -2,7 +2,13 @@ use std::fmt::Display;
 
 use binbuf::{Endianness, ReadBuffer, ReadResult, WriteBuffer, WriteError, Writeable};
 
-use crate::constants;
+use crate::{
+    constants,
+    types::rr::{
+        rdata::{format_char_string, parse_char_string},
+        RDataParseError, RType,
+    },
+};
 
 #[derive(Debug, Clone)]
 pub struct TXT {
@@ -11,14 +17,8 @@ pub struct TXT {
 
 impl Display for TXT {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.data
-                .iter()
-                .map(|v| format!("  {}", String::from_utf8(v.clone()).unwrap()))
-                .collect::<String>()
-        )
+        let strings: Vec<String> = self.data.iter().map(|s| format_char_string(s)).collect();
+        write!(f, "{}", strings.join(" "))
     }
 }
 
@@ -37,6 +37,23 @@ impl Writeable for TXT {
 }
 
 impl TXT {
+    /// Parses one or more <character-string>s in presentation format.
+    pub fn try_from_parts(parts: &[&str]) -> Result<Self, RDataParseError> {
+        if parts.is_empty() {
+            return Err(RDataParseError::new(
+                RType::TXT,
+                "expected at least one character-string".into(),
+            ));
+        }
+
+        let data = parts
+            .iter()
+            .map(|part| parse_char_string(RType::TXT, part))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { data })
+    }
+
     pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> ReadResult<Self> {
         let start_len = buf.len();
         let rdlen = rdlen as usize;
@@ -52,10 +69,11 @@ impl TXT {
 
     /// Returns the length of the [`TXT`] record.
     pub fn size(&self) -> usize {
+        // Each <character-string> is prefixed with a length octet
         let mut len = 0;
 
         for v in &self.data {
-            len += v.len()
+            len += v.len() + 1
         }
 
         len
@@ -0,0 +1,58 @@
+use std::fmt::Display;
+
+use binbuf::{
+    read::{ReadBuffer, ReadResult},
+    write::{WriteBuffer, WriteError},
+    Endianness, Writeable,
+};
+
+use crate::types::rr::rdata::format_generic;
+
+/// RDATA of a type which is not modelled (yet). The data is kept as opaque
+/// octets, so that records of unknown types can be relayed unchanged. See
+/// [RFC 3597](https://datatracker.ietf.org/doc/html/rfc3597).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UNKNOWN {
+    ty: u16,
+    data: Vec<u8>,
+}
+
+impl Display for UNKNOWN {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format_generic(&self.data))
+    }
+}
+
+impl Writeable for UNKNOWN {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        Ok(buf.write(&mut self.data.clone()))
+    }
+}
+
+impl UNKNOWN {
+    pub fn new(ty: u16, data: Vec<u8>) -> Self {
+        Self { ty, data }
+    }
+
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, ty: u16, rdlen: u16) -> ReadResult<Self> {
+        let data = buf.read_vec(rdlen as usize)?;
+        Ok(Self { ty, data })
+    }
+
+    /// Returns the type code of the record.
+    pub fn ty(&self) -> u16 {
+        self.ty
+    }
+
+    /// Returns the raw RDATA.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the size of the RDATA.
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+}
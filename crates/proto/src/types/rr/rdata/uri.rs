@@ -0,0 +1,107 @@
+use std::fmt::Display;
+
+use binbuf::{
+    bytes_written, Endianness, ReadBuffer, ReadResult, Readable, WriteBuffer, WriteError, Writeable,
+};
+
+use crate::types::rr::{
+    rdata::{expect_parts, format_char_string, parse_escaped, parse_number},
+    RDataParseError, RType,
+};
+
+/// ```text
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |          Priority             |          Weight               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                                                               /
+/// /                             Target                            /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// The target fills the remaining RDATA, it is not a <character-string>.
+///
+/// ### See
+///
+/// - https://datatracker.ietf.org/doc/html/rfc7553#section-4.5
+#[derive(Debug, Clone)]
+pub struct URI {
+    priority: u16,
+    weight: u16,
+    target: Vec<u8>,
+}
+
+impl Display for URI {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.priority,
+            self.weight,
+            format_char_string(&self.target)
+        )
+    }
+}
+
+impl Writeable for URI {
+    type Error = WriteError;
+
+    fn write<E: Endianness>(&self, buf: &mut WriteBuffer) -> Result<usize, Self::Error> {
+        let n = bytes_written! {
+            self.priority.write::<E>(buf)?;
+            self.weight.write::<E>(buf)?;
+            buf.write(&mut self.target.clone())
+        };
+
+        Ok(n)
+    }
+}
+
+impl URI {
+    pub fn read<E: Endianness>(buf: &mut ReadBuffer, rdlen: u16) -> ReadResult<Self> {
+        let priority = u16::read::<E>(buf)?;
+        let weight = u16::read::<E>(buf)?;
+        let target = buf.read_vec((rdlen as usize).saturating_sub(4))?;
+
+        Ok(Self {
+            priority,
+            weight,
+            target,
+        })
+    }
+
+    /// Parses the priority, weight and the quoted target in presentation
+    /// format.
+    pub fn try_from_parts(parts: &[&str]) -> Result<Self, RDataParseError> {
+        let parts = expect_parts(RType::URI, parts, 3)?;
+
+        let target = parse_escaped(RType::URI, parts[2])?;
+        if target.is_empty() {
+            return Err(RDataParseError::new(RType::URI, "empty target".into()));
+        }
+
+        Ok(Self {
+            priority: parse_number(RType::URI, parts[0])?,
+            weight: parse_number(RType::URI, parts[1])?,
+            target,
+        })
+    }
+
+    pub fn priority(&self) -> u16 {
+        self.priority
+    }
+
+    pub fn weight(&self) -> u16 {
+        self.weight
+    }
+
+    pub fn target(&self) -> &[u8] {
+        &self.target
+    }
+
+    /// Returns the size of the [`URI`] record.
+    pub fn size(&self) -> usize {
+        4 + self.target.len()
+    }
+}
@@ -11,7 +11,7 @@ use snafu::{ResultExt, Snafu};
 use crate::{
     constants::RECORD_FIXED_LENGTH,
     types::{
-        dns::{Name, NameError},
+        dns::{CompressionMap, Name, NameError},
         rr::{Class, RType},
     },
 };
@@ -119,6 +119,28 @@ impl RHeader {
         Self::default()
     }
 
+    /// Writes NAME (compressed), TYPE, CLASS and TTL to `buf`. RDLEN is
+    /// **NOT** written, because the length of the (compressed) RDATA is only
+    /// known after it was written. See [`Record::write_compressed`][r].
+    ///
+    /// [r]: crate::types::rr::Record::write_compressed
+    pub fn write_compressed<E: Endianness>(
+        &self,
+        buf: &mut WriteBuffer,
+        names: &mut CompressionMap,
+    ) -> Result<usize, RHeaderError> {
+        buf.enter();
+
+        self.name
+            .write_compressed::<E>(buf, names)
+            .context(WriteNameSnafu)?;
+        self.ty.write::<E>(buf).context(WriteTypeSnafu)?;
+        self.class.write::<E>(buf).context(WriteClassSnafu)?;
+        self.ttl.write::<E>(buf).context(WriteTTLSnafu)?;
+
+        Ok(buf.exit())
+    }
+
     pub fn size(&self) -> usize {
         RECORD_FIXED_LENGTH + self.name.size()
     }
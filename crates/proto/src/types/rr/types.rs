@@ -20,7 +20,7 @@ impl Display for RTypeParseError {
 
 /// [`Type`] describes resource record types.
 /// See https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.2
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum RType {
     /// A host address
     A,
@@ -55,9 +55,60 @@ pub enum RType {
     /// AAAA host address
     AAAA,
 
+    /// Server selection (RFC 2782)
+    SRV,
+
+    /// Naming authority pointer (RFC 3403)
+    NAPTR,
+
+    /// Redirection of a subtree (RFC 6672)
+    DNAME,
+
+    /// SSH key fingerprint (RFC 4255)
+    SSHFP,
+
+    /// Delegation signer (RFC 4034)
+    DS,
+
+    /// DNSSEC signature (RFC 4034)
+    RRSIG,
+
+    /// Next secure record (RFC 4034)
+    NSEC,
+
+    /// DNS public key (RFC 4034)
+    DNSKEY,
+
+    /// Hashed next secure record (RFC 5155)
+    NSEC3,
+
+    /// NSEC3 parameters (RFC 5155)
+    NSEC3PARAM,
+
     /// OPT Record / Meta record
     OPT,
 
+    /// TLS certificate association (RFC 6698)
+    TLSA,
+
+    /// Child copy of a DS record (RFC 7344)
+    CDS,
+
+    /// Child copy of a DNSKEY record (RFC 7344)
+    CDNSKEY,
+
+    /// General purpose service binding (RFC 9460)
+    SVCB,
+
+    /// Service binding for HTTP (RFC 9460)
+    HTTPS,
+
+    /// Uniform resource identifier (RFC 7553)
+    URI,
+
+    /// Certification authority authorization (RFC 8659)
+    CAA,
+
     // QTypes are a superset of types and should only be allowed in questions
     /// A request for a transfer of an entire zone
     AXFR,
@@ -113,12 +164,29 @@ impl Display for RType {
             RType::MX => write!(f, "MX"),
             RType::TXT => write!(f, "TXT"),
             RType::AAAA => write!(f, "AAAA"),
+            RType::SRV => write!(f, "SRV"),
+            RType::NAPTR => write!(f, "NAPTR"),
+            RType::DNAME => write!(f, "DNAME"),
+            RType::SSHFP => write!(f, "SSHFP"),
             RType::OPT => write!(f, "OPT"),
+            RType::TLSA => write!(f, "TLSA"),
+            RType::DS => write!(f, "DS"),
+            RType::RRSIG => write!(f, "RRSIG"),
+            RType::NSEC => write!(f, "NSEC"),
+            RType::DNSKEY => write!(f, "DNSKEY"),
+            RType::NSEC3 => write!(f, "NSEC3"),
+            RType::NSEC3PARAM => write!(f, "NSEC3PARAM"),
+            RType::CDS => write!(f, "CDS"),
+            RType::CDNSKEY => write!(f, "CDNSKEY"),
+            RType::SVCB => write!(f, "SVCB"),
+            RType::HTTPS => write!(f, "HTTPS"),
+            RType::URI => write!(f, "URI"),
+            RType::CAA => write!(f, "CAA"),
             RType::AXFR => write!(f, "AXFR"),
             RType::MAILB => write!(f, "MAILB"),
             RType::MAILA => write!(f, "MAILA"),
             RType::ANY => write!(f, "ANY"),
-            RType::UNKNOWN(c) => write!(f, "UNKNOWN({c})"),
+            RType::UNKNOWN(c) => write!(f, "TYPE{c}"),
         }
     }
 }
@@ -139,12 +207,33 @@ impl FromStr for RType {
             "MX" => Ok(Self::MX),
             "TXT" => Ok(Self::TXT),
             "AAAA" => Ok(Self::AAAA),
+            "SRV" => Ok(Self::SRV),
+            "NAPTR" => Ok(Self::NAPTR),
+            "DNAME" => Ok(Self::DNAME),
+            "SSHFP" => Ok(Self::SSHFP),
             "OPT" => Ok(Self::OPT),
+            "TLSA" => Ok(Self::TLSA),
+            "DS" => Ok(Self::DS),
+            "RRSIG" => Ok(Self::RRSIG),
+            "NSEC" => Ok(Self::NSEC),
+            "DNSKEY" => Ok(Self::DNSKEY),
+            "NSEC3" => Ok(Self::NSEC3),
+            "NSEC3PARAM" => Ok(Self::NSEC3PARAM),
+            "CDS" => Ok(Self::CDS),
+            "CDNSKEY" => Ok(Self::CDNSKEY),
+            "SVCB" => Ok(Self::SVCB),
+            "HTTPS" => Ok(Self::HTTPS),
+            "URI" => Ok(Self::URI),
+            "CAA" => Ok(Self::CAA),
             "AXFR" => Ok(Self::AXFR),
             "MAILB" => Ok(Self::MAILB),
             "MAILA" => Ok(Self::MAILA),
             "ANY" => Ok(Self::ANY),
-            _ => Err(RTypeParseError(format!("Invalid type: {s}"))),
+            // The generic type notation of RFC 3597 Section 5
+            t => match t.strip_prefix("TYPE").and_then(|c| c.parse::<u16>().ok()) {
+                Some(code) => Ok(Self::from(code)),
+                None => Err(RTypeParseError(format!("Invalid type: {s}"))),
+            },
         }
     }
 }
@@ -180,11 +269,28 @@ impl From<u16> for RType {
             15 => Self::MX,
             16 => Self::TXT,
             28 => Self::AAAA,
+            33 => Self::SRV,
+            35 => Self::NAPTR,
+            39 => Self::DNAME,
             41 => Self::OPT,
+            44 => Self::SSHFP,
+            43 => Self::DS,
+            46 => Self::RRSIG,
+            47 => Self::NSEC,
+            48 => Self::DNSKEY,
+            50 => Self::NSEC3,
+            51 => Self::NSEC3PARAM,
+            52 => Self::TLSA,
+            59 => Self::CDS,
+            60 => Self::CDNSKEY,
+            64 => Self::SVCB,
+            65 => Self::HTTPS,
             252 => Self::AXFR,
             253 => Self::MAILB,
             254 => Self::MAILA,
             255 => Self::ANY,
+            256 => Self::URI,
+            257 => Self::CAA,
             _ => Self::UNKNOWN(value),
         }
     }
@@ -204,11 +310,28 @@ impl From<RType> for u16 {
             RType::MX => 15,
             RType::TXT => 16,
             RType::AAAA => 28,
+            RType::SRV => 33,
+            RType::NAPTR => 35,
+            RType::DNAME => 39,
             RType::OPT => 41,
+            RType::SSHFP => 44,
+            RType::TLSA => 52,
+            RType::DS => 43,
+            RType::RRSIG => 46,
+            RType::NSEC => 47,
+            RType::DNSKEY => 48,
+            RType::NSEC3 => 50,
+            RType::NSEC3PARAM => 51,
+            RType::CDS => 59,
+            RType::CDNSKEY => 60,
+            RType::SVCB => 64,
+            RType::HTTPS => 65,
             RType::AXFR => 252,
             RType::MAILB => 253,
             RType::MAILA => 254,
             RType::ANY => 255,
+            RType::URI => 256,
+            RType::CAA => 257,
             RType::UNKNOWN(v) => v,
         }
     }
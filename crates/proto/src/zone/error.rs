@@ -10,6 +10,13 @@ pub enum ZoneError {
     #[error("Failed to parse zone file: {0}")]
     ParseError(String),
 
+    #[error("Syntax error at line {line}, column {column}: {message}")]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
     #[error("Tree error: {0}")]
     TreeError(#[from] TreeError),
 }
@@ -0,0 +1,305 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::zone::ZoneError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    /// A sequence of characters not containing any unescaped whitespace,
+    /// parentheses, semicolons or quotes. Escape sequences are kept as is.
+    Word(String),
+
+    /// The content of a quoted string without the surrounding quotes.
+    /// Escape sequences are kept as is.
+    Quoted(String),
+
+    /// Whitespace at the beginning of a line. The owner name of the entry is
+    /// inherited from the previous entry.
+    Blank,
+
+    /// The end of an entry. Line breaks inside parentheses don't end an entry.
+    Eol,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Token {
+    /// Returns the text of [`TokenKind::Word`] and [`TokenKind::Quoted`]
+    /// tokens.
+    pub(crate) fn text(&self) -> Option<&str> {
+        match &self.kind {
+            TokenKind::Word(text) | TokenKind::Quoted(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// The [`Lexer`] splits a master file into [`Token`]s as described in
+/// [RFC 1035 Section 5.1](https://datatracker.ietf.org/doc/html/rfc1035#section-5.1).
+/// Comments are dropped and parentheses are resolved, so that each entry is
+/// terminated by exactly one [`TokenKind::Eol`].
+pub(crate) struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+
+    /// The number of currently open parentheses.
+    depth: usize,
+
+    /// The position of the next character.
+    line: usize,
+    column: usize,
+
+    /// The position of the last opening parenthesis, used for error
+    /// reporting.
+    open: (usize, usize),
+
+    /// Whether the entry on the current line produced any tokens.
+    in_entry: bool,
+    done: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.chars().peekable(),
+            depth: 0,
+            line: 1,
+            column: 1,
+            open: (0, 0),
+            in_entry: false,
+            done: false,
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn token(&mut self, kind: TokenKind, line: usize, column: usize) -> Token {
+        self.in_entry = !matches!(kind, TokenKind::Eol);
+        Token { kind, line, column }
+    }
+
+    fn error(&self, line: usize, column: usize, message: impl Into<String>) -> ZoneError {
+        ZoneError::Syntax {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(' ' | '\t' | '\r')) {
+            self.next_char();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        while !matches!(self.chars.peek(), None | Some('\n')) {
+            self.next_char();
+        }
+    }
+
+    fn read_word(&mut self) -> String {
+        let mut word = String::new();
+
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                ' ' | '\t' | '\r' | '\n' | '(' | ')' | ';' | '"' => break,
+                '\\' => {
+                    word.push(c);
+                    self.next_char();
+
+                    if let Some(c) = self.next_char() {
+                        word.push(c)
+                    }
+                }
+                _ => {
+                    word.push(c);
+                    self.next_char();
+                }
+            }
+        }
+
+        word
+    }
+
+    fn read_quoted(&mut self, line: usize, column: usize) -> Result<String, ZoneError> {
+        let mut text = String::new();
+
+        // Skip the opening quote
+        self.next_char();
+
+        loop {
+            match self.next_char() {
+                Some('"') => return Ok(text),
+                Some('\\') => {
+                    text.push('\\');
+
+                    match self.next_char() {
+                        Some(c) => text.push(c),
+                        None => break,
+                    }
+                }
+                Some('\n') | None => break,
+                Some(c) => text.push(c),
+            }
+        }
+
+        Err(self.error(line, column, "unterminated quoted string"))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token, ZoneError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            let (line, column) = (self.line, self.column);
+
+            let c = match self.chars.peek() {
+                Some(c) => *c,
+                None => {
+                    self.done = true;
+
+                    if self.depth > 0 {
+                        let (line, column) = self.open;
+                        return Some(Err(self.error(line, column, "unbalanced parenthesis")));
+                    }
+
+                    // Terminate the last entry if the input doesn't end with
+                    // a line break.
+                    if self.in_entry {
+                        return Some(Ok(self.token(TokenKind::Eol, line, column)));
+                    }
+
+                    return None;
+                }
+            };
+
+            match c {
+                ' ' | '\t' | '\r' => {
+                    self.skip_whitespace();
+
+                    // Leading whitespace means the owner is omitted. Inside
+                    // parentheses it is just a separator.
+                    if column == 1 && self.depth == 0 {
+                        return Some(Ok(self.token(TokenKind::Blank, line, column)));
+                    }
+                }
+                ';' => self.skip_comment(),
+                '\n' => {
+                    self.next_char();
+
+                    if self.depth == 0 && self.in_entry {
+                        return Some(Ok(self.token(TokenKind::Eol, line, column)));
+                    }
+                }
+                '(' => {
+                    self.next_char();
+
+                    if self.depth == 0 {
+                        self.open = (line, column);
+                    }
+
+                    self.depth += 1;
+                }
+                ')' => {
+                    self.next_char();
+
+                    if self.depth == 0 {
+                        self.done = true;
+                        return Some(Err(self.error(line, column, "unbalanced parenthesis")));
+                    }
+
+                    self.depth -= 1;
+                }
+                '"' => {
+                    return Some(match self.read_quoted(line, column) {
+                        Ok(text) => Ok(self.token(TokenKind::Quoted(text), line, column)),
+                        Err(err) => {
+                            self.done = true;
+                            Err(err)
+                        }
+                    })
+                }
+                _ => {
+                    let word = self.read_word();
+                    return Some(Ok(self.token(TokenKind::Word(word), line, column)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<TokenKind> {
+        Lexer::new(input).map(|t| t.unwrap().kind).collect()
+    }
+
+    fn word(text: &str) -> TokenKind {
+        TokenKind::Word(text.into())
+    }
+
+    #[test]
+    fn lex_quoted_and_multi_line() {
+        let input = "a TXT \"b ; c\" ; comment\n\tIN ( 1\n 2 ) ; x\n\n";
+
+        assert_eq!(
+            kinds(input),
+            vec![
+                word("a"),
+                word("TXT"),
+                TokenKind::Quoted("b ; c".into()),
+                TokenKind::Eol,
+                TokenKind::Blank,
+                word("IN"),
+                word("1"),
+                word("2"),
+                TokenKind::Eol,
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_unbalanced() {
+        let err = Lexer::new("a ( b\n").find_map(Result::err).unwrap();
+        assert!(matches!(
+            err,
+            ZoneError::Syntax {
+                line: 1,
+                column: 3,
+                ..
+            }
+        ));
+
+        let err = Lexer::new("a \"b\n").find_map(Result::err).unwrap();
+        assert!(matches!(
+            err,
+            ZoneError::Syntax {
+                line: 1,
+                column: 3,
+                ..
+            }
+        ));
+    }
+}
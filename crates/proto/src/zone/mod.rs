@@ -1,42 +1,19 @@
-use std::{fs, path::PathBuf, str::FromStr};
-
-use crate::{
-    constants::{
-        ZONE_CONTROL_ENTRY_INCLUDE, ZONE_CONTROL_ENTRY_INCLUDE_LEN, ZONE_CONTROL_ENTRY_ORIGIN,
-        ZONE_CONTROL_ENTRY_ORIGIN_LEN,
-    },
-    Class, Name, RData, RHeader, RType, Record, Tree,
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
+use crate::{Label, Name, RData, RType, Record, Tree};
+
 mod error;
+pub(crate) mod lexer;
+mod parser;
 
 pub use error::*;
 
-pub enum ZoneParseState<'a> {
-    NewLine,
-    Entry(&'a str),
-    Origin(&'a str),
-    Include(&'a str),
-    Record(&'a str),
-    ClassAndTTL(RecordParseState<'a>),
-    ClassOrTTL(RecordParseState<'a>),
-    RecordRest(RecordParseState<'a>),
-}
-
-#[derive(Debug)]
-pub struct RecordParseState<'a> {
-    class: Option<Class>,
-    parts: Vec<&'a str>,
-    ttl: Option<u32>,
-    line: &'a str,
-    name: Name,
-}
-
-impl<'a> Default for ZoneParseState<'a> {
-    fn default() -> Self {
-        Self::NewLine
-    }
-}
+use parser::Parser;
 
 #[derive(Debug)]
 pub struct Zone {
@@ -52,208 +29,175 @@ impl Default for Zone {
 impl FromStr for Zone {
     type Err = ZoneError;
 
+    /// Parses a zone from a master file as described in
+    /// [RFC 1035 Section 5](https://datatracker.ietf.org/doc/html/rfc1035#section-5).
+    /// The initial origin is the root. Relative `$INCLUDE` paths are resolved
+    /// relative to the current working directory.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut zone = Zone::default();
 
-        let mut state = ZoneParseState::default();
-        let mut lines = s.lines();
-
-        loop {
-            state = match state {
-                ZoneParseState::NewLine => match lines.next() {
-                    // There are still lines left, continue
-                    Some(line) => {
-                        // If the line is completely empty, skip
-                        if line.is_empty() {
-                            continue;
-                        }
-
-                        // Lines starting with a semicolon are comments and
-                        // can be ignored
-                        if line.starts_with(';') {
-                            continue;
-                        }
-
-                        // If there is content, it has to be an entry
-                        ZoneParseState::Entry(line)
-                    }
-                    // We reached EOF, break
-                    None => break,
-                },
-                ZoneParseState::Entry(line) => {
-                    // We encountered an $ORIGIN control entry. $ORIGIN is
-                    // followed by a domain name, and resets the current origin
-                    // for relative domain names to the stated name.
-                    if line.starts_with(ZONE_CONTROL_ENTRY_ORIGIN) {
-                        let (_, rest) = line.split_at(ZONE_CONTROL_ENTRY_ORIGIN_LEN);
-
-                        state = ZoneParseState::Origin(rest);
-                        continue;
-                    }
-
-                    // We encountered an $INCLUDE control entry. $INCLUDE
-                    // inserts the named file into the current file, and may
-                    // optionally specify a domain name that sets the relative
-                    // domain name origin for the included file.
-                    if line.starts_with(ZONE_CONTROL_ENTRY_INCLUDE) {
-                        let (_, rest) = line.split_at(ZONE_CONTROL_ENTRY_INCLUDE_LEN);
-
-                        state = ZoneParseState::Include(rest);
-                        continue;
-                    }
-
-                    // We encountered a normal record entry
-                    ZoneParseState::Record(line)
-                }
-                ZoneParseState::Origin(line) => {
-                    let line = line.trim();
-                    let parts = line.split(';');
+        Parser::new(Name::default()).parse(s, Path::new("."), &mut zone)?;
+        Ok(zone)
+    }
+}
 
-                    ZoneParseState::NewLine
-                }
-                ZoneParseState::Include(_) => todo!(),
-                ZoneParseState::Record(line) => {
-                    let line = line.trim();
-
-                    // Split the line at ";". When there is a comment we can
-                    // safely ignore everything after the semicolon. If there
-                    // is not semicolon (parts len = 1) we can just use the
-                    // line contents as is.
-                    let parts: Vec<&str> = line.split(';').collect();
-                    let line = parts[0];
-
-                    // At this point we have to parse space / tab separated
-                    // items.
-                    let parts: Vec<&str> =
-                        line.split([' ', '\t']).filter(|p| !p.is_empty()).collect();
-
-                    // Now we need to parse a domain name.
-                    let name = match Name::try_from(parts[0]) {
-                        Ok(name) => name,
-                        Err(err) => return Err(ZoneError::ParseError(err.to_string())),
-                    };
-
-                    // For what ever bonkers reason the RFC 1035 states there
-                    // can be two forms how the RR is formatted. Either the
-                    // class or TTL comes first. AND they can BOTH be optional.
-                    // If the length of parts is only 3, we assume both class
-                    // and TTL is missing, skipping the parsing of those
-                    // completely.
-                    let parse_state = RecordParseState {
-                        parts: parts[1..].to_vec(),
-                        class: None,
-                        ttl: None,
-                        line,
-                        name,
-                    };
-
-                    if parts.len() == 5 {
-                        state = ZoneParseState::ClassAndTTL(parse_state);
-                        continue;
-                    }
-
-                    if parts.len() == 4 {
-                        state = ZoneParseState::ClassOrTTL(parse_state);
-                        continue;
-                    }
-
-                    ZoneParseState::RecordRest(parse_state)
-                }
-                ZoneParseState::ClassAndTTL(parse_state) => {
-                    // So we first try to parse the
-                    // next part (1) as a class. If this fails, we assume the
-                    // next part (2) HAS to be a class, otherwise this is
-                    // invalid input
-                    let mut parse_ttl_at = 1;
-                    let class = match Class::try_from(parse_state.parts[0]) {
-                        Ok(class) => class,
-                        Err(_) => match Class::try_from(parse_state.parts[1]) {
-                            Ok(class) => {
-                                parse_ttl_at = 0;
-                                class
-                            }
-                            Err(err) => return Err(ZoneError::ParseError(err.to_string())),
-                        },
-                    };
-
-                    // Now parse the TTL at the correct index, which is defined
-                    // by the parsing step above.
-                    let ttl = match u32::from_str(parse_state.parts[parse_ttl_at]) {
-                        Ok(ttl) => ttl,
-                        Err(err) => return Err(ZoneError::ParseError(err.to_string())),
-                    };
-
-                    ZoneParseState::RecordRest(RecordParseState {
-                        parts: parse_state.parts[2..].to_vec(),
-                        line: parse_state.line,
-                        name: parse_state.name,
-                        class: Some(class),
-                        ttl: Some(ttl),
-                    })
-                }
-                ZoneParseState::ClassOrTTL(mut parse_state) => {
-                    match Class::try_from(parse_state.parts[0]) {
-                        Ok(class) => {
-                            parse_state.class = Some(class);
-                            parse_state.parts = parse_state.parts[1..].to_vec();
-                            state = ZoneParseState::RecordRest(parse_state);
-                            continue;
-                        }
-                        Err(_) => match u32::from_str(parse_state.parts[0]) {
-                            Ok(ttl) => {
-                                parse_state.ttl = Some(ttl);
-                                parse_state.parts = parse_state.parts[1..].to_vec();
-                                state = ZoneParseState::RecordRest(parse_state);
-                                continue;
-                            }
-                            Err(err) => return Err(ZoneError::ParseError(err.to_string())),
-                        },
-                    };
-                }
-                ZoneParseState::RecordRest(state) => {
-                    // Now we are back in safe territory. The rest of the RR
-                    // has the same format across the board. We now need to
-                    // parse the RR type.
-                    let ty = match RType::try_from(state.parts[0]) {
-                        Ok(ty) => ty,
-                        Err(err) => return Err(ZoneError::ParseError(err.to_string())),
-                    };
-
-                    let rdata = RData::try_from_str(ty, state.parts[1])?;
-
-                    // println!(
-                    //     "{} {:?} {:?} {} {}",
-                    //     state.name, state.class, state.ttl, ty, rdata
-                    // );
-
-                    let mut record_header = RHeader::default();
-                    record_header.set_class(state.class.unwrap_or_default());
-                    record_header.set_ttl(state.ttl.unwrap_or_default());
-                    record_header.set_name(state.name.clone());
-                    record_header.set_ty(ty);
-
-                    let mut record = Record::new_with_header(record_header);
-                    record.set_rdata(rdata);
-
-                    zone.tree.insert(state.name, record)?;
-
-                    ZoneParseState::NewLine
-                }
+impl Display for Zone {
+    /// Formats the zone as master file. The output starts with `$ORIGIN` and
+    /// `$TTL` entries derived from the SOA record, followed by the SOA record
+    /// itself. All other records are sorted canonically by owner name, type
+    /// and RDATA, which makes the output stable. Owner names below the
+    /// origin are written relative to it.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nodes = self.tree.canonical_nodes();
+
+        let soa = nodes.iter().find_map(|(name, node)| {
+            node.records()
+                .iter()
+                .find(|r| r.is_soa())
+                .map(|record| (name, record))
+        });
+
+        let origin = soa.map(|(name, _)| name.clone()).unwrap_or_default();
+        let default_ttl = soa.map(|(_, record)| record.header().ttl());
+
+        writeln!(f, "$ORIGIN {}", absolute_name(&origin))?;
+
+        if let Some(ttl) = default_ttl {
+            writeln!(f, "$TTL {ttl}")?;
+        }
+
+        if let Some((_, record)) = soa {
+            write_record(f, record, &origin, default_ttl)?;
+        }
+
+        for (_, node) in nodes {
+            let mut records: Vec<(u16, Vec<u8>, &Record)> = node
+                .records()
+                .iter()
+                .filter(|r| !r.is_soa())
+                .map(|r| {
+                    let rdata = r.rdata().canonical_bytes().unwrap_or_default();
+                    (u16::from(r.header().ty()), rdata, r)
+                })
+                .collect();
+
+            records.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+            for (_, _, record) in records {
+                write_record(f, record, &origin, default_ttl)?;
             }
         }
 
-        Ok(zone)
+        Ok(())
     }
 }
 
 impl Zone {
-    /// Read a zone from a master zone file.
+    /// Read a zone from a master zone file. Relative `$INCLUDE` paths are
+    /// resolved relative to the directory of the file.
     pub fn from_file(path: PathBuf) -> Result<Self, ZoneError> {
-        let b = fs::read_to_string(path)?;
-        Ok(b.parse()?)
+        let b = fs::read_to_string(&path)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+
+        let mut zone = Zone::default();
+        Parser::new(Name::default()).parse(&b, dir, &mut zone)?;
+
+        Ok(zone)
     }
 
+    /// Writes the zone as master file to `path`. See the [`Display`]
+    /// implementation for details about the format.
     pub fn to_file(&self, path: PathBuf) -> Result<(), ZoneError> {
+        fs::write(path, self.to_string())?;
         Ok(())
     }
+
+    /// Returns the A and AAAA records of the target names of the SVCB and
+    /// HTTPS records in `records`. These records should be added to the
+    /// additional section of responses. See
+    /// [RFC 9460 Section 4.2](https://datatracker.ietf.org/doc/html/rfc9460#section-4.2).
+    pub fn glue(&self, records: &[Record]) -> Vec<Record> {
+        let mut targets: Vec<&Name> = Vec::new();
+
+        for record in records {
+            let target = match record.rdata() {
+                RData::SVCB(svcb) | RData::HTTPS(svcb) => svcb.target_name(record.header().name()),
+                _ => None,
+            };
+
+            if let Some(target) = target {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+        }
+
+        targets
+            .into_iter()
+            // Owner names in the tree are lowercase
+            .filter_map(|target| self.tree.find_node(target.to_lowercase()))
+            .flat_map(|node| node.records())
+            .filter(|record| matches!(record.header().ty(), RType::A | RType::AAAA))
+            .cloned()
+            .collect()
+    }
+}
+
+fn write_record(
+    f: &mut std::fmt::Formatter<'_>,
+    record: &Record,
+    origin: &Name,
+    default_ttl: Option<u32>,
+) -> std::fmt::Result {
+    let header = record.header();
+    write!(f, "{}", relative_name(header.name(), origin))?;
+
+    if default_ttl != Some(header.ttl()) {
+        write!(f, "\t{}", header.ttl())?;
+    }
+
+    writeln!(
+        f,
+        "\t{}\t{}\t{}",
+        header.class(),
+        header.ty(),
+        record.rdata()
+    )
+}
+
+/// Returns `name` relative to `origin` if it is a subdomain of it, `@` for
+/// the origin itself and the fully qualified name otherwise. Labels are
+/// escaped, see [`Label::escaped`].
+fn relative_name(name: &Name, origin: &Name) -> String {
+    if origin.is_root() || name.num_labels() < origin.num_labels() {
+        return absolute_name(name);
+    }
+
+    let (prefix, suffix) = name
+        .labels()
+        .split_at(name.num_labels() - origin.num_labels());
+
+    let in_zone = suffix
+        .iter()
+        .zip(origin.labels())
+        .all(|(a, b)| a.0.eq_ignore_ascii_case(&b.0));
+
+    if !in_zone {
+        return absolute_name(name);
+    }
+
+    if prefix.is_empty() {
+        return String::from("@");
+    }
+
+    prefix
+        .iter()
+        .map(Label::escaped)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Returns the fully qualified `name` with escaped labels.
+fn absolute_name(name: &Name) -> String {
+    name.as_dotted_string()
 }
@@ -0,0 +1,296 @@
+use std::{fs, path::Path};
+
+use crate::{
+    constants::{
+        ZONE_CONTROL_ENTRY_INCLUDE, ZONE_CONTROL_ENTRY_ORIGIN, ZONE_CONTROL_ENTRY_TTL,
+        ZONE_MAX_INCLUDE_DEPTH,
+    },
+    parse_duration,
+    zone::{
+        lexer::{Lexer, Token, TokenKind},
+        Zone, ZoneError,
+    },
+    Class, Name, RData, RHeader, RType, Record,
+};
+
+/// The [`Parser`] turns the entries of a master file into records. It keeps
+/// track of the values which carry over from one entry to the next: the
+/// current origin, the default TTL set by `$TTL` and the owner, class and TTL
+/// of the previous record.
+pub(crate) struct Parser {
+    origin: Name,
+    default_ttl: Option<u32>,
+
+    last_owner: Option<Name>,
+    last_class: Option<Class>,
+    last_ttl: Option<u32>,
+
+    /// Nesting level of `$INCLUDE` entries, used to detect include loops.
+    depth: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(origin: Name) -> Self {
+        Self {
+            origin,
+            default_ttl: None,
+            last_owner: None,
+            last_class: None,
+            last_ttl: None,
+            depth: 0,
+        }
+    }
+
+    /// Parses the master file `input` and inserts all records into `zone`.
+    /// Relative `$INCLUDE` paths are resolved relative to `dir`.
+    pub(crate) fn parse(
+        &mut self,
+        input: &str,
+        dir: &Path,
+        zone: &mut Zone,
+    ) -> Result<(), ZoneError> {
+        let mut entry = Vec::new();
+
+        for token in Lexer::new(input) {
+            let token = token?;
+
+            if token.kind != TokenKind::Eol {
+                entry.push(token);
+                continue;
+            }
+
+            self.parse_entry(&entry, &token, dir, zone)?;
+            entry.clear();
+        }
+
+        Ok(())
+    }
+
+    fn parse_entry(
+        &mut self,
+        entry: &[Token],
+        eol: &Token,
+        dir: &Path,
+        zone: &mut Zone,
+    ) -> Result<(), ZoneError> {
+        let first = match entry.first() {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+
+        match &first.kind {
+            TokenKind::Word(word) if word.starts_with('$') => {
+                self.parse_control_entry(word, &entry[1..], first, dir, zone)
+            }
+            TokenKind::Blank if entry.len() == 1 => Ok(()),
+            _ => {
+                // Nodes are keyed by the lowercase owner name, which makes
+                // lookups case insensitive. The record keeps its case.
+                let record = self.parse_record(entry, eol)?;
+                zone.tree
+                    .insert(record.header().name().to_lowercase(), record)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn parse_control_entry(
+        &mut self,
+        control: &str,
+        args: &[Token],
+        token: &Token,
+        dir: &Path,
+        zone: &mut Zone,
+    ) -> Result<(), ZoneError> {
+        match control.to_uppercase().as_str() {
+            // $ORIGIN <domain-name> resets the current origin for relative
+            // domain names to the stated name.
+            ZONE_CONTROL_ENTRY_ORIGIN => {
+                let name = expect_args(args, 1, 1, token)?;
+                self.origin = self.parse_name(&name[0])?;
+                Ok(())
+            }
+            // $TTL <ttl> sets the default TTL for records without an
+            // explicit TTL. See RFC 2308 Section 4.
+            ZONE_CONTROL_ENTRY_TTL => {
+                let ttl = expect_args(args, 1, 1, token)?;
+                let text = word(&ttl[0])?;
+
+                match parse_duration(text) {
+                    Some(ttl) => self.default_ttl = Some(ttl),
+                    None => return Err(syntax(&ttl[0], format!("invalid TTL '{text}'"))),
+                }
+
+                Ok(())
+            }
+            // $INCLUDE <file-name> [<domain-name>] inserts the named file
+            // into the current file. The optional domain name sets the origin
+            // for the included file. The origin of the current file is not
+            // changed.
+            ZONE_CONTROL_ENTRY_INCLUDE => {
+                let args = expect_args(args, 1, 2, token)?;
+
+                if self.depth >= ZONE_MAX_INCLUDE_DEPTH {
+                    return Err(syntax(token, "too many nested $INCLUDE entries"));
+                }
+
+                let path = match args[0].text() {
+                    Some(path) => dir.join(path),
+                    None => return Err(syntax(&args[0], "expected file name")),
+                };
+
+                let origin = match args.get(1) {
+                    Some(name) => self.parse_name(name)?,
+                    None => self.origin.clone(),
+                };
+
+                let input = fs::read_to_string(&path)?;
+                let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+                let previous = std::mem::replace(&mut self.origin, origin);
+                self.depth += 1;
+
+                let result = self.parse(&input, &dir, zone).map_err(|err| match err {
+                    ZoneError::Syntax {
+                        line,
+                        column,
+                        message,
+                    } => ZoneError::Syntax {
+                        line,
+                        column,
+                        message: format!("{}: {message}", path.display()),
+                    },
+                    err => err,
+                });
+
+                self.depth -= 1;
+                self.origin = previous;
+
+                result
+            }
+            _ => Err(syntax(token, format!("unknown control entry '{control}'"))),
+        }
+    }
+
+    /// Parses a record entry. The general format is
+    ///
+    /// ```text
+    /// <domain-name> [<TTL>] [<class>] <type> <RDATA>
+    /// <domain-name> [<class>] [<TTL>] <type> <RDATA>
+    /// ```
+    ///
+    /// If the domain name is omitted (the entry starts with a blank), the
+    /// owner of the previous record is used.
+    fn parse_record(&mut self, entry: &[Token], eol: &Token) -> Result<Record, ZoneError> {
+        let name = match &entry[0].kind {
+            TokenKind::Blank => match &self.last_owner {
+                Some(owner) => owner.clone(),
+                None => return Err(syntax(&entry[0], "no previous owner name")),
+            },
+            _ => self.parse_name(&entry[0])?,
+        };
+
+        // The TTL and class are both optional and can appear in any order.
+        let mut ttl = None;
+        let mut class = None;
+        let mut rest = &entry[1..];
+
+        while let Some(token) = rest.first() {
+            let text = word(token)?;
+
+            if ttl.is_none() && text.starts_with(|c: char| c.is_ascii_digit()) {
+                match parse_duration(text) {
+                    Some(value) => ttl = Some(value),
+                    None => return Err(syntax(token, format!("invalid TTL '{text}'"))),
+                }
+            } else if let (None, Ok(value)) = (class, Class::try_from(text)) {
+                class = Some(value);
+            } else {
+                break;
+            }
+
+            rest = &rest[1..];
+        }
+
+        let (ty, rdata) = match rest.split_first() {
+            Some((token, rdata)) => {
+                let text = word(token)?;
+
+                match RType::try_from(text) {
+                    Ok(ty) => (ty, rdata),
+                    Err(_) => return Err(syntax(token, format!("unknown record type '{text}'"))),
+                }
+            }
+            None => return Err(syntax(eol, "expected record type")),
+        };
+
+        // Records without an explicit class use the class of the previous
+        // record. Records without an explicit TTL use the $TTL default or
+        // the TTL of the previous record.
+        let class = class.or(self.last_class).unwrap_or_default();
+        let ttl = match ttl.or(self.default_ttl).or(self.last_ttl) {
+            Some(ttl) => ttl,
+            None => return Err(syntax(&entry[0], "no TTL specified")),
+        };
+
+        let position = rdata.first().unwrap_or(eol);
+
+        let rdata = RData::try_from_tokens(ty, rdata, &self.origin)
+            .map_err(|err| syntax(position, err.to_string()))?;
+
+        self.last_owner = Some(name.clone());
+        self.last_class = Some(class);
+        self.last_ttl = Some(ttl);
+
+        let mut header = RHeader::default();
+        header.set_name(name);
+        header.set_class(class);
+        header.set_ttl(ttl);
+        header.set_ty(ty);
+
+        let mut record = Record::new_with_header(header);
+        record.set_rdata(rdata).normalize_rdlen();
+
+        Ok(record)
+    }
+
+    fn parse_name(&self, token: &Token) -> Result<Name, ZoneError> {
+        let text = word(token)?;
+
+        Name::from_relative(text, &self.origin)
+            .map_err(|err| syntax(token, format!("invalid domain name '{text}': {err}")))
+    }
+}
+
+fn word(token: &Token) -> Result<&str, ZoneError> {
+    match &token.kind {
+        TokenKind::Word(word) => Ok(word),
+        TokenKind::Quoted(_) => Err(syntax(token, "unexpected quoted string")),
+        _ => Err(syntax(token, "unexpected blank")),
+    }
+}
+
+fn expect_args<'a>(
+    args: &'a [Token],
+    min: usize,
+    max: usize,
+    token: &Token,
+) -> Result<&'a [Token], ZoneError> {
+    if args.len() < min {
+        return Err(syntax(token, "missing argument"));
+    }
+
+    if args.len() > max {
+        return Err(syntax(&args[max], "unexpected argument"));
+    }
+
+    Ok(args)
+}
+
+fn syntax(token: &Token, message: impl Into<String>) -> ZoneError {
+    ZoneError::Syntax {
+        line: token.line,
+        column: token.column,
+        message: message.into(),
+    }
+}
@@ -0,0 +1,241 @@
+use binbuf::prelude::*;
+use portal_proto::{
+    edns::{self, OptionCode, OptionData},
+    Class, Header, Message, Name, Question, RData, RHeader, RType, Rcode, Record, OPT, UNKNOWN,
+};
+
+fn ns_record(owner: &str, target: &str) -> Record {
+    let mut header = RHeader::new();
+    header.set_name(Name::try_from(owner).unwrap());
+    header.set_ty(RType::NS);
+    header.set_class(Class::IN);
+    header.set_ttl(3600);
+
+    let mut record = Record::new_with_header(header);
+    record.set_rdata(RData::NS(Name::try_from(target).unwrap()));
+    record.normalize_rdlen();
+    record
+}
+
+fn example_message() -> Message {
+    let mut message = Message::new_with_header(Header::new(4242));
+    message.add_question(Question::new(
+        Name::try_from("example.com").unwrap(),
+        RType::NS,
+        Class::IN,
+    ));
+
+    message.add_answer(ns_record("example.com", "ns1.example.com"));
+    message.add_answer(ns_record("example.com", "ns2.example.com"));
+    message.add_answer(ns_record("EXAMPLE.com", "ns3.example.com"));
+
+    message
+}
+
+#[test]
+fn test_message_name_compression() {
+    let message = example_message();
+
+    let mut compressed = WriteBuffer::new();
+    message.write::<BigEndian>(&mut compressed).unwrap();
+
+    let mut canonical = WriteBuffer::new();
+    message
+        .write_canonical::<BigEndian>(&mut canonical)
+        .unwrap();
+
+    assert!(compressed.len() < canonical.len());
+
+    let mut buf = ReadBuffer::new(compressed.bytes());
+    let header = Header::read::<BigEndian>(&mut buf).unwrap();
+    let read = Message::read::<BigEndian>(&mut buf, header).unwrap();
+
+    assert_eq!(read.ancount(), 3);
+
+    for (answer, target) in
+        read.answers()
+            .iter()
+            .zip(["ns1.example.com", "ns2.example.com", "ns3.example.com"])
+    {
+        assert_eq!(
+            answer.header().name(),
+            &Name::try_from("example.com").unwrap()
+        );
+        match answer.rdata() {
+            RData::NS(name) => assert_eq!(name, &Name::try_from(target).unwrap()),
+            rdata => panic!("unexpected RDATA {rdata}"),
+        }
+    }
+}
+
+#[test]
+fn test_message_name_compression_with_prefix() {
+    let message = example_message();
+
+    // Simulate a TCP length prefix in front of the message. Compression
+    // pointers need to be relative to the start of the message.
+    let mut compressed = WriteBuffer::new();
+    0u16.write::<BigEndian>(&mut compressed).unwrap();
+    message.write::<BigEndian>(&mut compressed).unwrap();
+
+    let mut buf = ReadBuffer::new(&compressed.bytes()[2..]);
+    let header = Header::read::<BigEndian>(&mut buf).unwrap();
+    let read = Message::read::<BigEndian>(&mut buf, header).unwrap();
+
+    assert_eq!(read.ancount(), 3);
+    assert_eq!(
+        read.question().unwrap().name,
+        Name::try_from("example.com").unwrap()
+    );
+}
+
+#[test]
+fn test_message_truncate() {
+    let mut message = example_message();
+
+    // The message already fits, nothing to do
+    assert!(!message.truncate::<BigEndian>(512).unwrap());
+    assert_eq!(message.ancount(), 3);
+
+    // Only the header and question fit, so the NS RRset is removed as a
+    // whole
+    assert!(message.truncate::<BigEndian>(40).unwrap());
+    assert!(message.is_truncated());
+    assert_eq!(message.ancount(), 0);
+
+    let mut buf = WriteBuffer::new();
+    message.write::<BigEndian>(&mut buf).unwrap();
+    assert!(buf.len() <= 40);
+
+    let mut reader = ReadBuffer::new(buf.bytes());
+    let header = Header::read::<BigEndian>(&mut reader).unwrap();
+    let read = Message::read::<BigEndian>(&mut reader, header).unwrap();
+    assert!(read.is_truncated());
+    assert_eq!(read.ancount(), 0);
+}
+
+#[test]
+fn test_message_truncate_keeps_fitting_rrsets() {
+    let owners = [
+        "a.example.com",
+        "b.example.com",
+        "c.example.com",
+        "d.example.com",
+    ];
+
+    let mut message = Message::new_with_header(Header::new(4242));
+    message.add_question(Question::new(
+        Name::try_from("example.com").unwrap(),
+        RType::NS,
+        Class::IN,
+    ));
+
+    for owner in owners {
+        message.add_answer(ns_record(owner, "ns.example.com"));
+    }
+
+    // The size of the message with only the first two RRsets
+    let mut expected = Message::new_with_header(Header::new(4242));
+    expected.add_question(Question::new(
+        Name::try_from("example.com").unwrap(),
+        RType::NS,
+        Class::IN,
+    ));
+
+    for owner in &owners[..2] {
+        expected.add_answer(ns_record(owner, "ns.example.com"));
+    }
+
+    let mut buf = WriteBuffer::new();
+    let max_size = expected.write::<BigEndian>(&mut buf).unwrap();
+
+    // Only as many RRsets as required are dropped
+    assert!(message.truncate::<BigEndian>(max_size).unwrap());
+    assert_eq!(message.ancount(), 2);
+
+    let mut buf = WriteBuffer::new();
+    assert!(message.write::<BigEndian>(&mut buf).unwrap() <= max_size);
+}
+
+#[test]
+fn test_message_edns_roundtrip() {
+    let mut opt = OPT::new(1232);
+    opt.add_option(edns::Option::new(
+        OptionCode::NSID,
+        OptionData::NSID(b"ns1".to_vec()),
+    ));
+    opt.add_option(edns::Option::new(
+        OptionCode::from(65001),
+        OptionData::UNKNOWN(vec![0xde, 0xad, 0xbe, 0xef]),
+    ));
+
+    let mut message = example_message();
+    message.set_opt(opt);
+    message.set_dnssec_ok(true);
+    message.set_extended_rcode(Rcode::BadVers);
+    assert_eq!(message.arcount(), 1);
+
+    let mut buf = WriteBuffer::new();
+    message.write::<BigEndian>(&mut buf).unwrap();
+
+    let mut reader = ReadBuffer::new(buf.bytes());
+    let header = Header::read::<BigEndian>(&mut reader).unwrap();
+    let read = Message::read::<BigEndian>(&mut reader, header).unwrap();
+
+    assert!(read.is_edns());
+    assert_eq!(read.udp_payload_size(), Some(1232));
+    assert_eq!(read.edns_version(), Some(0));
+    assert!(read.dnssec_ok());
+    assert_eq!(read.extended_rcode(), Rcode::BadVers);
+    assert_eq!(read.opt(), message.opt());
+
+    let opt = read.opt().unwrap();
+    assert_eq!(opt.options().len(), 2);
+    assert_eq!(
+        opt.option(OptionCode::from(65001)).unwrap().data(),
+        &OptionData::UNKNOWN(vec![0xde, 0xad, 0xbe, 0xef])
+    );
+}
+
+#[test]
+fn test_message_unknown_type_roundtrip() {
+    let data = vec![0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x03, 0x02, 0x68, 0x32];
+
+    let mut header = RHeader::new();
+    header.set_name(Name::try_from("example.com").unwrap());
+    header.set_ty(RType::from(65280));
+    header.set_class(Class::IN);
+    header.set_ttl(300);
+
+    let mut record = Record::new_with_header(header);
+    record.set_rdata(RData::UNKNOWN(UNKNOWN::new(65280, data.clone())));
+    record.normalize_rdlen();
+
+    let mut message = example_message();
+    message.add_answer(record);
+
+    let mut buf = WriteBuffer::new();
+    message.write::<BigEndian>(&mut buf).unwrap();
+    let bytes = buf.owned_bytes();
+
+    let mut reader = ReadBuffer::new(&bytes);
+    let header = Header::read::<BigEndian>(&mut reader).unwrap();
+    let read = Message::read::<BigEndian>(&mut reader, header).unwrap();
+
+    let answer = &read.answers()[3];
+    assert_eq!(answer.header().ty().to_string(), "TYPE65280");
+    assert_eq!(answer.rdata().to_string(), r"\# 10 00010000010003026832");
+
+    match answer.rdata() {
+        RData::UNKNOWN(unknown) => {
+            assert_eq!(unknown.ty(), 65280);
+            assert_eq!(unknown.data(), data.as_slice());
+        }
+        rdata => panic!("expected opaque RDATA, got {rdata:?}"),
+    }
+
+    // Writing the message again results in the exact same bytes
+    let mut buf = WriteBuffer::new();
+    read.write::<BigEndian>(&mut buf).unwrap();
+    assert_eq!(buf.bytes(), bytes.as_slice());
+}
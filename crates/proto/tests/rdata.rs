@@ -0,0 +1,278 @@
+use std::cmp::Ordering;
+
+use binbuf::prelude::*;
+use portal_proto::{Name, RData, RHeader, RType};
+
+/// Parses `text` in presentation format, writes the RDATA in wire format,
+/// reads it back and returns it formatted again.
+fn roundtrip(ty: RType, text: &str) -> String {
+    let rdata = match RData::try_from_str(ty, text) {
+        Ok(rdata) => rdata,
+        Err(err) => panic!("{err}"),
+    };
+
+    let mut buf = WriteBuffer::new();
+    let n = rdata.write::<BigEndian>(&mut buf).unwrap();
+    assert_eq!(n, rdata.size());
+
+    let mut header = RHeader::new();
+    header.set_ty(ty);
+    header.set_rdlen(n as u16);
+
+    let mut reader = ReadBuffer::new(buf.bytes());
+    let read = RData::read::<BigEndian>(&mut reader, &header).unwrap();
+    assert_eq!(read.size(), n);
+
+    read.to_string()
+}
+
+#[test]
+fn test_rdata_srv() {
+    let text = "10 60 5060 sip.example.com.";
+    assert_eq!(roundtrip(RType::SRV, text), text);
+    assert!(RData::try_from_str(RType::SRV, "10 60 sip.example.com.").is_err());
+}
+
+#[test]
+fn test_rdata_caa() {
+    let text = "128 issue \"letsencrypt.org; validationmethods=dns-01\"";
+    assert_eq!(roundtrip(RType::CAA, text), text);
+    assert!(RData::try_from_str(RType::CAA, "0 is-sue \"ca.example.net\"").is_err());
+}
+
+#[test]
+fn test_rdata_naptr() {
+    let text = "100 10 \"U\" \"E2U+sip\" \"!^.*$!sip:info@example.com!\" .";
+    assert_eq!(roundtrip(RType::NAPTR, text), text);
+}
+
+#[test]
+fn test_rdata_dname() {
+    let text = "example.net.";
+    assert_eq!(roundtrip(RType::DNAME, text), text);
+}
+
+#[test]
+fn test_rdata_sshfp() {
+    let text = "4 2 9A3C1F6C0E4BB7C9A7B5F4D48D0C0A2F6E1D7C3B5A4F2E1D0C9B8A7F6E5D4C3B";
+    assert_eq!(roundtrip(RType::SSHFP, text), text);
+
+    // The fingerprint can be split and use lowercase letters
+    assert_eq!(
+        roundtrip(
+            RType::SSHFP,
+            "1 1 dd465c09cfa51fb45020cc83316fff ( 21b9ec74ac )"
+        ),
+        "1 1 DD465C09CFA51FB45020CC83316FFF21B9EC74AC"
+    );
+}
+
+#[test]
+fn test_rdata_tlsa() {
+    let text = "3 1 1 0D6FCE3BEC8C2B5ED1F1E9E34D0C2A6A5D4E3F2A1B0C9D8E7F6A5B4C3D2E1F0A";
+    assert_eq!(roundtrip(RType::TLSA, text), text);
+    assert!(RData::try_from_str(RType::TLSA, "3 1 1 0D6").is_err());
+}
+
+#[test]
+fn test_rdata_uri() {
+    let text = "10 1 \"ftp://ftp1.example.com/public\"";
+    assert_eq!(roundtrip(RType::URI, text), text);
+    assert!(RData::try_from_str(RType::URI, "10 1 \"\"").is_err());
+}
+
+#[test]
+fn test_rdata_svcb() {
+    let text = "1 . alpn=\"h2,h3\" port=8443 ipv4hint=192.0.2.1,192.0.2.2 ech=AQID ipv6hint=2001:db8::1 key65333=\"ex\"";
+    assert_eq!(roundtrip(RType::HTTPS, text), text);
+
+    // Parameters are sorted by key, mandatory keys are listed in order
+    assert_eq!(
+        roundtrip(
+            RType::SVCB,
+            "16 foo.example.org. port=53 mandatory=port,alpn alpn=dot no-default-alpn"
+        ),
+        "16 foo.example.org. mandatory=alpn,port alpn=\"dot\" no-default-alpn port=53"
+    );
+
+    // Escaped commas are part of the ALPN ID
+    assert_eq!(
+        roundtrip(RType::SVCB, r#"1 . alpn="f\\,oo,bar""#),
+        r#"1 . alpn="f\\,oo,bar""#
+    );
+
+    assert_eq!(
+        roundtrip(RType::HTTPS, "0 pool.svc.example."),
+        "0 pool.svc.example."
+    );
+}
+
+#[test]
+fn test_rdata_svcb_invalid() {
+    for text in [
+        // Duplicate key
+        "1 . port=53 port=54",
+        // Missing mandatory key
+        "1 . mandatory=alpn port=53",
+        // Mandatory lists itself
+        "1 . mandatory=mandatory",
+        // no-default-alpn takes no value
+        "1 . alpn=h2 no-default-alpn=1",
+        "1 . port=http",
+        "1 . key65535=1",
+    ] {
+        assert!(RData::try_from_str(RType::SVCB, text).is_err(), "{text}");
+    }
+
+    // Keys on the wire have to be in strictly increasing order
+    let data = [
+        0x00, 0x01, 0x00, 0x00, 0x03, 0x00, 0x02, 0x01, 0xbb, 0x00, 0x01, 0x00, 0x03, 0x02, 0x68,
+        0x32,
+    ];
+
+    let mut header = RHeader::new();
+    header.set_ty(RType::HTTPS);
+    header.set_rdlen(data.len() as u16);
+
+    let mut reader = ReadBuffer::new(&data);
+    assert!(RData::read::<BigEndian>(&mut reader, &header).is_err());
+}
+
+const DNSKEY: &str = "256 3 5 AQPSKmynfzW4kyBv015MUG2DeIQ3Cbl+BBZH4b/0PY1kxkmvHjcZc8nokfzj31GajIQKY+5CptLr3buXA10hWqTkF7H6RfoRqXQeogmMHfpftf6zMv1LyBUgia7za6ZEzOJBOztyvhjL742iU/TpPSEDhm2SNKLijfUppn1UaNvv4w==";
+
+#[test]
+fn test_rdata_dnskey() {
+    assert_eq!(roundtrip(RType::DNSKEY, DNSKEY), DNSKEY);
+    assert_eq!(roundtrip(RType::CDNSKEY, "0 3 0 AA=="), "0 3 0 AA==");
+
+    match RData::try_from_str(RType::DNSKEY, DNSKEY) {
+        Ok(RData::DNSKEY(dnskey)) => {
+            assert_eq!(dnskey.key_tag(), 2642);
+            assert!(dnskey.is_zone_key());
+            assert!(!dnskey.is_secure_entry_point());
+        }
+        _ => panic!("expected DNSKEY"),
+    }
+
+    assert!(RData::try_from_str(RType::DNSKEY, "256 3 5 AQPS*").is_err());
+}
+
+#[test]
+fn test_rdata_ds() {
+    let text = "60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118";
+    assert_eq!(roundtrip(RType::DS, text), text);
+    assert_eq!(roundtrip(RType::CDS, "0 0 0 00"), "0 0 0 00");
+}
+
+#[test]
+fn test_rdata_rrsig() {
+    let text = "A 5 3 86400 20030322173103 20030220173103 2642 example.com. oJB1W6WNGv+ldvQ3WDG0MQkg5IEhjRip8WTrPYGv07h108dUKGMeDPKijVCHX3DDKdfb+v6oB9wfuh3DTJXUAfI/M0zmO/zz8bW0Rznl8O3tGNazPwQKkRN20XPXV6nwwfoXmJQbsLNrLfkGJ5D6fwFm8nN+6pBzeDQfsS3Ap3o=";
+    assert_eq!(roundtrip(RType::RRSIG, text), text);
+
+    // Timestamps can also be given as seconds since the epoch
+    let secs = text.replace("20030322173103", "1048354263");
+    assert_eq!(roundtrip(RType::RRSIG, &secs), text);
+
+    let invalid = text.replace("20030322173103", "20030231173103");
+    assert!(RData::try_from_str(RType::RRSIG, &invalid).is_err());
+}
+
+#[test]
+fn test_rdata_nsec() {
+    let text = "host.example.com. A MX RRSIG NSEC TYPE1234";
+    assert_eq!(roundtrip(RType::NSEC, text), text);
+
+    // The type bit maps from RFC 4034 Section 4.3
+    let rdata = RData::try_from_str(RType::NSEC, text).unwrap();
+    let mut buf = WriteBuffer::new();
+    rdata.write::<BigEndian>(&mut buf).unwrap();
+
+    let mut expected = vec![0x00, 0x06, 0x40, 0x01, 0x00, 0x00, 0x00, 0x03, 0x04, 0x1b];
+    expected.extend_from_slice(&[0x00; 31]);
+    expected.push(0x20);
+    assert_eq!(&buf.bytes()[18..], expected.as_slice());
+
+    // Bitmaps must not be empty
+    let data = [0x00, 0x00, 0x00];
+    let mut header = RHeader::new();
+    header.set_ty(RType::NSEC);
+    header.set_rdlen(data.len() as u16);
+
+    let mut reader = ReadBuffer::new(&data);
+    assert!(RData::read::<BigEndian>(&mut reader, &header).is_err());
+}
+
+#[test]
+fn test_rdata_nsec3() {
+    assert_eq!(
+        roundtrip(
+            RType::NSEC3,
+            "1 1 12 aabbccdd 2t7b4g4vsa5smi47k61mv5bv1a22bojr MX DNSKEY NS SOA NSEC3PARAM RRSIG"
+        ),
+        "1 1 12 AABBCCDD 2t7b4g4vsa5smi47k61mv5bv1a22bojr NS SOA MX RRSIG DNSKEY NSEC3PARAM"
+    );
+
+    let text = "1 0 0 - 2vptu5timamqttgl4luu9kg21e0aor3s";
+    assert_eq!(roundtrip(RType::NSEC3, text), text);
+    assert!(RData::try_from_str(RType::NSEC3, "1 0 0 - 2vptu5timamqttgl4luu9kg21e0aor3w").is_err());
+
+    assert_eq!(
+        roundtrip(RType::NSEC3PARAM, "1 0 12 aabbccdd"),
+        "1 0 12 AABBCCDD"
+    );
+    assert_eq!(roundtrip(RType::NSEC3PARAM, "1 0 0 -"), "1 0 0 -");
+}
+
+#[test]
+fn test_canonical_order() {
+    let names: Vec<Name> = [
+        "example",
+        "a.example",
+        "yljkjljk.a.example",
+        "Z.a.example",
+        "zABC.a.EXAMPLE",
+        "z.example",
+    ]
+    .iter()
+    .map(|n| Name::try_from(*n).unwrap())
+    .collect();
+
+    for pair in names.windows(2) {
+        assert_eq!(pair[0].cmp_canonical(&pair[1]), Ordering::Less);
+    }
+
+    let mx = RData::try_from_str(RType::MX, "10 MAIL.Example.COM.").unwrap();
+    assert_eq!(mx.to_canonical().to_string(), "10 mail.example.com.");
+
+    // Names in NSEC records are not converted (RFC 6840 Section 5.1)
+    let nsec = RData::try_from_str(RType::NSEC, "Host.example.com. A").unwrap();
+    assert_eq!(nsec.to_canonical().to_string(), "Host.example.com. A");
+}
+
+#[test]
+fn test_rtype_mappings() {
+    for (ty, code, name) in [
+        (RType::SRV, 33, "SRV"),
+        (RType::NAPTR, 35, "NAPTR"),
+        (RType::DNAME, 39, "DNAME"),
+        (RType::SSHFP, 44, "SSHFP"),
+        (RType::DS, 43, "DS"),
+        (RType::RRSIG, 46, "RRSIG"),
+        (RType::NSEC, 47, "NSEC"),
+        (RType::DNSKEY, 48, "DNSKEY"),
+        (RType::NSEC3, 50, "NSEC3"),
+        (RType::NSEC3PARAM, 51, "NSEC3PARAM"),
+        (RType::TLSA, 52, "TLSA"),
+        (RType::CDS, 59, "CDS"),
+        (RType::CDNSKEY, 60, "CDNSKEY"),
+        (RType::SVCB, 64, "SVCB"),
+        (RType::HTTPS, 65, "HTTPS"),
+        (RType::URI, 256, "URI"),
+        (RType::CAA, 257, "CAA"),
+    ] {
+        assert_eq!(RType::from(code), ty);
+        assert_eq!(u16::from(ty), code);
+        assert_eq!(ty.to_string(), name);
+        assert_eq!(RType::try_from(name).unwrap(), ty);
+    }
+}
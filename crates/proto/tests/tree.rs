@@ -1,6 +1,6 @@
 use std::net::Ipv4Addr;
 
-use portal_proto::{Name, RData, Record, Tree};
+use portal_proto::{Name, RData, RHeader, RType, Record, Tree};
 
 #[test]
 fn test_tree_population() {
@@ -19,3 +19,111 @@ fn test_tree_population() {
     println!("{:#?}", tree);
     println!("{:#?}", example_node);
 }
+
+fn tree() -> Tree {
+    let mut tree = Tree::new();
+
+    let records = [
+        (
+            "example.com.",
+            RData::NS(Name::try_from("ns.example.com.").unwrap()),
+        ),
+        ("ns.example.com.", RData::A(Ipv4Addr::new(192, 0, 2, 1))),
+        ("a.b.example.com.", RData::A(Ipv4Addr::new(192, 0, 2, 2))),
+        ("*.example.com.", RData::A(Ipv4Addr::new(192, 0, 2, 3))),
+        (
+            "sub.example.com.",
+            RData::NS(Name::try_from("ns.sub.example.com.").unwrap()),
+        ),
+    ];
+
+    for (name, rdata) in records {
+        let mut header = RHeader::new();
+        header.set_name(Name::try_from(name).unwrap());
+        header.set_ty(match rdata {
+            RData::NS(_) => RType::NS,
+            _ => RType::A,
+        });
+
+        let mut record = Record::new_with_header(header);
+        record.set_rdata(rdata);
+
+        tree.insert(Name::try_from(name).unwrap(), record).unwrap();
+    }
+
+    tree
+}
+
+#[test]
+fn test_tree_lookup_exact() {
+    let tree = tree();
+    let lookup = tree.lookup(&Name::try_from("ns.example.com.").unwrap());
+
+    assert!(lookup.exists());
+    assert!(!lookup.is_empty_non_terminal());
+    assert!(lookup.wildcard.is_none());
+    assert_eq!(lookup.encloser, Name::try_from("ns.example.com.").unwrap());
+    assert_eq!(lookup.node().unwrap().records().len(), 1);
+}
+
+#[test]
+fn test_tree_lookup_wildcard() {
+    let tree = tree();
+    let lookup = tree.lookup(&Name::try_from("www.example.com.").unwrap());
+
+    assert!(!lookup.exists());
+    assert!(lookup.node().is_none());
+    assert_eq!(lookup.encloser, Name::try_from("example.com.").unwrap());
+    assert_eq!(lookup.wildcard.unwrap().records().len(), 1);
+
+    // The closest encloser b.example.com has no wildcard child
+    let lookup = tree.lookup(&Name::try_from("x.b.example.com.").unwrap());
+    assert_eq!(lookup.encloser, Name::try_from("b.example.com.").unwrap());
+    assert!(lookup.wildcard.is_none());
+}
+
+#[test]
+fn test_tree_lookup_empty_non_terminal() {
+    let tree = tree();
+    let lookup = tree.lookup(&Name::try_from("b.example.com.").unwrap());
+
+    assert!(lookup.exists());
+    assert!(lookup.is_empty_non_terminal());
+    assert!(lookup.wildcard.is_none());
+}
+
+#[test]
+fn test_tree_lookup_zone_cut() {
+    let tree = tree();
+
+    let lookup = tree.lookup(&Name::try_from("host.sub.example.com.").unwrap());
+    let (cut, node) = lookup.zone_cut.unwrap();
+    assert_eq!(cut, Name::try_from("sub.example.com.").unwrap());
+    assert!(node.is_zone_cut());
+
+    let lookup = tree.lookup(&Name::try_from("a.b.example.com.").unwrap());
+    let (cut, _) = lookup.zone_cut.unwrap();
+    assert_eq!(cut, Name::try_from("example.com.").unwrap());
+
+    let lookup = tree.lookup(&Name::try_from("example.org.").unwrap());
+    assert!(lookup.zone_cut.is_none());
+}
+
+#[test]
+fn test_node_children() {
+    let tree = tree();
+    let node = tree
+        .find_node(Name::try_from("example.com.").unwrap())
+        .unwrap();
+
+    let mut labels: Vec<String> = node
+        .children()
+        .map(|(label, index)| {
+            assert_eq!(tree.find_node_by_index(index).unwrap().index(), index);
+            label.to_string()
+        })
+        .collect();
+    labels.sort();
+
+    assert_eq!(labels, vec!["*", "b", "ns", "sub"]);
+}
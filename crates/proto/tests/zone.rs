@@ -1,4 +1,7 @@
-use portal_proto::{Name, Zone};
+use std::str::FromStr;
+
+use binbuf::prelude::*;
+use portal_proto::{Name, RData, RType, Zone, ZoneError};
 
 #[test]
 fn test_parse_zone_file() {
@@ -14,6 +17,74 @@ fn test_parse_zone_file() {
     };
 }
 
+#[test]
+fn test_parse_zone_file_with_control_entries() {
+    let zone = match Zone::from_file("./tests/files/example.com.zone".into()) {
+        Ok(zone) => zone,
+        Err(err) => panic!("{}", err),
+    };
+
+    // The SOA record spans multiple lines and uses relative names
+    let apex = zone
+        .tree
+        .find_node(Name::try_from("example.com.").unwrap())
+        .unwrap();
+    let records = apex.records();
+    assert_eq!(records.len(), 3);
+
+    match records[0].rdata() {
+        RData::SOA(soa) => {
+            assert_eq!(
+                soa.get_mname(),
+                &Name::try_from("ns1.example.com.").unwrap()
+            );
+            assert_eq!(soa.get_serial(), 2024032401);
+            assert_eq!(soa.get_minimum(), 300);
+        }
+        rdata => panic!("expected SOA, got {rdata:?}"),
+    }
+
+    // $TTL applies to records without an explicit TTL
+    assert_eq!(records[0].header().ttl(), 3600);
+
+    // Both NS records inherit the owner of the SOA
+    assert!(records[1..].iter().all(|r| *r.header().ty() == RType::NS));
+
+    let ns1 = zone
+        .tree
+        .find_node(Name::try_from("ns1.example.com.").unwrap())
+        .unwrap();
+    assert_eq!(ns1.records().len(), 2);
+
+    let www = zone
+        .tree
+        .find_node(Name::try_from("www.example.com.").unwrap())
+        .unwrap();
+    assert_eq!(www.records()[0].header().ttl(), 300);
+
+    // Records of the included file are relative to the $INCLUDE origin
+    let host = zone
+        .tree
+        .find_node(Name::try_from("host.sub.example.com.").unwrap())
+        .unwrap();
+    assert_eq!(host.records()[0].header().ttl(), 86400);
+}
+
+#[test]
+fn test_parse_zone_errors() {
+    let input = "$TTL 3600\nexample.com. IN A 192.0.2.1\n  IN A ( 192.0.2.2\n";
+
+    match Zone::from_str(input) {
+        Err(ZoneError::Syntax { line, column, .. }) => assert_eq!((line, column), (3, 8)),
+        result => panic!("expected syntax error, got {result:?}"),
+    }
+
+    match Zone::from_str("example.com. 3600 IN A not-an-ip\n") {
+        Err(ZoneError::Syntax { line, column, .. }) => assert_eq!((line, column), (1, 24)),
+        result => panic!("expected syntax error, got {result:?}"),
+    }
+}
+
 // #[test]
 // fn test_parse_zone_into_hints() {
 //     let zone = match Zone::from_file("./named.root".into()) {
@@ -26,3 +97,201 @@ fn test_parse_zone_file() {
 
 //     println!("{:#?}", hints);
 // }
+
+fn wire(rdata: &RData) -> Vec<u8> {
+    let mut buf = WriteBuffer::new();
+    rdata.write::<BigEndian>(&mut buf).unwrap();
+    buf.bytes().to_vec()
+}
+
+#[test]
+fn test_parse_rdata_presentation_format() {
+    let origin = Name::try_from("example.com.").unwrap();
+
+    let mx = RData::try_from_parts(RType::MX, &["10", "mail"], &origin).unwrap();
+    let mut expected = vec![0, 10, 4];
+    expected.extend_from_slice(b"mail\x07example\x03com\x00");
+    assert_eq!(wire(&mx), expected);
+
+    let txt = RData::try_from_str(RType::TXT, r#""v=spf1 -all; x" "a\"b" \065"#).unwrap();
+    assert_eq!(wire(&txt), b"\x0ev=spf1 -all; x\x03a\"b\x01A");
+
+    let hinfo = RData::try_from_str(RType::HINFO, "\"INTEL-386\" UNIX").unwrap();
+    assert_eq!(wire(&hinfo), b"\x09INTEL-386\x04UNIX");
+
+    let ptr = RData::try_from_str(RType::PTR, "host.example.com.").unwrap();
+    assert_eq!(wire(&ptr), b"\x04host\x07example\x03com\x00");
+
+    // RFC 3597 generic encoding for known types
+    let a = RData::try_from_str(RType::A, r"\# 4 C0000201").unwrap();
+    assert_eq!(a.to_string(), "192.0.2.1");
+
+    let null = RData::try_from_str(RType::NULL, r"\# 3 0102 03").unwrap();
+    assert_eq!(wire(&null), vec![1, 2, 3]);
+
+    // Only an unquoted \# starts the generic encoding
+    let txt = RData::try_from_str(RType::TXT, r#""\#" "4""#).unwrap();
+    assert_eq!(wire(&txt), b"\x01#\x014");
+
+    assert!(RData::try_from_str(RType::A, r"\# 5 C0000201").is_err());
+    assert!(RData::try_from_str(RType::MX, "10").is_err());
+    assert!(RData::try_from_str(RType::MX, "high mail.example.com.").is_err());
+}
+
+#[test]
+fn test_zone_to_file_roundtrip() {
+    let zone = Zone::from_file("./tests/files/example.com.zone".into()).unwrap();
+    let output = zone.to_string();
+
+    let mut lines = output.lines();
+    assert_eq!(lines.next(), Some("$ORIGIN example.com."));
+    assert_eq!(lines.next(), Some("$TTL 3600"));
+    assert_eq!(
+        lines.next(),
+        Some(
+            "@\tIN\tSOA\tns1.example.com. hostmaster.example.com. 2024032401 7200 900 1209600 300"
+        )
+    );
+
+    // Records are sorted canonically, the TTL is only written if it differs
+    // from the default.
+    assert_eq!(
+        lines.collect::<Vec<_>>(),
+        vec![
+            "@\tIN\tNS\tns1.example.com.",
+            "@\tIN\tNS\tns2.example.net.",
+            "ns1\tIN\tA\t192.0.2.1",
+            "ns1\tIN\tAAAA\t2001:db8::1",
+            "host.sub\t86400\tIN\tA\t192.0.2.10",
+            "www\t300\tIN\tCNAME\texample.com.",
+        ]
+    );
+
+    // Parsing the output again results in the same zone
+    let path = std::env::temp_dir().join("portal-example.com.zone");
+    zone.to_file(path.clone()).unwrap();
+
+    let reparsed = Zone::from_file(path).unwrap();
+    assert_eq!(reparsed.to_string(), output);
+}
+
+#[test]
+fn test_zone_records_have_rdlen() {
+    let zone = Zone::from_file("./tests/files/example.com.zone".into()).unwrap();
+    let apex = zone
+        .tree
+        .find_node(Name::try_from("example.com.").unwrap())
+        .unwrap();
+
+    // Records are written with the stored RDLEN, so it has to match the
+    // uncompressed RDATA
+    for record in apex.records() {
+        let rdata = record.rdata().canonical_bytes().unwrap();
+        assert_eq!(record.header().rdlen() as usize, rdata.len());
+    }
+}
+
+#[test]
+fn test_zone_to_string_escapes_and_sorts_canonically() {
+    let input = "$ORIGIN example.com.\n\
+        $TTL 300\n\
+        @ SOA ns1 hostmaster 1 7200 900 1209600 300\n\
+        @ NS NS2.example.com.\n\
+        @ NS ns1.example.com.\n\
+        a\\.b TXT \"dot\"\n\
+        a\\032c TXT \"space\"\n";
+
+    let zone = Zone::from_str(input).unwrap();
+    let output = zone.to_string();
+    let lines: Vec<&str> = output.lines().collect();
+
+    // Labels containing special characters are escaped
+    assert!(lines.contains(&"a\\.b\tIN\tTXT\t\"dot\""));
+    assert!(lines.contains(&"a\\032c\tIN\tTXT\t\"space\""));
+
+    // RDATA is compared in its canonical, lowercased form
+    let ns1 = lines.iter().position(|l| l.ends_with("ns1.example.com."));
+    let ns2 = lines.iter().position(|l| l.ends_with("NS2.example.com."));
+    assert!(ns1.unwrap() < ns2.unwrap());
+
+    let reparsed = Zone::from_str(&output).unwrap();
+    assert_eq!(reparsed.to_string(), output);
+}
+
+#[test]
+fn test_zone_to_string_escapes_origin_and_rdata() {
+    let input = "$ORIGIN ex\\.ample.com.\n\
+        $TTL 300\n\
+        @ SOA ns1 hostmaster 1 7200 900 1209600 300\n\
+        www CNAME a\\;b\\032c.example.org.\n";
+
+    let zone = Zone::from_str(input).unwrap();
+    let output = zone.to_string();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(lines[0], "$ORIGIN ex\\.ample.com.");
+    assert!(lines.contains(&"www\tIN\tCNAME\ta\\;b\\032c.example.org."));
+
+    // The escaped names are parsed back into the same labels
+    let reparsed = Zone::from_str(&output).unwrap();
+    assert_eq!(reparsed.to_string(), output);
+
+    let owner = Name::from_relative("www.ex\\.ample.com.", &Name::default()).unwrap();
+    let node = reparsed.tree.find_node(owner).unwrap();
+
+    match node.records()[0].rdata() {
+        RData::CNAME(target) => assert_eq!(target.labels()[0].0, b"a;b c"),
+        rdata => panic!("unexpected RDATA {rdata}"),
+    }
+}
+
+#[test]
+fn test_zone_svcb_glue() {
+    let input = "$ORIGIN example.com.\n\
+        $TTL 300\n\
+        @ HTTPS 1 . alpn=h2\n\
+        @ A 192.0.2.1\n\
+        _dns HTTPS 1 svc port=853\n\
+        svc A 192.0.2.2\n\
+        svc AAAA 2001:db8::2\n\
+        svc TXT \"not glue\"\n";
+
+    let zone = Zone::from_str(input).unwrap();
+
+    let apex = zone
+        .tree
+        .find_node(Name::try_from("example.com").unwrap())
+        .unwrap();
+    let glue = zone.glue(apex.records());
+    assert_eq!(glue.len(), 1);
+    assert_eq!(glue[0].rdata().to_string(), "192.0.2.1");
+
+    let dns = zone
+        .tree
+        .find_node(Name::try_from("_dns.example.com").unwrap())
+        .unwrap();
+    let glue: Vec<String> = zone
+        .glue(dns.records())
+        .iter()
+        .map(|r| r.rdata().to_string())
+        .collect();
+    assert_eq!(glue, vec!["192.0.2.2", "2001:db8::2"]);
+}
+
+#[test]
+fn test_zone_svcb_glue_case_insensitive() {
+    let input = "$ORIGIN example.com.\n\
+        $TTL 300\n\
+        _dns HTTPS 1 SVC port=853\n\
+        Svc A 192.0.2.2\n";
+
+    let zone = Zone::from_str(input).unwrap();
+
+    let dns = zone
+        .tree
+        .find_node(Name::try_from("_dns.example.com").unwrap())
+        .unwrap();
+    let glue = zone.glue(dns.records());
+    assert_eq!(glue.len(), 1);
+    assert_eq!(glue[0].header().name().to_string(), "Svc.example.com.");
+}
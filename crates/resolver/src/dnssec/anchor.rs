@@ -0,0 +1,76 @@
+use portal_proto::{Name, RData, Record, Zone, DNSKEY, DS};
+
+use crate::ResolverError;
+
+/// The trust anchors of a validating resolver. Every chain of trust starts
+/// at these DS or DNSKEY records, which all belong to a single zone (usually
+/// the root zone). See
+/// [RFC 4033 Section 5](https://datatracker.ietf.org/doc/html/rfc4033#section-5).
+#[derive(Debug, Clone)]
+pub struct TrustAnchors {
+    zone: Name,
+    ds: Vec<DS>,
+    keys: Vec<DNSKEY>,
+}
+
+impl TrustAnchors {
+    /// Reads the trust anchors from a master file containing DS and / or
+    /// DNSKEY records. This is the same format the hint file uses.
+    pub fn from_file(path: String) -> Result<Self, ResolverError> {
+        let zone = Zone::from_file(path.into())?;
+        let nodes = zone.tree.canonical_nodes();
+
+        Self::from_records(nodes.iter().flat_map(|(_, node)| node.records()))
+    }
+
+    /// Collects the trust anchors from `records`. Records other than DS and
+    /// DNSKEY are ignored. Returns an error if there are no anchors or if
+    /// they belong to more than one zone.
+    pub fn from_records<'a>(
+        records: impl IntoIterator<Item = &'a Record>,
+    ) -> Result<Self, ResolverError> {
+        let mut zone: Option<Name> = None;
+        let mut ds = Vec::new();
+        let mut keys = Vec::new();
+
+        for record in records {
+            match record.rdata() {
+                RData::DS(anchor) => ds.push(anchor.clone()),
+                RData::DNSKEY(anchor) => keys.push(anchor.clone()),
+                _ => continue,
+            }
+
+            let name = record.header().name().to_lowercase();
+
+            match &zone {
+                Some(zone) if *zone != name => {
+                    return Err(ResolverError::InvalidTrustAnchors(format!(
+                        "anchors for both {zone} and {name}"
+                    )))
+                }
+                Some(_) => {}
+                None => zone = Some(name),
+            }
+        }
+
+        match zone {
+            Some(zone) => Ok(Self { zone, ds, keys }),
+            None => Err(ResolverError::InvalidTrustAnchors(
+                "no DS or DNSKEY records".into(),
+            )),
+        }
+    }
+
+    /// Returns the zone of the trust anchors.
+    pub fn zone(&self) -> &Name {
+        &self.zone
+    }
+
+    pub fn ds(&self) -> &[DS] {
+        &self.ds
+    }
+
+    pub fn keys(&self) -> &[DNSKEY] {
+        &self.keys
+    }
+}
@@ -0,0 +1,157 @@
+use portal_proto::{Name, DNSKEY, DS};
+use ring::{
+    digest,
+    signature::{self, RsaPublicKeyComponents, UnparsedPublicKey},
+};
+
+/// DNSSEC algorithm numbers, see
+/// [RFC 8624 Section 3.1](https://datatracker.ietf.org/doc/html/rfc8624#section-3.1).
+pub(crate) const RSASHA256: u8 = 8;
+pub(crate) const ECDSAP256SHA256: u8 = 13;
+pub(crate) const ECDSAP384SHA384: u8 = 14;
+pub(crate) const ED25519: u8 = 15;
+
+/// DS digest types, see
+/// [RFC 8624 Section 3.3](https://datatracker.ietf.org/doc/html/rfc8624#section-3.3).
+pub(crate) const DIGEST_SHA1: u8 = 1;
+pub(crate) const DIGEST_SHA256: u8 = 2;
+pub(crate) const DIGEST_SHA384: u8 = 4;
+
+/// The only NSEC3 hash algorithm, see
+/// [RFC 5155 Section 11](https://datatracker.ietf.org/doc/html/rfc5155#section-11).
+pub(crate) const NSEC3_SHA1: u8 = 1;
+
+pub(crate) fn is_supported_algorithm(algorithm: u8) -> bool {
+    matches!(
+        algorithm,
+        RSASHA256 | ECDSAP256SHA256 | ECDSAP384SHA384 | ED25519
+    )
+}
+
+pub(crate) fn is_supported_digest(digest_type: u8) -> bool {
+    matches!(digest_type, DIGEST_SHA1 | DIGEST_SHA256 | DIGEST_SHA384)
+}
+
+/// Verifies the `signature` over `data` with the public key of `key`.
+/// Unsupported algorithms and malformed keys never verify.
+pub(crate) fn verify(key: &DNSKEY, data: &[u8], signature: &[u8]) -> bool {
+    let public_key = key.public_key();
+
+    match key.algorithm() {
+        RSASHA256 => match rsa_components(public_key) {
+            Some((e, n)) => RsaPublicKeyComponents { n, e }
+                .verify(
+                    &signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY,
+                    data,
+                    signature,
+                )
+                .is_ok(),
+            None => false,
+        },
+        // ECDSA keys are stored without the leading 0x04 octet of the
+        // uncompressed point format. See RFC 6605 Section 4.
+        ECDSAP256SHA256 => verify_ecdsa(
+            &signature::ECDSA_P256_SHA256_FIXED,
+            public_key,
+            data,
+            signature,
+        ),
+        ECDSAP384SHA384 => verify_ecdsa(
+            &signature::ECDSA_P384_SHA384_FIXED,
+            public_key,
+            data,
+            signature,
+        ),
+        ED25519 => UnparsedPublicKey::new(&signature::ED25519, public_key)
+            .verify(data, signature)
+            .is_ok(),
+        _ => false,
+    }
+}
+
+fn verify_ecdsa(
+    algorithm: &'static signature::EcdsaVerificationAlgorithm,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> bool {
+    let mut point = Vec::with_capacity(public_key.len() + 1);
+    point.push(0x04);
+    point.extend_from_slice(public_key);
+
+    UnparsedPublicKey::new(algorithm, point)
+        .verify(data, signature)
+        .is_ok()
+}
+
+/// Splits an RSA public key into exponent and modulus. The exponent length
+/// is stored in one octet, or in three octets if the first one is zero. See
+/// [RFC 3110 Section 2](https://datatracker.ietf.org/doc/html/rfc3110#section-2).
+fn rsa_components(public_key: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&first, rest) = public_key.split_first()?;
+
+    let (len, rest) = match first {
+        0 if rest.len() >= 2 => (u16::from_be_bytes([rest[0], rest[1]]) as usize, &rest[2..]),
+        0 => return None,
+        len => (len as usize, rest),
+    };
+
+    if len == 0 || rest.len() <= len {
+        return None;
+    }
+
+    Some(rest.split_at(len))
+}
+
+/// Returns if `ds` refers to `key` of the zone `owner`. The digest is
+/// calculated over the owner name and the DNSKEY RDATA. See
+/// [RFC 4034 Section 5.1.4](https://datatracker.ietf.org/doc/html/rfc4034#section-5.1.4).
+pub(crate) fn matches_ds(owner: &Name, key: &DNSKEY, ds: &DS) -> bool {
+    if ds.key_tag() != key.key_tag() || ds.algorithm() != key.algorithm() {
+        return false;
+    }
+
+    let algorithm = match ds.digest_type() {
+        DIGEST_SHA1 => &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        DIGEST_SHA256 => &digest::SHA256,
+        DIGEST_SHA384 => &digest::SHA384,
+        _ => return false,
+    };
+
+    let mut ctx = digest::Context::new(algorithm);
+    ctx.update(&wire_name(owner));
+    ctx.update(&key.flags().to_be_bytes());
+    ctx.update(&[key.protocol(), key.algorithm()]);
+    ctx.update(key.public_key());
+
+    ctx.finish().as_ref() == ds.digest()
+}
+
+/// Calculates the iterated NSEC3 hash of `name`. See
+/// [RFC 5155 Section 5](https://datatracker.ietf.org/doc/html/rfc5155#section-5).
+pub(crate) fn nsec3_hash(name: &Name, salt: &[u8], iterations: u16) -> Vec<u8> {
+    let mut hash = wire_name(name);
+
+    for _ in 0..=iterations {
+        let mut ctx = digest::Context::new(&digest::SHA1_FOR_LEGACY_USE_ONLY);
+        ctx.update(&hash);
+        ctx.update(salt);
+        hash = ctx.finish().as_ref().to_vec();
+    }
+
+    hash
+}
+
+/// Returns the canonical wire format of `name`, which is uncompressed and
+/// lowercase. See [RFC 4034 Section 6.2](https://datatracker.ietf.org/doc/html/rfc4034#section-6.2).
+pub(crate) fn wire_name(name: &Name) -> Vec<u8> {
+    let mut wire = Vec::with_capacity(name.size());
+
+    for label in name.iter() {
+        wire.push(label.len() as u8);
+        wire.extend(label.0.to_ascii_lowercase());
+    }
+
+    wire.push(0);
+    wire
+}
@@ -0,0 +1,306 @@
+use std::cmp::Ordering;
+
+use portal_proto::{Name, RData, RType, Record, TypeBitMaps, NSEC, NSEC3};
+
+use crate::dnssec::crypto::{nsec3_hash, NSEC3_SHA1};
+
+/// NSEC3 records with more iterations are treated as insecure. See
+/// [RFC 9276 Section 3.2](https://datatracker.ietf.org/doc/html/rfc9276#section-3.2).
+const MAX_NSEC3_ITERATIONS: u16 = 150;
+
+/// The outcome of checking a denial of existence proof.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Proof {
+    /// The NSEC or NSEC3 records prove the denial.
+    Proven,
+
+    /// The denial is covered by an opt-out NSEC3 record (or uses too many
+    /// iterations), so there might be an unsigned delegation.
+    OptOut,
+
+    /// The records don't prove the denial.
+    Missing,
+}
+
+/// The NSEC and NSEC3 records of a zone, which are used to prove that names
+/// or types don't exist. Callers have to validate the signatures of these
+/// records first.
+pub(crate) struct Denial<'a> {
+    nsec: Vec<(Name, &'a NSEC)>,
+    nsec3: Vec<(Vec<u8>, &'a NSEC3)>,
+
+    /// The parameters used to hash names, taken from the first NSEC3 record
+    params: Option<(&'a [u8], u16)>,
+}
+
+impl<'a> Denial<'a> {
+    /// Collects the NSEC and NSEC3 records of `zone` in `records`.
+    pub fn new(records: &'a [Record], zone: &Name) -> Self {
+        let mut denial = Self {
+            nsec: Vec::new(),
+            nsec3: Vec::new(),
+            params: None,
+        };
+
+        for record in records {
+            let owner = record.header().name();
+            if !owner.is_subdomain_of(zone) {
+                continue;
+            }
+
+            match record.rdata() {
+                RData::NSEC(nsec) => denial.nsec.push((owner.to_lowercase(), nsec)),
+                RData::NSEC3(nsec3) if nsec3.hash_algorithm() == NSEC3_SHA1 => {
+                    // NSEC3 owner names are the hash directly below the zone
+                    if owner.num_labels() != zone.num_labels() + 1 {
+                        continue;
+                    }
+
+                    let params = (nsec3.salt(), nsec3.iterations());
+                    if *denial.params.get_or_insert(params) != params {
+                        continue;
+                    }
+
+                    if let Some(hash) = NSEC3::owner_hash(owner) {
+                        denial.nsec3.push((hash, nsec3))
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        denial
+    }
+
+    /// Returns the types of the NSEC or NSEC3 record matching `name`.
+    pub fn types(&self, name: &Name) -> Option<&'a TypeBitMaps> {
+        self.nsec_matching(name)
+            .or_else(|| self.nsec3_matching(name).map(|nsec3| nsec3.types()))
+    }
+
+    /// Proves that `name` doesn't exist and that there is no wildcard which
+    /// could have been expanded. See
+    /// [RFC 4035 Section 5.4](https://datatracker.ietf.org/doc/html/rfc4035#section-5.4)
+    /// and [RFC 5155 Section 8.4](https://datatracker.ietf.org/doc/html/rfc5155#section-8.4).
+    pub fn prove_nxdomain(&self, name: &Name) -> Proof {
+        if let Some((owner, nsec)) = self.nsec_covering(name) {
+            let encloser = closest_encloser(name, owner, nsec.next());
+
+            if self.nsec_covering(&encloser.to_wildcard()).is_some() {
+                return Proof::Proven;
+            }
+        }
+
+        if self.too_many_iterations() {
+            return Proof::OptOut;
+        }
+
+        match self.nsec3_closest_encloser(name) {
+            Some((encloser, opt_out)) => match self.nsec3_covering(&encloser.to_wildcard()) {
+                Some(_) if opt_out => Proof::OptOut,
+                Some(_) => Proof::Proven,
+                None => Proof::Missing,
+            },
+            None => Proof::Missing,
+        }
+    }
+
+    /// Proves that `name` exists, but has no records of type `ty`. This
+    /// includes empty non-terminals and wildcard NODATA responses. See
+    /// [RFC 4035 Section 5.4](https://datatracker.ietf.org/doc/html/rfc4035#section-5.4)
+    /// and [RFC 5155 Section 8.5 - 8.7](https://datatracker.ietf.org/doc/html/rfc5155#section-8.5).
+    pub fn prove_nodata(&self, name: &Name, ty: RType) -> Proof {
+        if let Some(types) = self.nsec_matching(name) {
+            return match denies_type(types, ty) {
+                true => Proof::Proven,
+                false => Proof::Missing,
+            };
+        }
+
+        if let Some((owner, nsec)) = self.nsec_covering(name) {
+            // The name is an empty non-terminal
+            if nsec.next().is_subdomain_of(name) {
+                return Proof::Proven;
+            }
+
+            let wildcard = closest_encloser(name, owner, nsec.next()).to_wildcard();
+            if let Some(types) = self.nsec_matching(&wildcard) {
+                if denies_type(types, ty) {
+                    return Proof::Proven;
+                }
+            }
+        }
+
+        if self.too_many_iterations() {
+            return Proof::OptOut;
+        }
+
+        if let Some(nsec3) = self.nsec3_matching(name) {
+            return match denies_type(nsec3.types(), ty) {
+                true => Proof::Proven,
+                false => Proof::Missing,
+            };
+        }
+
+        let (encloser, opt_out) = match self.nsec3_closest_encloser(name) {
+            Some(proof) => proof,
+            None => return Proof::Missing,
+        };
+
+        // There is no DS record at an unsigned delegation covered by an
+        // opt-out NSEC3 record. See RFC 5155 Section 8.6.
+        if ty == RType::DS && opt_out {
+            return Proof::OptOut;
+        }
+
+        match self.nsec3_matching(&encloser.to_wildcard()) {
+            Some(nsec3) if denies_type(nsec3.types(), ty) => Proof::Proven,
+            _ => Proof::Missing,
+        }
+    }
+
+    /// Proves that `name` doesn't exist, which is required for answers
+    /// synthesized from the wildcard below the ancestor of `name` with
+    /// `num_labels` labels. See
+    /// [RFC 4035 Section 5.3.4](https://datatracker.ietf.org/doc/html/rfc4035#section-5.3.4)
+    /// and [RFC 5155 Section 8.8](https://datatracker.ietf.org/doc/html/rfc5155#section-8.8).
+    pub fn prove_expansion(&self, name: &Name, num_labels: usize) -> Proof {
+        if self.nsec_covering(name).is_some() {
+            return Proof::Proven;
+        }
+
+        if self.too_many_iterations() {
+            return Proof::OptOut;
+        }
+
+        let next_closer = name.ancestor(num_labels + 1);
+
+        match self.nsec3_covering(&next_closer) {
+            Some(nsec3) if nsec3.is_opt_out() => Proof::OptOut,
+            Some(_) => Proof::Proven,
+            None => Proof::Missing,
+        }
+    }
+
+    fn nsec_matching(&self, name: &Name) -> Option<&'a TypeBitMaps> {
+        let name = name.to_lowercase();
+
+        self.nsec
+            .iter()
+            .find(|(owner, _)| *owner == name)
+            .map(|&(_, nsec)| nsec.types())
+    }
+
+    /// Returns the NSEC record whose owner name is before and whose next
+    /// name is after `name` in canonical order. The last NSEC record of a
+    /// zone points back to the apex.
+    fn nsec_covering(&self, name: &Name) -> Option<(&Name, &'a NSEC)> {
+        self.nsec
+            .iter()
+            .find(|(owner, nsec)| {
+                covers(
+                    owner.cmp_canonical(name),
+                    name.cmp_canonical(nsec.next()),
+                    owner.cmp_canonical(nsec.next()),
+                )
+            })
+            .map(|(owner, nsec)| (owner, *nsec))
+    }
+
+    fn nsec3_matching(&self, name: &Name) -> Option<&'a NSEC3> {
+        let hash = self.hash(name)?;
+
+        self.nsec3
+            .iter()
+            .find(|(owner, _)| *owner == hash)
+            .map(|&(_, nsec3)| nsec3)
+    }
+
+    fn nsec3_covering(&self, name: &Name) -> Option<&'a NSEC3> {
+        let hash = self.hash(name)?;
+
+        self.nsec3
+            .iter()
+            .find(|(owner, nsec3)| {
+                let next = nsec3.next_hashed();
+                covers(
+                    owner.as_slice().cmp(&hash),
+                    hash.as_slice().cmp(next),
+                    owner.as_slice().cmp(next),
+                )
+            })
+            .map(|&(_, nsec3)| nsec3)
+    }
+
+    /// Returns the closest encloser of `name` and if the next closer name
+    /// is covered by an opt-out NSEC3 record. The closest encloser has a
+    /// matching NSEC3 record and the next closer name is covered by another
+    /// NSEC3 record. See
+    /// [RFC 5155 Section 8.3](https://datatracker.ietf.org/doc/html/rfc5155#section-8.3).
+    fn nsec3_closest_encloser(&self, name: &Name) -> Option<(Name, bool)> {
+        for num_labels in (0..name.num_labels()).rev() {
+            let encloser = name.ancestor(num_labels);
+
+            if self.nsec3_matching(&encloser).is_none() {
+                continue;
+            }
+
+            let next_closer = name.ancestor(num_labels + 1);
+            return self
+                .nsec3_covering(&next_closer)
+                .map(|nsec3| (encloser, nsec3.is_opt_out()));
+        }
+
+        None
+    }
+
+    /// Returns the NSEC3 hash of `name`. Names are never hashed with more
+    /// than [`MAX_NSEC3_ITERATIONS`] iterations, as that is expensive.
+    fn hash(&self, name: &Name) -> Option<Vec<u8>> {
+        match self.params? {
+            (_, iterations) if iterations > MAX_NSEC3_ITERATIONS => None,
+            (salt, iterations) => Some(nsec3_hash(name, salt, iterations)),
+        }
+    }
+
+    fn too_many_iterations(&self) -> bool {
+        self.params
+            .is_some_and(|(_, iterations)| iterations > MAX_NSEC3_ITERATIONS)
+    }
+}
+
+/// Returns if a name is between owner and next. `owner_name`, `name_next`
+/// and `owner_next` are the comparisons of the respective values. If next is
+/// not after owner, this is the last record of the zone which wraps around.
+fn covers(owner_name: Ordering, name_next: Ordering, owner_next: Ordering) -> bool {
+    match owner_next {
+        Ordering::Less => owner_name == Ordering::Less && name_next == Ordering::Less,
+        _ => owner_name == Ordering::Less || name_next == Ordering::Less,
+    }
+}
+
+/// Returns if the type bit maps of a matching NSEC or NSEC3 record prove that
+/// there are no records of type `ty`. The NSEC record of a delegation point
+/// in the parent zone can only prove the absence of DS records.
+fn denies_type(types: &TypeBitMaps, ty: RType) -> bool {
+    if types.contains(ty) || types.contains(RType::CNAME) {
+        return false;
+    }
+
+    ty == RType::DS || !types.contains(RType::NS) || types.contains(RType::SOA)
+}
+
+/// Returns the closest encloser of `name` based on the owner and next name
+/// of the NSEC record covering it. This is the longest common ancestor.
+fn closest_encloser(name: &Name, owner: &Name, next: &Name) -> Name {
+    let common = |other: &Name| {
+        name.labels()
+            .iter()
+            .rev()
+            .zip(other.labels().iter().rev())
+            .take_while(|(a, b)| a.0.eq_ignore_ascii_case(&b.0))
+            .count()
+    };
+
+    name.ancestor(common(owner).max(common(next)))
+}
@@ -0,0 +1,90 @@
+use portal_proto::{edns::EDE, Name, RType};
+use thiserror::Error;
+
+mod anchor;
+mod crypto;
+mod denial;
+mod rrset;
+mod validator;
+
+pub use anchor::*;
+pub use validator::*;
+
+/// The security status of resolved records. See
+/// [RFC 4035 Section 4.3](https://datatracker.ietf.org/doc/html/rfc4035#section-4.3).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Security {
+    /// The records are validated via an unbroken chain of trust starting at
+    /// a trust anchor.
+    Secure,
+
+    /// The records are provably unsigned, e.g. because they are below an
+    /// unsigned delegation.
+    Insecure,
+
+    /// Validation failed. These records must not be returned to clients.
+    Bogus(Bogus),
+
+    /// The records were not validated.
+    #[default]
+    Indeterminate,
+}
+
+impl Security {
+    pub fn is_secure(&self) -> bool {
+        matches!(self, Self::Secure)
+    }
+
+    pub fn is_bogus(&self) -> bool {
+        matches!(self, Self::Bogus(_))
+    }
+
+    /// Combines the status of two parts of a response. The response is only
+    /// secure if all parts are secure, a single bogus part makes the whole
+    /// response bogus.
+    pub(crate) fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Bogus(bogus), _) | (_, Self::Bogus(bogus)) => Self::Bogus(bogus),
+            (Self::Indeterminate, _) | (_, Self::Indeterminate) => Self::Indeterminate,
+            (Self::Insecure, _) | (_, Self::Insecure) => Self::Insecure,
+            (Self::Secure, Self::Secure) => Self::Secure,
+        }
+    }
+}
+
+/// The reason why validation resulted in [`Security::Bogus`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Bogus {
+    #[error("signature of {0} {1} expired")]
+    SignatureExpired(Name, RType),
+
+    #[error("signature of {0} {1} not yet valid")]
+    SignatureNotYetValid(Name, RType),
+
+    #[error("invalid signature of {0} {1}")]
+    InvalidSignature(Name, RType),
+
+    #[error("RRSIGs of {0} {1} missing")]
+    RrsigsMissing(Name, RType),
+
+    #[error("no DNSKEY of {0} matches the trusted DS records")]
+    DnskeyMissing(Name),
+
+    #[error("NSEC or NSEC3 proof for {0} missing")]
+    NsecMissing(Name),
+}
+
+impl Bogus {
+    /// Returns the Extended DNS Error info code which describes this failure.
+    /// See [RFC 8914 Section 4](https://datatracker.ietf.org/doc/html/rfc8914#section-4).
+    pub fn ede_code(&self) -> u16 {
+        match self {
+            Self::SignatureExpired(..) => EDE::SIGNATURE_EXPIRED,
+            Self::SignatureNotYetValid(..) => EDE::SIGNATURE_NOT_YET_VALID,
+            Self::InvalidSignature(..) => EDE::DNSSEC_BOGUS,
+            Self::RrsigsMissing(..) => EDE::RRSIGS_MISSING,
+            Self::DnskeyMissing(_) => EDE::DNSKEY_MISSING,
+            Self::NsecMissing(_) => EDE::NSEC_MISSING,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use binbuf::prelude::*;
+use portal_proto::{Name, RData, RType, Record, RRSIG};
+
+use crate::dnssec::crypto::wire_name;
+
+/// An RRset groups all records with the same owner name, class and type
+/// together with the RRSIGs covering them. RRSIGs are always validated
+/// per RRset. See [RFC 4034 Section 3](https://datatracker.ietf.org/doc/html/rfc4034#section-3).
+#[derive(Debug)]
+pub(crate) struct RRset<'a> {
+    /// The lowercase owner name
+    pub name: Name,
+    pub ty: RType,
+    pub records: Vec<&'a Record>,
+    pub rrsigs: Vec<&'a RRSIG>,
+}
+
+impl<'a> RRset<'a> {
+    /// Groups `records` into RRsets. RRSIGs are attached to the RRset they
+    /// cover, RRSIGs without a matching RRset are dropped.
+    pub fn group(records: &'a [Record]) -> Vec<Self> {
+        let mut rrsets: Vec<Self> = Vec::new();
+
+        for record in records.iter().filter(|r| !r.is_edns()) {
+            let ty = *record.header().ty();
+            if ty == RType::RRSIG {
+                continue;
+            }
+
+            let name = record.header().name().to_lowercase();
+
+            match rrsets.iter_mut().find(|s| s.name == name && s.ty == ty) {
+                Some(rrset) => rrset.records.push(record),
+                None => rrsets.push(Self {
+                    name,
+                    ty,
+                    records: vec![record],
+                    rrsigs: Vec::new(),
+                }),
+            }
+        }
+
+        for record in records {
+            let rrsig = match record.rdata() {
+                RData::RRSIG(rrsig) => rrsig,
+                _ => continue,
+            };
+
+            let name = record.header().name().to_lowercase();
+
+            if let Some(rrset) = rrsets
+                .iter_mut()
+                .find(|s| s.name == name && s.ty == rrsig.type_covered())
+            {
+                rrset.rrsigs.push(rrsig)
+            }
+        }
+
+        rrsets
+    }
+
+    /// Returns the number of labels of the owner name as counted in the
+    /// labels field of RRSIGs. A leading wildcard label is not counted.
+    pub fn num_labels(&self) -> usize {
+        match self.name.is_wildcard() {
+            true => self.name.num_labels() - 1,
+            false => self.name.num_labels(),
+        }
+    }
+
+    /// Returns the number of labels of the wildcard owner name if this RRset
+    /// was synthesized from a wildcard according to `rrsig`.
+    pub fn wildcard_labels(&self, rrsig: &RRSIG) -> Option<usize> {
+        let labels = rrsig.labels() as usize;
+
+        match labels < self.num_labels() {
+            true => Some(labels),
+            false => None,
+        }
+    }
+
+    /// Returns the data covered by `rrsig`: the RRSIG RDATA without the
+    /// signature followed by all records in canonical form and order. The
+    /// TTL of every record is replaced by the original TTL. See
+    /// [RFC 4034 Section 3.1.8.1](https://datatracker.ietf.org/doc/html/rfc4034#section-3.1.8.1).
+    pub fn signed_data(&self, rrsig: &RRSIG) -> Option<Vec<u8>> {
+        let mut buf = WriteBuffer::new();
+        rrsig
+            .to_lowercase()
+            .write_signed_fields::<BigEndian>(&mut buf)
+            .ok()?;
+
+        // Wildcard expansions are signed with the wildcard owner name. See
+        // RFC 4035 Section 5.3.2.
+        let owner = match self.wildcard_labels(rrsig) {
+            Some(labels) => self.name.ancestor(labels).to_wildcard(),
+            None => self.name.clone(),
+        };
+        let owner = wire_name(&owner);
+
+        let class = u16::from(self.records.first()?.header().class());
+
+        let mut rdatas = self
+            .records
+            .iter()
+            .map(|r| r.rdata().canonical_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+
+        // Duplicate RRs are removed, see RFC 4034 Section 6.3
+        rdatas.sort();
+        rdatas.dedup();
+
+        let mut data = buf.owned_bytes();
+
+        for rdata in rdatas {
+            data.extend_from_slice(&owner);
+            data.extend_from_slice(&u16::from(self.ty).to_be_bytes());
+            data.extend_from_slice(&class.to_be_bytes());
+            data.extend_from_slice(&rrsig.original_ttl().to_be_bytes());
+            data.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            data.extend_from_slice(&rdata);
+        }
+
+        Some(data)
+    }
+
+    /// Returns the lowest TTL of the RRset.
+    pub fn ttl(&self) -> u32 {
+        self.records
+            .iter()
+            .map(|r| r.header().ttl())
+            .min()
+            .unwrap_or_default()
+    }
+}
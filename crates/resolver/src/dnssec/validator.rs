@@ -0,0 +1,541 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use portal_proto::{Class, Name, Query, RData, RType, Rcode, DNSKEY, DS};
+use thiserror::Error;
+
+use crate::{
+    dnssec::{
+        crypto,
+        denial::{Denial, Proof},
+        rrset::RRset,
+        Bogus, Security, TrustAnchors,
+    },
+    ResolveResult, ResolverError, ResultRecords,
+};
+
+/// Links of the chain of trust are cached for at most one hour, even if the
+/// TTLs of the DS and DNSKEY records are longer.
+const MAX_LINK_TTL: u32 = 3600;
+
+/// A [`Lookup`] resolves queries without validating the responses. The
+/// responses have to include RRSIG, NSEC and NSEC3 records, which means
+/// queries have to be sent with the DO bit set.
+#[async_trait]
+pub trait Lookup: Sync {
+    async fn lookup(&self, query: Query) -> ResolveResult;
+}
+
+#[derive(Debug, Error)]
+enum ValidationError {
+    #[error("bogus: {0}")]
+    Bogus(#[from] Bogus),
+
+    #[error("resolver error: {0}")]
+    Resolver(#[from] ResolverError),
+}
+
+type ValidationResult<T> = Result<T, ValidationError>;
+
+/// A link in the chain of trust, which describes if there is a zone cut at a
+/// name.
+#[derive(Debug, Clone)]
+enum Link {
+    /// The name is the apex of a signed zone with these validated keys.
+    Secure(Vec<DNSKEY>),
+
+    /// The name is the apex of an unsigned zone.
+    Insecure,
+
+    /// The name is no zone cut.
+    NoCut,
+}
+
+/// The status of the closest zone enclosing a name.
+enum Trust {
+    /// The closest enclosing zone and its validated keys.
+    Secure(Name, Vec<DNSKEY>),
+
+    /// The name is below an unsigned delegation or outside of the trust
+    /// anchor zone.
+    Insecure,
+}
+
+/// The [`Validator`] validates resolved records via DNSSEC. It follows the
+/// chain of trust from the [`TrustAnchors`] down to the zone of the records
+/// and caches every link. See
+/// [RFC 4035 Section 5](https://datatracker.ietf.org/doc/html/rfc4035#section-5).
+pub struct Validator {
+    anchors: TrustAnchors,
+    links: Mutex<HashMap<Name, (Link, Instant)>>,
+}
+
+impl Validator {
+    pub fn new(anchors: TrustAnchors) -> Self {
+        Self {
+            anchors,
+            links: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Validates the `records` resolved for `query`. The DS and DNSKEY
+    /// records which are required to build the chain of trust are looked up
+    /// via `lookup`.
+    pub async fn validate<L: Lookup>(
+        &self,
+        lookup: &L,
+        query: &Query,
+        records: &ResultRecords,
+    ) -> Result<Security, ResolverError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as u32)
+            .unwrap_or_default();
+
+        self.validate_at(lookup, query, records, now).await
+    }
+
+    /// Validates the `records` like [`Validator::validate`], but checks the
+    /// validity period of signatures at `now` (seconds since 1 January 1970)
+    /// instead of the current time.
+    pub async fn validate_at<L: Lookup>(
+        &self,
+        lookup: &L,
+        query: &Query,
+        records: &ResultRecords,
+        now: u32,
+    ) -> Result<Security, ResolverError> {
+        match self.validate_records(lookup, query, records, now).await {
+            Ok(security) => Ok(security),
+            Err(ValidationError::Bogus(bogus)) => Ok(Security::Bogus(bogus)),
+            Err(ValidationError::Resolver(err)) => Err(err),
+        }
+    }
+
+    async fn validate_records<L: Lookup>(
+        &self,
+        lookup: &L,
+        query: &Query,
+        records: &ResultRecords,
+        now: u32,
+    ) -> ValidationResult<Security> {
+        let answers = RRset::group(&records.answers);
+        let authorities = RRset::group(&records.authorities);
+
+        let mut security = Security::Secure;
+
+        for rrset in answers.iter().chain(authorities.iter()) {
+            // CNAMEs synthesized from a DNAME are unsigned, the DNAME itself
+            // is validated instead. See RFC 6672 Section 5.3.1.
+            if is_synthesized(rrset, &answers) {
+                continue;
+            }
+
+            security = security.and(self.validate_rrset(lookup, rrset, now).await?);
+        }
+
+        // Answers synthesized from a wildcard additionally require a proof
+        // that the owner name itself doesn't exist.
+        for rrset in &answers {
+            if !security.is_secure() {
+                break;
+            }
+
+            let (rrsig, num_labels) = match rrset
+                .rrsigs
+                .iter()
+                .find_map(|rrsig| Some((rrsig, rrset.wildcard_labels(rrsig)?)))
+            {
+                Some(expansion) => expansion,
+                None => continue,
+            };
+
+            let denial = Denial::new(&records.authorities, rrsig.signer());
+            let proof = denial.prove_expansion(&rrset.name, num_labels);
+            security = security.and(proof_security(proof, &rrset.name)?);
+        }
+
+        // Negative answers (NXDOMAIN or NODATA) have to be proven by NSEC or
+        // NSEC3 records, unless the zone is unsigned.
+        let negative = match records.rcode {
+            Rcode::NameError => true,
+            Rcode::NoError => records.answers.is_empty(),
+            _ => false,
+        };
+
+        if !negative {
+            return Ok(security);
+        }
+
+        let name = follow_cnames(&query.name, &answers);
+
+        // DS records are part of the parent zone
+        let zone_name = match query.ty {
+            RType::DS => name.parent().unwrap_or_default(),
+            _ => name.clone(),
+        };
+
+        let zone = match self.trust(lookup, &zone_name, now).await? {
+            Trust::Secure(zone, _) => zone,
+            Trust::Insecure => return Ok(security.and(Security::Insecure)),
+        };
+
+        let denial = Denial::new(&records.authorities, &zone);
+        let proof = match records.rcode {
+            Rcode::NameError => denial.prove_nxdomain(&name),
+            _ => denial.prove_nodata(&name, query.ty),
+        };
+
+        Ok(security.and(proof_security(proof, &name)?))
+    }
+
+    /// Validates a single RRset. Unsigned RRsets are only insecure if they
+    /// are part of an unsigned zone.
+    async fn validate_rrset<L: Lookup>(
+        &self,
+        lookup: &L,
+        rrset: &RRset<'_>,
+        now: u32,
+    ) -> ValidationResult<Security> {
+        let signer = match rrset.rrsigs.first() {
+            Some(rrsig) => rrsig.signer().to_lowercase(),
+            None => {
+                let name = match rrset.ty {
+                    RType::DS => rrset.name.parent().unwrap_or_default(),
+                    _ => rrset.name.clone(),
+                };
+
+                return match self.trust(lookup, &name, now).await? {
+                    Trust::Secure(..) => {
+                        Err(Bogus::RrsigsMissing(rrset.name.clone(), rrset.ty).into())
+                    }
+                    Trust::Insecure => Ok(Security::Insecure),
+                };
+            }
+        };
+
+        if !rrset.name.is_subdomain_of(&signer) {
+            return Err(Bogus::InvalidSignature(rrset.name.clone(), rrset.ty).into());
+        }
+
+        match self.trust(lookup, &signer, now).await? {
+            Trust::Secure(zone, keys) if zone == signer => {
+                verify_rrset(rrset, &zone, &keys, now)?;
+                Ok(Security::Secure)
+            }
+            Trust::Secure(..) => Err(Bogus::InvalidSignature(rrset.name.clone(), rrset.ty).into()),
+            Trust::Insecure => Ok(Security::Insecure),
+        }
+    }
+
+    /// Follows the chain of trust from the trust anchors down to the closest
+    /// zone enclosing `name`. Every ancestor of `name` is checked for a zone
+    /// cut by looking up its DS records.
+    async fn trust<L: Lookup>(&self, lookup: &L, name: &Name, now: u32) -> ValidationResult<Trust> {
+        let anchor = self.anchors.zone();
+
+        if !name.is_subdomain_of(anchor) {
+            return Ok(Trust::Insecure);
+        }
+
+        let mut zone = anchor.clone();
+        let mut keys = match self.cached(&zone) {
+            Some(Link::Secure(keys)) => keys,
+            _ => {
+                let ds: Vec<&DS> = self.anchors.ds().iter().collect();
+                let (keys, ttl) = self
+                    .fetch_keys(lookup, &zone, &ds, self.anchors.keys(), now)
+                    .await?;
+
+                self.cache(&zone, Link::Secure(keys.clone()), ttl);
+                keys
+            }
+        };
+
+        for num_labels in anchor.num_labels() + 1..=name.num_labels() {
+            let child = name.ancestor(num_labels).to_lowercase();
+
+            let link = match self.cached(&child) {
+                Some(link) => link,
+                None => {
+                    let (link, ttl) = self.find_link(lookup, &zone, &keys, &child, now).await?;
+                    self.cache(&child, link.clone(), ttl);
+                    link
+                }
+            };
+
+            match link {
+                Link::Secure(child_keys) => {
+                    zone = child;
+                    keys = child_keys;
+                }
+                Link::Insecure => return Ok(Trust::Insecure),
+                Link::NoCut => {}
+            }
+        }
+
+        Ok(Trust::Secure(zone, keys))
+    }
+
+    /// Checks if there is a zone cut at `child`, which is below the secure
+    /// `zone`. Signed delegations have DS records, the absence of DS records
+    /// has to be proven by NSEC or NSEC3 records. See
+    /// [RFC 4035 Section 5.2](https://datatracker.ietf.org/doc/html/rfc4035#section-5.2).
+    async fn find_link<L: Lookup>(
+        &self,
+        lookup: &L,
+        zone: &Name,
+        keys: &[DNSKEY],
+        child: &Name,
+        now: u32,
+    ) -> ValidationResult<(Link, u32)> {
+        let response = lookup
+            .lookup(Query::new(child.clone(), RType::DS, Class::IN))
+            .await?;
+
+        let answers = RRset::group(&response.answers);
+
+        if let Some(rrset) = answers
+            .iter()
+            .find(|s| s.name == *child && s.ty == RType::DS)
+        {
+            verify_rrset(rrset, zone, keys, now)?;
+
+            let ds: Vec<&DS> = rrset
+                .records
+                .iter()
+                .filter_map(|r| match r.rdata() {
+                    RData::DS(ds) => Some(ds),
+                    _ => None,
+                })
+                .filter(|ds| {
+                    crypto::is_supported_algorithm(ds.algorithm())
+                        && crypto::is_supported_digest(ds.digest_type())
+                })
+                .collect();
+
+            // Zones which are only signed with unsupported algorithms are
+            // treated as unsigned.
+            if ds.is_empty() {
+                return Ok((Link::Insecure, rrset.ttl()));
+            }
+
+            let (keys, ttl) = self.fetch_keys(lookup, child, &ds, &[], now).await?;
+            return Ok((Link::Secure(keys), ttl.min(rrset.ttl())));
+        }
+
+        // Any other answer, e.g. a CNAME, means that there is no zone cut.
+        if !response.answers.is_empty() {
+            return Ok((Link::NoCut, MAX_LINK_TTL));
+        }
+
+        if !matches!(response.rcode, Rcode::NoError | Rcode::NameError) {
+            return Err(ResolverError::NoAnswer.into());
+        }
+
+        let authorities = RRset::group(&response.authorities);
+        let mut ttl = MAX_LINK_TTL;
+
+        for rrset in authorities
+            .iter()
+            .filter(|s| matches!(s.ty, RType::NSEC | RType::NSEC3))
+        {
+            verify_rrset(rrset, zone, keys, now)?;
+            ttl = ttl.min(rrset.ttl());
+        }
+
+        let denial = Denial::new(&response.authorities, zone);
+        let proof = match response.rcode {
+            Rcode::NameError => denial.prove_nxdomain(child),
+            _ => denial.prove_nodata(child, RType::DS),
+        };
+
+        let link = match proof {
+            Proof::Missing => return Err(Bogus::NsecMissing(child.clone()).into()),
+            Proof::OptOut => Link::Insecure,
+            Proof::Proven => match denial.types(child) {
+                Some(types) if types.contains(RType::NS) && !types.contains(RType::SOA) => {
+                    Link::Insecure
+                }
+                _ => Link::NoCut,
+            },
+        };
+
+        Ok((link, ttl))
+    }
+
+    /// Looks up and validates the DNSKEY RRset of `zone`. The RRset has to be
+    /// signed by a key which matches one of the `ds` records or which is one
+    /// of the `trusted` keys.
+    async fn fetch_keys<L: Lookup>(
+        &self,
+        lookup: &L,
+        zone: &Name,
+        ds: &[&DS],
+        trusted: &[DNSKEY],
+        now: u32,
+    ) -> ValidationResult<(Vec<DNSKEY>, u32)> {
+        let response = lookup
+            .lookup(Query::new(zone.clone(), RType::DNSKEY, Class::IN))
+            .await?;
+
+        let answers = RRset::group(&response.answers);
+        let rrset = answers
+            .iter()
+            .find(|s| s.name == *zone && s.ty == RType::DNSKEY)
+            .ok_or_else(|| Bogus::DnskeyMissing(zone.clone()))?;
+
+        let keys: Vec<DNSKEY> = rrset
+            .records
+            .iter()
+            .filter_map(|r| match r.rdata() {
+                RData::DNSKEY(key) => Some(key.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let entry_keys: Vec<DNSKEY> = keys
+            .iter()
+            .filter(|key| {
+                trusted.contains(key) || ds.iter().any(|ds| crypto::matches_ds(zone, key, ds))
+            })
+            .cloned()
+            .collect();
+
+        if entry_keys.is_empty() {
+            return Err(Bogus::DnskeyMissing(zone.clone()).into());
+        }
+
+        verify_rrset(rrset, zone, &entry_keys, now)?;
+        Ok((keys, rrset.ttl()))
+    }
+
+    fn cached(&self, name: &Name) -> Option<Link> {
+        // TODO (Techassi): Handle the unwrapping
+        let links = self.links.lock().unwrap();
+
+        match links.get(name) {
+            Some((link, expires)) if *expires > Instant::now() => Some(link.clone()),
+            _ => None,
+        }
+    }
+
+    fn cache(&self, name: &Name, link: Link, ttl: u32) {
+        let expires = Instant::now() + Duration::from_secs(ttl.min(MAX_LINK_TTL) as u64);
+        self.links
+            .lock()
+            .unwrap()
+            .insert(name.clone(), (link, expires));
+    }
+}
+
+/// Verifies that at least one RRSIG of `rrset` is a valid signature by one of
+/// the `keys` of `zone` at the time `now`. See
+/// [RFC 4035 Section 5.3](https://datatracker.ietf.org/doc/html/rfc4035#section-5.3).
+fn verify_rrset(rrset: &RRset<'_>, zone: &Name, keys: &[DNSKEY], now: u32) -> Result<(), Bogus> {
+    let mut error = Bogus::RrsigsMissing(rrset.name.clone(), rrset.ty);
+
+    for rrsig in &rrset.rrsigs {
+        if rrsig.signer().to_lowercase() != *zone || rrsig.labels() as usize > rrset.num_labels() {
+            error = Bogus::InvalidSignature(rrset.name.clone(), rrset.ty);
+            continue;
+        }
+
+        // The validity period uses serial number arithmetic, see RFC 4034
+        // Section 3.1.5.
+        if (now.wrapping_sub(rrsig.inception()) as i32) < 0 {
+            error = Bogus::SignatureNotYetValid(rrset.name.clone(), rrset.ty);
+            continue;
+        }
+
+        if (rrsig.expiration().wrapping_sub(now) as i32) < 0 {
+            error = Bogus::SignatureExpired(rrset.name.clone(), rrset.ty);
+            continue;
+        }
+
+        let data = match rrset.signed_data(rrsig) {
+            Some(data) => data,
+            None => continue,
+        };
+
+        let valid = keys
+            .iter()
+            .filter(|key| {
+                key.key_tag() == rrsig.key_tag()
+                    && key.algorithm() == rrsig.algorithm()
+                    && key.protocol() == 3
+                    && key.is_zone_key()
+                    && !key.is_revoked()
+            })
+            .any(|key| crypto::verify(key, &data, rrsig.signature()));
+
+        if valid {
+            return Ok(());
+        }
+
+        error = Bogus::InvalidSignature(rrset.name.clone(), rrset.ty);
+    }
+
+    Err(error)
+}
+
+fn proof_security(proof: Proof, name: &Name) -> Result<Security, Bogus> {
+    match proof {
+        Proof::Proven => Ok(Security::Secure),
+        Proof::OptOut => Ok(Security::Insecure),
+        Proof::Missing => Err(Bogus::NsecMissing(name.clone())),
+    }
+}
+
+/// Returns if `rrset` is an unsigned CNAME which was synthesized from one of
+/// the DNAME RRsets in `answers`.
+fn is_synthesized(rrset: &RRset<'_>, answers: &[RRset<'_>]) -> bool {
+    if rrset.ty != RType::CNAME || !rrset.rrsigs.is_empty() {
+        return false;
+    }
+
+    let target = match rrset.records.first().map(|r| r.rdata()) {
+        Some(RData::CNAME(target)) => target.to_lowercase(),
+        _ => return false,
+    };
+
+    answers
+        .iter()
+        .filter(|s| s.ty == RType::DNAME)
+        .flat_map(|s| s.records.iter().map(move |r| (&s.name, r.rdata())))
+        .any(|(owner, rdata)| match rdata {
+            RData::DNAME(dname) => rrset
+                .name
+                .replace_suffix(owner, dname)
+                .is_some_and(|name| name.to_lowercase() == target),
+            _ => false,
+        })
+}
+
+/// Follows the CNAME chain in `answers` starting at `name` and returns the
+/// final target.
+fn follow_cnames(name: &Name, answers: &[RRset<'_>]) -> Name {
+    let mut name = name.to_lowercase();
+
+    // Every RRset is followed at most once, which guards against loops
+    for _ in 0..answers.len() {
+        let target = answers
+            .iter()
+            .find(|s| s.name == name && s.ty == RType::CNAME)
+            .and_then(|s| match s.records.first()?.rdata() {
+                RData::CNAME(target) => Some(target.to_lowercase()),
+                _ => None,
+            });
+
+        match target {
+            Some(target) => name = target,
+            None => break,
+        }
+    }
+
+    name
+}
@@ -1,5 +1,5 @@
 use portal_client::ClientError;
-use portal_proto::ZoneError;
+use portal_proto::{Name, ZoneError};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -24,4 +24,19 @@ pub enum ResolverError {
 
     #[error("No more DNS server target IPs left")]
     NoMoreTargets,
+
+    #[error("Referral to {0} doesn't lead closer to the query name")]
+    InvalidReferral(Name),
+
+    #[error("Too many referrals")]
+    TooManyReferrals,
+
+    #[error("CNAME or DNAME loop at {0}")]
+    AliasLoop(Name),
+
+    #[error("CNAME or DNAME chain too long")]
+    AliasChainTooLong,
+
+    #[error("Invalid trust anchors: {0}")]
+    InvalidTrustAnchors(String),
 }
@@ -24,8 +24,13 @@ impl ToResolver for ForwardingResolver {
     }
 
     async fn resolve_raw<Q: ToQuery>(&self, query: Q) -> ResolveResult {
-        match self.client.query(query, self.addr.into_sockets()).await {
-            Ok((msg, _, _)) => Ok(msg.into()),
+        // Truncated responses are retried via TCP by the client
+        match self
+            .client
+            .query_multi(query, self.addr.into_sockets())
+            .await
+        {
+            Ok((msg, _, _, _)) => Ok(msg.into()),
             Err(err) => Err(ResolverError::ClientError(err)),
         }
     }
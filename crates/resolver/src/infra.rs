@@ -0,0 +1,250 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use rand::seq::SliceRandom;
+
+/// The RTT assumed for servers which were never asked. This is the initial
+/// timeout used by Unbound, which is low enough to give unknown servers a
+/// chance before slow servers are asked again.
+const UNKNOWN_RTT: Duration = Duration::from_millis(376);
+
+/// The upper limit of the smoothed RTT. Timeouts double the smoothed RTT up
+/// to this value.
+const MAX_RTT: Duration = Duration::from_secs(120);
+
+/// Servers whose smoothed RTT is within this band of the fastest server are
+/// selected randomly. This spreads the load and makes sure the RTT of all
+/// fast servers is measured from time to time.
+const RTT_BAND: Duration = Duration::from_millis(400);
+
+/// The number of consecutive timeouts after which a server is backed off.
+const BACKOFF_THRESHOLD: u32 = 3;
+
+/// The backoff duration after reaching [`BACKOFF_THRESHOLD`]. It doubles
+/// with every further timeout, up to [`MAX_BACKOFF`].
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(900);
+
+/// Entries which were not updated for this long are forgotten.
+const ENTRY_TTL: Duration = Duration::from_secs(900);
+
+/// The maximum number of servers tracked by default.
+const DEFAULT_MAX_ENTRIES: usize = 10000;
+
+/// The infrastructure cache tracks the smoothed round-trip time (SRTT) and
+/// timeouts of every name server the resolver talks to. It is used to send
+/// queries to the fastest healthy server of a zone. Servers which stop
+/// responding are backed off exponentially. See
+/// [RFC 1035 Section 7.2](https://datatracker.ietf.org/doc/html/rfc1035#section-7.2).
+///
+/// The cache can be shared between tasks, all methods take `&self`.
+#[derive(Debug)]
+pub struct InfraCache {
+    inner: Mutex<InfraCacheInner>,
+}
+
+#[derive(Debug)]
+struct InfraCacheInner {
+    servers: HashMap<IpAddr, ServerStats>,
+    max_entries: usize,
+}
+
+/// The statistics of a single name server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerStats {
+    /// The smoothed round-trip time
+    pub srtt: Duration,
+
+    /// The number of consecutive timeouts. This is reset by every response.
+    pub timeouts: u32,
+
+    /// The server is not asked until this point in time, unless there is no
+    /// other server left.
+    pub backoff_until: Option<Instant>,
+
+    updated: Instant,
+}
+
+impl Default for InfraCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ENTRIES)
+    }
+}
+
+impl InfraCache {
+    /// Creates a new infrastructure cache which tracks at most `max_entries`
+    /// servers.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            inner: Mutex::new(InfraCacheInner {
+                servers: HashMap::new(),
+                max_entries,
+            }),
+        }
+    }
+
+    /// Returns the statistics of `server`, if known.
+    pub fn get(&self, server: &IpAddr) -> Option<ServerStats> {
+        self.inner.lock().unwrap().servers.get(server).copied()
+    }
+
+    /// Records a response of `server`, which arrived after `rtt`. The
+    /// smoothed RTT is updated with a weight of 1/8, like the TCP
+    /// retransmission timer. See
+    /// [RFC 6298 Section 2](https://datatracker.ietf.org/doc/html/rfc6298#section-2).
+    pub fn record_rtt(&self, server: IpAddr, rtt: Duration) {
+        self.record_rtt_at(server, rtt, Instant::now())
+    }
+
+    /// Same as [`InfraCache::record_rtt`], but uses `now` as the current
+    /// time.
+    pub fn record_rtt_at(&self, server: IpAddr, rtt: Duration, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let srtt = match inner.get(&server, now) {
+            // Timeouts inflate the smoothed RTT. The first response after a
+            // timeout resets it instead of slowly decaying it.
+            Some(stats) if stats.timeouts == 0 => (stats.srtt * 7 + rtt) / 8,
+            _ => rtt,
+        };
+
+        inner.insert(
+            server,
+            ServerStats {
+                srtt: srtt.min(MAX_RTT),
+                timeouts: 0,
+                backoff_until: None,
+                updated: now,
+            },
+        );
+    }
+
+    /// Records a timeout of `server`. The smoothed RTT is doubled and the
+    /// server is backed off after [`BACKOFF_THRESHOLD`] consecutive
+    /// timeouts.
+    pub fn record_timeout(&self, server: IpAddr) {
+        self.record_timeout_at(server, Instant::now())
+    }
+
+    /// Same as [`InfraCache::record_timeout`], but uses `now` as the
+    /// current time.
+    pub fn record_timeout_at(&self, server: IpAddr, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let (srtt, timeouts) = match inner.get(&server, now) {
+            Some(stats) => (stats.srtt, stats.timeouts),
+            None => (UNKNOWN_RTT, 0),
+        };
+
+        let timeouts = timeouts.saturating_add(1);
+        let backoff_until = match timeouts.checked_sub(BACKOFF_THRESHOLD) {
+            Some(exp) => {
+                let backoff = BASE_BACKOFF.saturating_mul(1 << exp.min(16));
+                Some(now + backoff.min(MAX_BACKOFF))
+            }
+            None => None,
+        };
+
+        inner.insert(
+            server,
+            ServerStats {
+                srtt: (srtt * 2).min(MAX_RTT),
+                updated: now,
+                backoff_until,
+                timeouts,
+            },
+        );
+    }
+
+    /// Selects the server out of `candidates` which is asked next. Healthy
+    /// servers are preferred, the server is picked randomly among those
+    /// within [`RTT_BAND`] of the fastest one. Backed off servers are only
+    /// returned if there is no healthy server, the one whose backoff ends
+    /// first is picked.
+    pub fn select(&self, candidates: &[IpAddr]) -> Option<IpAddr> {
+        self.select_at(candidates, Instant::now())
+    }
+
+    /// Same as [`InfraCache::select`], but uses `now` as the current time.
+    pub fn select_at(&self, candidates: &[IpAddr], now: Instant) -> Option<IpAddr> {
+        let inner = self.inner.lock().unwrap();
+
+        let (healthy, backed_off): (Vec<_>, Vec<_>) = candidates
+            .iter()
+            .map(|server| (*server, inner.get(server, now)))
+            .partition(|(_, stats)| match stats.and_then(|s| s.backoff_until) {
+                Some(backoff_until) => backoff_until <= now,
+                None => true,
+            });
+
+        let srtt = |stats: Option<ServerStats>| stats.map_or(UNKNOWN_RTT, |s| s.srtt);
+
+        if let Some(fastest) = healthy.iter().map(|(_, stats)| srtt(*stats)).min() {
+            let band: Vec<IpAddr> = healthy
+                .iter()
+                .filter(|(_, stats)| srtt(*stats) <= fastest + RTT_BAND)
+                .map(|(server, _)| *server)
+                .collect();
+
+            return band.choose(&mut rand::thread_rng()).copied();
+        }
+
+        backed_off
+            .into_iter()
+            .min_by_key(|(_, stats)| stats.and_then(|s| s.backoff_until))
+            .map(|(server, _)| server)
+    }
+
+    /// Returns the number of tracked servers.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().servers.len()
+    }
+
+    /// Returns if no servers are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl InfraCacheInner {
+    /// Returns the statistics of `server`, ignoring outdated entries.
+    fn get(&self, server: &IpAddr, now: Instant) -> Option<ServerStats> {
+        self.servers
+            .get(server)
+            .filter(|stats| now.saturating_duration_since(stats.updated) < ENTRY_TTL)
+            .copied()
+    }
+
+    fn insert(&mut self, server: IpAddr, stats: ServerStats) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        // Outdated entries are removed first. If the cache is still full,
+        // the least recently updated server is evicted.
+        if !self.servers.contains_key(&server) && self.servers.len() >= self.max_entries {
+            let now = stats.updated;
+            self.servers
+                .retain(|_, stats| now.saturating_duration_since(stats.updated) < ENTRY_TTL);
+
+            while self.servers.len() >= self.max_entries {
+                let oldest = self
+                    .servers
+                    .iter()
+                    .min_by_key(|(_, stats)| stats.updated)
+                    .map(|(server, _)| *server);
+
+                match oldest {
+                    Some(oldest) => self.servers.remove(&oldest),
+                    None => break,
+                };
+            }
+        }
+
+        self.servers.insert(server, stats);
+    }
+}
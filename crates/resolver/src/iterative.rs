@@ -1,27 +1,689 @@
+use std::{
+    future::Future,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::Mutex,
+};
+
 use async_trait::async_trait;
+use portal_client::{Client, ClientError};
+use portal_common::IpVersion;
+use portal_proto::{
+    sockets::IntoSockets, Class, Message, Name, Query, RData, RType, Rcode, Record, ToQuery, Zone,
+};
 
 use crate::{
-    resolver::{ResolveResult, ToResolver},
-    types::dns::ToQuery,
+    recursive::address_types, IntoHints, ResolveResult, ResolverError, ResultRecords, ToResolver,
 };
 
-pub struct IterativeResolver {}
+/// The maximum number of servers asked while walking the delegation chain
+/// of a single query.
+pub(crate) const MAX_STEPS: usize = 32;
+
+/// The maximum nesting of walks started to resolve the addresses of name
+/// servers in glueless delegations.
+pub(crate) const MAX_DEPTH: usize = 4;
+
+/// The port name servers are contacted on.
+const DNS_PORT: u16 = 53;
+
+/// The [`IterativeResolver`] doesn't follow referrals. Queries are answered
+/// with the response of the root servers, which is usually a referral to
+/// the TLD servers. Clients then walk the delegation chain themselves. See
+/// [RFC 1034 Section 4.3.1](https://datatracker.ietf.org/doc/html/rfc1034#section-4.3.1).
+///
+/// The complete chain can be walked hop by hop via [`IterativeResolver::trace`].
+pub struct IterativeResolver {
+    hint_index: Mutex<usize>,
+    hints: Vec<IpAddr>,
+    client: Client,
+
+    /// The address families used to contact name servers.
+    ip_version: IpVersion,
+
+    /// The port name servers are contacted on, which is [`DNS_PORT`] outside
+    /// of tests.
+    port: u16,
+}
 
 #[async_trait]
 impl ToResolver for IterativeResolver {
-    async fn resolve(&self, message: &crate::types::dns::Message) -> ResolveResult {
-        todo!()
+    async fn resolve(&self, message: &Message) -> ResolveResult {
+        self.resolve_raw(message).await
     }
 
     async fn resolve_raw<Q: ToQuery>(&self, query: Q) -> ResolveResult {
-        todo!()
+        match self.trace(query).next_step().await {
+            Some(step) => Ok(step?.records),
+            None => Err(ResolverError::NoAnswer),
+        }
+    }
+}
+
+impl IterativeResolver {
+    /// Creates a new iterative resolver. Name servers are contacted via the
+    /// address families of `ip_version`.
+    pub async fn new<T>(hint_file_path: String, ip_version: T) -> Result<Self, ResolverError>
+    where
+        T: Into<IpVersion>,
+    {
+        let ip_version = ip_version.into();
+        let client = Client::builder()
+            .with_ip_version(ip_version)
+            .build()
+            .await?;
+
+        let zone = Zone::from_file(hint_file_path.into())?;
+        let hints = zone
+            .into_hints()
+            .iter()
+            .flat_map(|hint| hint.ip_addrs(ip_version))
+            .collect();
+
+        Ok(Self {
+            hint_index: Mutex::new(0),
+            port: DNS_PORT,
+            ip_version,
+            client,
+            hints,
+        })
+    }
+
+    /// Returns a [`Trace`] which walks the delegation chain of `query` from
+    /// the root servers down to the authoritative servers, one hop at a
+    /// time.
+    ///
+    /// ### Example
+    ///
+    /// ```ignore
+    /// let mut trace = resolver.trace(("example.com", RType::A, Class::IN));
+    ///
+    /// while let Some(step) = trace.next_step().await {
+    ///     let step = step?;
+    ///     println!("{} ({}): {:?}", step.zone, step.server, step.referral);
+    /// }
+    /// ```
+    pub fn trace<Q: ToQuery>(&self, query: Q) -> Trace<'_> {
+        Trace::new(self, query.to_query(), 0)
+    }
+
+    /// Returns the addresses of the root servers. The first server rotates
+    /// with every call to spread the load.
+    fn root_servers(&self) -> Vec<IpAddr> {
+        // TODO (Techassi): Handle the unwrapping
+        let mut hint_index = self.hint_index.lock().unwrap();
+
+        if *hint_index >= self.hints.len() {
+            *hint_index = 0;
+        }
+
+        let mut servers = self.hints.clone();
+        servers.rotate_left(*hint_index);
+        *hint_index += 1;
+
+        servers
+    }
+
+    /// Resolves the addresses of the first of the name servers `names` which
+    /// has any usable ones. This is required for glueless delegations.
+    fn resolve_addresses(
+        &self,
+        names: Vec<Name>,
+        depth: usize,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<IpAddr>, ResolverError>> + Send + '_>> {
+        Box::pin(async move {
+            for name in names {
+                for ty in address_types(self.ip_version) {
+                    let query = Query::new(name.clone(), *ty, Class::IN);
+                    let records = match Trace::new(self, query, depth).run().await {
+                        Ok(records) => records,
+                        Err(ResolverError::ClientError(err)) => return Err(err.into()),
+                        Err(_) => continue,
+                    };
+
+                    let addrs: Vec<IpAddr> = records
+                        .answers
+                        .iter()
+                        .filter_map(ip_addr)
+                        .filter(|addr| self.ip_version.supports(addr))
+                        .collect();
+
+                    if !addrs.is_empty() {
+                        return Ok(addrs);
+                    }
+                }
+            }
+
+            Ok(Vec::new())
+        })
+    }
+}
+
+/// A single hop of a [`Trace`].
+#[derive(Debug)]
+pub struct Step {
+    /// The zone the asked server is authoritative for.
+    pub zone: Name,
+
+    /// The address of the name server which was asked.
+    pub server: IpAddr,
+
+    /// The response of the server.
+    pub records: ResultRecords,
+
+    /// The zone the response delegates to. This is `None` for answers and
+    /// negative answers, which end the trace.
+    pub referral: Option<Name>,
+
+    /// The addresses of the name servers of the delegated zone, which are
+    /// asked in the next step. These are taken from the glue records of the
+    /// referral or are resolved separately for glueless delegations.
+    pub glue: Vec<IpAddr>,
+}
+
+/// Walks the delegation chain of a query one hop at a time. Created by
+/// [`IterativeResolver::trace`].
+pub struct Trace<'a> {
+    resolver: &'a IterativeResolver,
+    query: Query,
+
+    /// The zone of the servers asked in the next step.
+    zone: Name,
+    servers: Vec<IpAddr>,
+
+    steps: usize,
+    depth: usize,
+    done: bool,
+}
+
+impl<'a> Trace<'a> {
+    fn new(resolver: &'a IterativeResolver, query: Query, depth: usize) -> Self {
+        Self {
+            servers: resolver.root_servers(),
+            zone: Name::default(),
+            steps: 0,
+            done: false,
+            resolver,
+            query,
+            depth,
+        }
+    }
+
+    /// Asks the next name server and returns the hop. Returns `None` once
+    /// an answer, a negative answer or an error was returned.
+    pub async fn next_step(&mut self) -> Option<Result<Step, ResolverError>> {
+        if self.done {
+            return None;
+        }
+
+        let step = self.step().await;
+        self.done = !matches!(&step, Ok(step) if step.referral.is_some());
+
+        Some(step)
+    }
+
+    /// Walks the remaining delegation chain and returns the final response.
+    pub async fn run(mut self) -> ResolveResult {
+        let mut records = Err(ResolverError::NoAnswer);
+
+        while let Some(step) = self.next_step().await {
+            records = step.map(|step| step.records);
+        }
+
+        records
+    }
+
+    async fn step(&mut self) -> Result<Step, ResolverError> {
+        if self.steps >= MAX_STEPS || self.depth > MAX_DEPTH {
+            return Err(ResolverError::TooManyReferrals);
+        }
+        self.steps += 1;
+
+        // Unresponsive and unreachable servers are skipped, the next server
+        // of the zone is asked instead.
+        let (server, message) = loop {
+            if self.servers.is_empty() {
+                return Err(ResolverError::NoMoreTargets);
+            }
+            let server = self.servers.remove(0);
+
+            match self
+                .resolver
+                .client
+                .query_multi(
+                    self.query.clone(),
+                    SocketAddr::new(server, self.resolver.port).into_sockets(),
+                )
+                .await
+            {
+                Ok((message, _, _, _)) => break (server, message),
+                Err(ClientError::ReadTimeout(_) | ClientError::IO(_)) => continue,
+                Err(err) => return Err(err.into()),
+            }
+        };
+
+        let records = ResultRecords::from(message);
+        let mut step = Step {
+            zone: self.zone.clone(),
+            referral: None,
+            glue: Vec::new(),
+            records,
+            server,
+        };
+
+        let zone = match referral(&step.records, &self.query, &self.zone)? {
+            Some(zone) => zone,
+            None => return Ok(step),
+        };
+
+        let mut glue = find_glue(&step.records, &self.zone, self.resolver.ip_version);
+        if glue.is_empty() {
+            glue = self
+                .resolver
+                .resolve_addresses(name_servers(&step.records), self.depth + 1)
+                .await?;
+        }
+
+        if glue.is_empty() {
+            return Err(ResolverError::NoGlueRecords);
+        }
+
+        self.zone = zone.clone();
+        self.servers = glue.clone();
+
+        step.referral = Some(zone);
+        step.glue = glue;
+
+        Ok(step)
+    }
+}
+
+/// Returns the zone the response `records` to `query` delegates to, which
+/// was sent by a server of `zone`. Answers and negative answers (NXDOMAIN or
+/// NODATA with the SOA of the zone) end the trace and return `None`.
+fn referral(
+    records: &ResultRecords,
+    query: &Query,
+    zone: &Name,
+) -> Result<Option<Name>, ResolverError> {
+    if !records.answers.is_empty()
+        || records.rcode != Rcode::NoError
+        || records.authorities.iter().any(|r| r.is_soa())
+    {
+        return Ok(None);
+    }
+
+    let referral = match referral_zone(records) {
+        Some(referral) => referral,
+        None => return Err(ResolverError::NoAnswer),
+    };
+
+    // Every referral has to lead closer to the query name, otherwise the
+    // walk could loop forever.
+    if !query.name.is_subdomain_of(&referral) || referral.num_labels() <= zone.num_labels() {
+        return Err(ResolverError::InvalidReferral(referral));
     }
 
-    // async fn lookup<Q: ToQuery>(&self, query: Q) -> ResolveResult {
-    //     todo!()
-    // }
+    Ok(Some(referral))
+}
+
+/// Returns the zone a referral delegates to, which is the owner name of the
+/// NS records in the authority section.
+fn referral_zone(records: &ResultRecords) -> Option<Name> {
+    records
+        .authorities
+        .iter()
+        .find(|r| *r.header().ty() == RType::NS)
+        .map(|r| r.header().name().to_lowercase())
+}
+
+/// Returns the names of the name servers of a referral.
+fn name_servers(records: &ResultRecords) -> Vec<Name> {
+    records
+        .authorities
+        .iter()
+        .filter_map(|r| match r.rdata() {
+            RData::NS(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the addresses of the name servers of a referral from the
+/// additional section. Only glue within `zone`, the zone of the server
+/// which sent the referral, is trusted. See
+/// [RFC 2181 Section 5.4.1](https://datatracker.ietf.org/doc/html/rfc2181#section-5.4.1).
+fn find_glue(records: &ResultRecords, zone: &Name, ip_version: IpVersion) -> Vec<IpAddr> {
+    let names = name_servers(records);
+
+    records
+        .additionals
+        .iter()
+        .filter(|r| r.header().name().is_subdomain_of(zone))
+        .filter(|r| {
+            let owner = r.header().name().to_lowercase();
+            names.iter().any(|name| name.to_lowercase() == owner)
+        })
+        .filter_map(ip_addr)
+        .filter(|addr| ip_version.supports(addr))
+        .collect()
+}
 
-    // async fn refresh<Q: ToQuery>(&self, query: Q) {
-    //     todo!()
-    // }
+/// Returns the address of A and AAAA records.
+fn ip_addr(record: &Record) -> Option<IpAddr> {
+    match record.rdata() {
+        RData::A(addr) => Some(IpAddr::V4(*addr)),
+        RData::AAAA(addr) => Some(IpAddr::V6(*addr)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use binbuf::prelude::*;
+    use portal_proto::{Header, RHeader, SOA};
+    use tokio::net::UdpSocket;
+
+    use super::*;
+
+    fn query(name: &str) -> Query {
+        Query::new(Name::try_from(name).unwrap(), RType::A, Class::IN)
+    }
+
+    fn name(name: &str) -> Name {
+        Name::try_from(name).unwrap()
+    }
+
+    fn record(owner: &str, rdata: RData) -> Record {
+        let ty = match &rdata {
+            RData::A(_) => RType::A,
+            RData::AAAA(_) => RType::AAAA,
+            RData::NS(_) => RType::NS,
+            RData::SOA(_) => RType::SOA,
+            _ => unreachable!(),
+        };
+
+        let mut header = RHeader::new();
+        header.set_name(name(owner));
+        header.set_ty(ty);
+        header.set_class(Class::IN);
+        header.set_ttl(3600);
+
+        let mut record = Record::new_with_header(header);
+        record.set_rdata(rdata).normalize_rdlen();
+        record
+    }
+
+    fn a(owner: &str, addr: Ipv4Addr) -> Record {
+        record(owner, RData::A(addr))
+    }
+
+    fn ns(owner: &str, target: &str) -> Record {
+        record(owner, RData::NS(name(target)))
+    }
+
+    fn soa(owner: &str) -> Record {
+        let parts = [
+            "ns.example.",
+            "admin.example.",
+            "1",
+            "3600",
+            "600",
+            "86400",
+            "300",
+        ];
+        let soa = SOA::try_from_parts(&parts, &Name::default()).unwrap();
+        record(owner, RData::SOA(soa))
+    }
+
+    /// Returns a referral to `zone`, whose name server `ns.<zone>` has the
+    /// glue address `addr`.
+    fn delegation(zone: &str, addr: Ipv4Addr) -> ResultRecords {
+        let target = format!("ns.{zone}");
+
+        ResultRecords {
+            authorities: vec![ns(zone, &target)],
+            additionals: vec![a(&target, addr)],
+            ..Default::default()
+        }
+    }
+
+    async fn resolver(port: u16) -> IterativeResolver {
+        let client = Client::builder()
+            .with_ip_version(IpVersion::V4)
+            .with_read_timeout(1)
+            .build()
+            .await
+            .unwrap();
+
+        IterativeResolver {
+            hint_index: Mutex::new(0),
+            hints: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
+            ip_version: IpVersion::V4,
+            client,
+            port,
+        }
+    }
+
+    /// Answers the queries sent to the returned port with `responses` in
+    /// order. All name servers of the tests share this loopback address.
+    async fn serve(responses: Vec<ResultRecords>) -> u16 {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = socket.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 512];
+
+            for records in responses {
+                let (len, client_addr) = socket.recv_from(&mut buf).await.unwrap();
+
+                let mut reader = ReadBuffer::new(&buf[..len]);
+                let header = Header::read::<BigEndian>(&mut reader).unwrap();
+                let query = Message::read::<BigEndian>(&mut reader, header).unwrap();
+
+                let mut message = Message::new_with_header(Header::new(query.transaction_id()));
+                message.set_is_response(true);
+                message.set_rcode(records.rcode);
+                message.add_question(query.question().unwrap().clone());
+                message.add_answers(&mut records.answers.clone());
+                message.add_authorities(&mut records.authorities.clone());
+                message.add_additionals(&mut records.additionals.clone());
+
+                let mut writer = WriteBuffer::new();
+                message.write::<BigEndian>(&mut writer).unwrap();
+                socket.send_to(writer.bytes(), client_addr).await.unwrap();
+            }
+        });
+
+        port
+    }
+
+    #[test]
+    fn test_referral_zone() {
+        let records = ResultRecords {
+            authorities: vec![ns("Example.COM.", "ns.example.com.")],
+            ..Default::default()
+        };
+
+        assert_eq!(referral_zone(&records), Some(name("example.com.")));
+        assert_eq!(referral_zone(&ResultRecords::default()), None);
+    }
+
+    #[test]
+    fn test_name_servers() {
+        let records = ResultRecords {
+            authorities: vec![
+                soa("example.com."),
+                ns("example.com.", "ns1.example.com."),
+                ns("example.com.", "ns2.example.org."),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            name_servers(&records),
+            vec![name("ns1.example.com."), name("ns2.example.org.")]
+        );
+    }
+
+    #[test]
+    fn test_find_glue() {
+        let records = ResultRecords {
+            authorities: vec![
+                ns("example.com.", "ns1.example.com."),
+                ns("example.com.", "NS2.example.org."),
+            ],
+            additionals: vec![
+                a("ns1.example.com.", Ipv4Addr::new(192, 0, 2, 1)),
+                record("ns1.example.com.", RData::AAAA(Ipv6Addr::LOCALHOST)),
+                a("ns2.example.org.", Ipv4Addr::new(192, 0, 2, 2)),
+                a("other.example.com.", Ipv4Addr::new(192, 0, 2, 3)),
+            ],
+            ..Default::default()
+        };
+
+        // Glue outside of com. is not trusted by the com. servers, neither
+        // are addresses of names which aren't name servers
+        assert_eq!(
+            find_glue(&records, &name("com."), IpVersion::V4),
+            vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))]
+        );
+
+        // The root servers are trusted with any glue
+        assert_eq!(
+            find_glue(&records, &Name::default(), IpVersion::Both),
+            vec![
+                IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+                IpAddr::V6(Ipv6Addr::LOCALHOST),
+                IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_referral() {
+        let query = query("www.example.com.");
+        let root = Name::default();
+
+        let answer = ResultRecords {
+            answers: vec![a("www.example.com.", Ipv4Addr::new(192, 0, 2, 1))],
+            ..Default::default()
+        };
+        assert_eq!(referral(&answer, &query, &root).unwrap(), None);
+
+        let nxdomain = ResultRecords {
+            rcode: Rcode::NameError,
+            ..Default::default()
+        };
+        assert_eq!(referral(&nxdomain, &query, &root).unwrap(), None);
+
+        let nodata = ResultRecords {
+            authorities: vec![soa("example.com.")],
+            ..Default::default()
+        };
+        assert_eq!(referral(&nodata, &query, &root).unwrap(), None);
+
+        let records = delegation("com.", Ipv4Addr::LOCALHOST);
+        assert_eq!(
+            referral(&records, &query, &root).unwrap(),
+            Some(name("com."))
+        );
+
+        assert!(matches!(
+            referral(&ResultRecords::default(), &query, &root),
+            Err(ResolverError::NoAnswer)
+        ));
+    }
+
+    #[test]
+    fn test_referral_progress() {
+        let query = query("www.example.com.");
+
+        // Referrals have to enclose the query name
+        let records = delegation("example.org.", Ipv4Addr::LOCALHOST);
+        assert!(matches!(
+            referral(&records, &query, &Name::default()),
+            Err(ResolverError::InvalidReferral(zone)) if zone == name("example.org.")
+        ));
+
+        // Referrals have to lead below the zone of the asking server
+        for zone in ["com.", "example.com."] {
+            let records = delegation("com.", Ipv4Addr::LOCALHOST);
+            assert!(matches!(
+                referral(&records, &query, &name(zone)),
+                Err(ResolverError::InvalidReferral(to)) if to == name("com.")
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trace_bounds() {
+        let resolver = resolver(DNS_PORT).await;
+
+        let mut trace = resolver.trace(query("example.com."));
+        trace.steps = MAX_STEPS;
+        assert!(matches!(
+            trace.next_step().await,
+            Some(Err(ResolverError::TooManyReferrals))
+        ));
+        assert!(trace.next_step().await.is_none());
+
+        let trace = Trace::new(&resolver, query("example.com."), MAX_DEPTH + 1);
+        assert!(matches!(
+            trace.run().await,
+            Err(ResolverError::TooManyReferrals)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trace_answer() {
+        let answer = ResultRecords {
+            answers: vec![a("www.example.", Ipv4Addr::new(192, 0, 2, 1))],
+            ..Default::default()
+        };
+
+        let port = serve(vec![delegation("example.", Ipv4Addr::LOCALHOST), answer]).await;
+        let resolver = resolver(port).await;
+        let mut trace = resolver.trace(query("www.example."));
+
+        let step = trace.next_step().await.unwrap().unwrap();
+        assert_eq!(step.zone, Name::default());
+        assert_eq!(step.referral, Some(name("example.")));
+        assert_eq!(step.glue, vec![IpAddr::V4(Ipv4Addr::LOCALHOST)]);
+
+        let step = trace.next_step().await.unwrap().unwrap();
+        assert_eq!(step.zone, name("example."));
+        assert_eq!(step.referral, None);
+        assert_eq!(step.records.answers.len(), 1);
+
+        assert!(trace.next_step().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_trace_negative() {
+        let nxdomain = ResultRecords {
+            rcode: Rcode::NameError,
+            authorities: vec![soa("example.")],
+            ..Default::default()
+        };
+
+        let port = serve(vec![delegation("example.", Ipv4Addr::LOCALHOST), nxdomain]).await;
+        let resolver = resolver(port).await;
+
+        let records = resolver.trace(query("nope.example.")).run().await.unwrap();
+        assert_eq!(records.rcode, Rcode::NameError);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_raw_first_hop() {
+        let port = serve(vec![delegation("example.", Ipv4Addr::LOCALHOST)]).await;
+        let resolver = resolver(port).await;
+
+        // The referral of the root servers is returned as is
+        let records = resolver.resolve_raw(query("www.example.")).await.unwrap();
+        assert!(records.answers.is_empty());
+        assert_eq!(referral_zone(&records), Some(name("example.")));
+    }
 }
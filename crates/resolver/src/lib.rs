@@ -2,35 +2,61 @@ use std::fmt::Display;
 
 use async_trait::async_trait;
 use enum_dispatch::enum_dispatch;
-use portal_proto::{Message, Record, ToQuery};
+use portal_proto::{Message, Rcode, Record, ToQuery};
 
+mod dnssec;
 mod error;
 mod forwarding;
-// mod iterative;
+mod infra;
+mod iterative;
 mod mode;
 mod recursive;
 
+pub use dnssec::*;
 pub use error::*;
 pub use forwarding::*;
-// pub use iterative::*;
+pub use infra::*;
+pub use iterative::*;
 pub use mode::*;
 pub use recursive::*;
 
 pub type ResolveResult = Result<ResultRecords, ResolverError>;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ResultRecords {
+    /// The RCODE of the final response. This is used to differentiate
+    /// between NXDOMAIN and NODATA answers, which both don't contain any
+    /// answer records.
+    pub rcode: Rcode,
     pub answers: Vec<Record>,
     pub authorities: Vec<Record>,
     pub additionals: Vec<Record>,
+
+    /// The DNSSEC security status. This is [`Security::Indeterminate`] if
+    /// the records were not validated.
+    pub security: Security,
+}
+
+impl Default for ResultRecords {
+    fn default() -> Self {
+        Self {
+            rcode: Rcode::NoError,
+            answers: Vec::new(),
+            authorities: Vec::new(),
+            additionals: Vec::new(),
+            security: Security::default(),
+        }
+    }
 }
 
 impl From<Message> for ResultRecords {
     fn from(msg: Message) -> Self {
         Self {
+            rcode: msg.rcode(),
             answers: msg.answers().clone(),
             authorities: msg.authorities().clone(),
             additionals: msg.additionals().clone(),
+            security: Security::default(),
         }
     }
 }
@@ -57,6 +83,12 @@ impl Display for ResultRecords {
 }
 
 impl ResultRecords {
+    /// Returns if this is a negative answer (NXDOMAIN or NODATA). See
+    /// [RFC 2308](https://datatracker.ietf.org/doc/html/rfc2308#section-2).
+    pub fn is_negative(&self) -> bool {
+        self.rcode == Rcode::NameError || (self.rcode == Rcode::NoError && self.answers.is_empty())
+    }
+
     pub fn normalize_rdlens(&mut self) {
         for answer in &mut self.answers {
             answer.normalize_rdlen();
@@ -83,6 +115,6 @@ pub trait ToResolver {
 #[enum_dispatch]
 pub enum Resolver {
     Recursive(recursive::RecursiveResolver),
-    // Iterative(iterative::IterativeResolver),
+    Iterative(iterative::IterativeResolver),
     Forwarding(forwarding::ForwardingResolver),
 }
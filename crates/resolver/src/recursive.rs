@@ -1,14 +1,41 @@
 use std::{
+    future::Future,
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
-    sync::Mutex,
+    pin::Pin,
+    time::Instant,
 };
 
 use async_trait::async_trait;
+use futures::future::join_all;
 use portal_client::{Client, ClientError};
-use portal_common::{cast, cast_or};
-use portal_proto::{sockets::IntoSockets, Message, RData, RType, ToQuery, Zone};
+use portal_common::{cast, cast_or, IpVersion};
+use portal_proto::{
+    sockets::IntoSockets, Message, Name, Query, RData, RHeader, RType, Rcode, Record, ToQuery, Zone,
+};
+
+use crate::{
+    iterative::{MAX_DEPTH, MAX_STEPS},
+    InfraCache, Lookup, ResolveResult, ResolverError, ResultRecords, ToResolver, TrustAnchors,
+    Validator,
+};
+
+/// The UDP payload size advertised by validating resolvers. DNSSEC responses
+/// are usually larger than 512 octets. This value avoids IP fragmentation,
+/// see <https://www.dnsflagday.net/2020>.
+const DNSSEC_BUFFER_SIZE: usize = 1232;
+
+/// The maximum number of CNAME and DNAME records followed for a single
+/// query.
+const MAX_CHAIN_LENGTH: usize = 12;
 
-use crate::{ResolveResult, ResolverError, ToResolver};
+/// The maximum number of name servers of a glueless referral whose
+/// addresses are resolved concurrently.
+const MAX_GLUELESS_NS: usize = 3;
+
+/// The maximum number of additional queries sent because of QNAME
+/// minimisation, after which the full name is sent. See
+/// [RFC 9156 Section 2.3](https://datatracker.ietf.org/doc/html/rfc9156#section-2.3).
+const MAX_MINIMISE_COUNT: usize = 10;
 
 #[derive(Debug)]
 pub struct Hint {
@@ -29,6 +56,25 @@ pub trait IntoHints {
     fn into_hints(self) -> Vec<Hint>;
 }
 
+impl Hint {
+    pub fn ipv4_addr(&self) -> Ipv4Addr {
+        self.ipv4_addr
+    }
+
+    pub fn ipv6_addr(&self) -> Ipv6Addr {
+        self.ipv6_addr
+    }
+
+    /// Returns the addresses of the hint which are usable with
+    /// `ip_version`. Unset addresses are skipped.
+    pub fn ip_addrs(&self, ip_version: IpVersion) -> Vec<IpAddr> {
+        [IpAddr::V4(self.ipv4_addr), IpAddr::V6(self.ipv6_addr)]
+            .into_iter()
+            .filter(|addr| ip_version.supports(addr))
+            .collect()
+    }
+}
+
 impl IntoHints for Zone {
     fn into_hints(self) -> Vec<Hint> {
         let mut hints = Vec::new();
@@ -68,9 +114,23 @@ impl IntoHints for Zone {
 }
 
 pub struct RecursiveResolver {
-    hint_index: Mutex<usize>,
     hints: Vec<Hint>,
     client: Client,
+
+    /// Tracks the RTT and timeouts of all name servers. Queries are sent to
+    /// the fastest healthy server of a zone.
+    infra: InfraCache,
+
+    /// The address families used to contact name servers. This is the same
+    /// IP version the client socket is bound with.
+    ip_version: IpVersion,
+
+    /// Only send the labels of the query name required by each server
+    qname_minimisation: bool,
+
+    /// Validates resolved records via DNSSEC. This is `None` if validation
+    /// is disabled.
+    validator: Option<Validator>,
 }
 
 #[async_trait]
@@ -80,112 +140,94 @@ impl ToResolver for RecursiveResolver {
     }
 
     async fn resolve_raw<Q: ToQuery>(&self, query: Q) -> ResolveResult {
-        let mut target_candidates: Vec<IpAddr> = Vec::new();
         let query = query.to_query();
+        let mut records = self.chase(query.clone()).await?;
 
-        target_candidates.push(self.hint());
-
-        // TODO (Techassi): Introduce a state machine here
-        loop {
-            // Remove one of the target candidates and use it to send a DNS
-            // query.
-            let target = match target_candidates.pop() {
-                Some(t) => t,
-                None => return Err(ResolverError::NoMoreTargets),
-            };
-
-            // If we timeout on read, this is most likely a network related
-            // issue, e.g. the target server is not responding. If this
-            // happens we just continue the loop and remove the next target
-            // candidate in line.
-            let (message, _, _) = match self
-                .client
-                .query(query.clone(), SocketAddr::new(target, 53).into_sockets())
-                .await
-            {
-                Ok(msg) => msg,
-                Err(err) => match err {
-                    ClientError::ReadTimeout(_) => continue,
-                    _ => return Err(ResolverError::ClientError(err)),
-                },
-            };
+        let validator = match &self.validator {
+            Some(validator) => validator,
+            None => return Ok(records),
+        };
 
-            // We got at least one answer. We can immediately return these.
-            if message.ancount() > 0 {
-                return Ok(message.into());
-            }
+        records.security = validator.validate(self, &query, &records).await?;
 
-            // We received no NS records. That's bad.
-            if message.nscount() == 0 {
-                return Err(ResolverError::NoAnswer);
-            }
+        // Bogus records are never returned. Clients receive SERVFAIL instead.
+        // See RFC 4035 Section 5.5.
+        if records.security.is_bogus() {
+            return Ok(ResultRecords {
+                rcode: Rcode::ServerFailure,
+                security: records.security,
+                ..Default::default()
+            });
+        }
 
-            // We can ask the original / primary DNS server. This involves
-            // looking up the IP address for the provided domain name. If we
-            // were able to retrieve the IP, we can continue this loop with
-            // the updated target IP address.
-
-            // TODO (Techassi): Add support to handle SOA records
-            // if message.is_soa() {
-            //     let soa = match message.get_soa_record() {
-            //         Some(soa) => soa,
-            //         None => return Err(ResolverError::NoSoaRecord),
-            //     };
-
-            //     let soa_query = Query::new(soa.get_mname().clone(), query.ty, query.class);
-            //     let _results = self.resolve_raw(soa_query).await?;
-            // }
-
-            // At this step there should be some "glue" records. These records
-            // provide NS records in the authority section. NS RRs contain a
-            // domain name. To avoid resolving this name, most DNS servers
-            // provide A and AAAA records for these domain names in the
-            // additional records section.
-            if let Some(mut ip_addrs) = self.find_glue_records(&message).await {
-                target_candidates.clear();
-                target_candidates.append(&mut ip_addrs);
-                continue;
-            }
+        Ok(records)
+    }
+}
 
-            // The DNS server didn't provide any glue records in the additional
-            // section, bummer... We know have to look them up manually by
-            // querying the root DNS servers again.
+#[async_trait]
+impl Lookup for RecursiveResolver {
+    async fn lookup(&self, query: Query) -> ResolveResult {
+        self.lookup_at(query, 0).await
+    }
+}
 
-            // TODO (Techassi): We should query for multiple NS servers in
-            // parallel
-            for record in message.authorities() {
-                let ns_name = cast_or!(record.rdata(), RData::NS, continue);
-                let records = self
-                    .resolve_raw((ns_name, &RType::A, record.header().class()))
-                    .await?;
+impl RecursiveResolver {
+    /// Creates a new recursive resolver. Name servers are contacted via the
+    /// address families of `ip_version`.
+    pub async fn new<T>(hint_file_path: String, ip_version: T) -> Result<Self, ResolverError>
+    where
+        T: Into<IpVersion>,
+    {
+        let ip_version = ip_version.into();
+        let client = Client::builder()
+            .with_ip_version(ip_version)
+            .build()
+            .await?;
 
-                if !records.answers.is_empty() {
-                    target_candidates.clear();
+        let zone = Zone::from_file(hint_file_path.into())?;
+        let hints = zone.into_hints();
 
-                    for answer in records.answers {
-                        let ip_addr = cast_or!(answer.rdata(), RData::A, continue);
-                        target_candidates.push(IpAddr::V4(*ip_addr))
-                    }
+        let resolver = Self {
+            infra: InfraCache::default(),
+            qname_minimisation: false,
+            validator: None,
+            ip_version,
+            client,
+            hints,
+        };
 
-                    break;
-                }
-            }
-        }
+        Ok(resolver)
     }
-}
 
-impl RecursiveResolver {
-    pub async fn new(hint_file_path: String) -> Result<Self, ResolverError> {
-        let client = match Client::new().await {
-            Ok(client) => client,
-            Err(_) => todo!(),
-        };
+    /// Creates a new validating recursive resolver. The chain of trust
+    /// starts at the DS or DNSKEY records in the file at `trust_anchor_path`.
+    /// Queries are sent with the DO bit set to retrieve DNSSEC records.
+    pub async fn new_validating<T>(
+        hint_file_path: String,
+        trust_anchor_path: String,
+        ip_version: T,
+    ) -> Result<Self, ResolverError>
+    where
+        T: Into<IpVersion>,
+    {
+        let ip_version = ip_version.into();
+        let client = Client::builder()
+            .with_ip_version(ip_version)
+            .with_dnssec_ok(true)
+            .with_buffer_size(DNSSEC_BUFFER_SIZE)
+            .build()
+            .await?;
 
         let zone = Zone::from_file(hint_file_path.into())?;
         let hints = zone.into_hints();
 
+        let anchors = TrustAnchors::from_file(trust_anchor_path)?;
+
         let resolver = Self {
-            hint_index: Mutex::new(0),
+            infra: InfraCache::default(),
+            qname_minimisation: false,
+            validator: Some(Validator::new(anchors)),
+            ip_version,
             client,
             hints,
         };
@@ -193,6 +235,191 @@ impl RecursiveResolver {
         Ok(resolver)
     }
 
+    /// Customize if QNAME minimisation is used. When enabled, each server
+    /// only receives the query name up to one label below the zone it is
+    /// authoritative for, which hides the full name from the root and TLD
+    /// servers. This is disabled by default. See
+    /// [RFC 9156](https://datatracker.ietf.org/doc/html/rfc9156).
+    pub fn with_qname_minimisation(&mut self, qname_minimisation: bool) -> &mut Self {
+        self.qname_minimisation = qname_minimisation;
+        self
+    }
+
+    /// Walks the delegation chain from the root servers down to the servers
+    /// authoritative for `query`. `depth` is the nesting of lookups started
+    /// to resolve the addresses of name servers in glueless referrals.
+    fn lookup_at(
+        &self,
+        query: Query,
+        depth: usize,
+    ) -> Pin<Box<dyn Future<Output = ResolveResult> + Send + '_>> {
+        Box::pin(async move {
+            if depth > MAX_DEPTH {
+                return Err(ResolverError::TooManyReferrals);
+            }
+
+            let mut target_candidates = self.hint();
+            let mut minimisation = Minimisation::new(self.qname_minimisation);
+
+            // The zone the target candidates are authoritative for
+            let mut zone = Name::default();
+            let mut steps = 0;
+
+            // TODO (Techassi): Introduce a state machine here
+            loop {
+                if steps >= MAX_STEPS {
+                    return Err(ResolverError::TooManyReferrals);
+                }
+
+                // Remove the fastest of the target candidates and use it to send
+                // a DNS query.
+                let target = match self.infra.select(&target_candidates) {
+                    Some(t) => t,
+                    None => return Err(ResolverError::NoMoreTargets),
+                };
+                target_candidates.retain(|candidate| *candidate != target);
+
+                // If we timeout on read, this is most likely a network related
+                // issue, e.g. the target server is not responding. If this
+                // happens we just continue the loop and remove the next target
+                // candidate in line. The same applies to IO errors, which are
+                // returned if the network of the target is unreachable, e.g. IPv6
+                // targets on hosts without IPv6 connectivity. Both are recorded
+                // as timeouts, which eventually backs off the target.
+                // Truncated responses are retried via TCP by the client, which
+                // is required for large RRsets (e.g. DNSKEY or TXT).
+                let minimised = minimisation.query(&query);
+                let start = Instant::now();
+                let (message, _, _, _) = match self
+                    .client
+                    .query_multi(
+                        minimised.clone().unwrap_or_else(|| query.clone()),
+                        SocketAddr::new(target, 53).into_sockets(),
+                    )
+                    .await
+                {
+                    Ok(msg) => {
+                        self.infra.record_rtt(target, start.elapsed());
+                        steps += 1;
+                        msg
+                    }
+                    Err(err) => match err {
+                        ClientError::ReadTimeout(_) | ClientError::IO(_) => {
+                            self.infra.record_timeout(target);
+                            continue;
+                        }
+                        _ => return Err(ResolverError::ClientError(err)),
+                    },
+                };
+
+                // Responses to minimised queries which are no referrals are
+                // not returned. Servers which failed to answer are treated
+                // like unresponsive ones and the next server is asked the
+                // same minimised query.
+                if minimised.is_some() && !is_referral(&message) {
+                    match minimisation.response(message.rcode()) {
+                        true => target_candidates.push(target),
+                        false => self.infra.record_timeout(target),
+                    }
+
+                    continue;
+                }
+
+                // We got at least one answer. We can immediately return these.
+                if message.ancount() > 0 {
+                    return Ok(message.into());
+                }
+
+                // The name doesn't exist (NXDOMAIN) or there are no records of
+                // the requested type (NODATA). Both negative answers include
+                // the SOA of the zone in the authority section, which is used
+                // to cache the negative answer. See RFC 2308 Section 2.
+                if message.rcode() == Rcode::NameError || message.is_soa() {
+                    return Ok(message.into());
+                }
+
+                // We received no NS records. That's bad.
+                if message.nscount() == 0 {
+                    return Err(ResolverError::NoAnswer);
+                }
+
+                // We can ask the original / primary DNS server. This involves
+                // looking up the IP address for the provided domain name. If we
+                // were able to retrieve the IP, we can continue this loop with
+                // the updated target IP address.
+
+                // TODO (Techassi): Add support to handle SOA records
+                // if message.is_soa() {
+                //     let soa = match message.get_soa_record() {
+                //         Some(soa) => soa,
+                //         None => return Err(ResolverError::NoSoaRecord),
+                //     };
+
+                //     let soa_query = Query::new(soa.get_mname().clone(), query.ty, query.class);
+                //     let _results = self.resolve_raw(soa_query).await?;
+                // }
+
+                // At this step there should be some "glue" records. These records
+                // provide NS records in the authority section. NS RRs contain a
+                // domain name. To avoid resolving this name, most DNS servers
+                // provide A and AAAA records for these domain names in the
+                // additional records section.
+                let referral = match message
+                    .authorities()
+                    .iter()
+                    .find(|r| *r.header().ty() == RType::NS)
+                {
+                    Some(record) => record.header().name().clone(),
+                    None => return Err(ResolverError::NoAnswer),
+                };
+
+                // Every referral has to lead closer to the query name,
+                // otherwise the lookup could loop forever.
+                if !query.name.is_subdomain_of(&referral)
+                    || referral.num_labels() <= zone.num_labels()
+                {
+                    return Err(ResolverError::InvalidReferral(referral));
+                }
+
+                minimisation.referral(&referral);
+                zone = referral;
+
+                if let Some(mut ip_addrs) = self.find_glue_records(&message).await {
+                    target_candidates.clear();
+                    target_candidates.append(&mut ip_addrs);
+                    continue;
+                }
+
+                // The DNS server didn't provide any glue records in the additional
+                // section, bummer... We know have to look them up manually by
+                // querying the root DNS servers again.
+                target_candidates = self.resolve_name_servers(&message, depth + 1).await?;
+            }
+        })
+    }
+
+    /// Resolves `query` and follows CNAME and DNAME records, also across
+    /// zones. The answer section contains the complete chain in order, see
+    /// [`Chain::finish`]. The other sections are taken from the last
+    /// response. See
+    /// [RFC 1034 Section 5.3.3](https://datatracker.ietf.org/doc/html/rfc1034#section-5.3.3).
+    async fn chase(&self, query: Query) -> ResolveResult {
+        let mut response = self.lookup(query.clone()).await?;
+
+        // Queries for the aliases themselves are not followed
+        if matches!(query.ty, RType::CNAME | RType::DNAME | RType::ANY) {
+            return Ok(response);
+        }
+
+        let mut chain = Chain::new(&query);
+
+        while let Some(name) = chain.follow(&mut response)? {
+            response = self.lookup(Query::new(name, query.ty, query.class)).await?;
+        }
+
+        Ok(response)
+    }
+
     pub async fn find_glue_records(&self, message: &Message) -> Option<Vec<IpAddr>> {
         let mut ip_addrs: Vec<IpAddr> = Vec::new();
 
@@ -206,11 +433,16 @@ impl RecursiveResolver {
                     continue;
                 }
 
-                // We are only interested in A and AAAA records.
-                match ar_record.rdata() {
-                    RData::A(ip) => ip_addrs.push(IpAddr::V4(*ip)),
-                    // RData::AAAA(ip) => ip_addrs.push(IpAddr::V6(*ip)),
+                // We are only interested in A and AAAA records of the
+                // address families we can reach.
+                let ip_addr = match ar_record.rdata() {
+                    RData::A(ip) => IpAddr::V4(*ip),
+                    RData::AAAA(ip) => IpAddr::V6(*ip),
                     _ => continue,
+                };
+
+                if self.ip_version.supports(&ip_addr) {
+                    ip_addrs.push(ip_addr);
                 }
             }
         }
@@ -222,17 +454,581 @@ impl RecursiveResolver {
         None
     }
 
-    pub fn hint(&self) -> IpAddr {
-        // TODO (Techassi): Handle the unwrapping
-        let mut hint_index = self.hint_index.lock().unwrap();
+    /// Resolves the addresses of the name servers of a glueless referral.
+    /// The first [`MAX_GLUELESS_NS`] names are resolved concurrently at
+    /// `depth` and the addresses of all of them are returned.
+    async fn resolve_name_servers(
+        &self,
+        message: &Message,
+        depth: usize,
+    ) -> Result<Vec<IpAddr>, ResolverError> {
+        let queries: Vec<Query> = message
+            .authorities()
+            .iter()
+            .filter_map(|record| {
+                let ns_name = cast_or!(record.rdata(), RData::NS, return None);
+                Some((ns_name.clone(), *record.header().class()))
+            })
+            .take(MAX_GLUELESS_NS)
+            .flat_map(|(ns_name, class)| {
+                address_types(self.ip_version)
+                    .iter()
+                    .map(move |ty| Query::new(ns_name.clone(), *ty, class))
+            })
+            .collect();
+
+        let results = join_all(
+            queries
+                .into_iter()
+                .map(|query| self.lookup_at(query, depth)),
+        )
+        .await;
+
+        let mut ip_addrs: Vec<IpAddr> = Vec::new();
+        let mut error = None;
+
+        for result in results {
+            let records = match result {
+                Ok(records) => records,
+                Err(err) => {
+                    error = Some(err);
+                    continue;
+                }
+            };
+
+            for answer in records.answers {
+                let ip_addr = match answer.rdata() {
+                    RData::A(ip) => IpAddr::V4(*ip),
+                    RData::AAAA(ip) => IpAddr::V6(*ip),
+                    _ => continue,
+                };
+
+                if self.ip_version.supports(&ip_addr) && !ip_addrs.contains(&ip_addr) {
+                    ip_addrs.push(ip_addr);
+                }
+            }
+        }
+
+        // Failed lookups are only reported if none of the name servers could
+        // be resolved.
+        match (ip_addrs.is_empty(), error) {
+            (false, _) => Ok(ip_addrs),
+            (true, Some(err)) => Err(err),
+            (true, None) => Err(ResolverError::NoGlueRecords),
+        }
+    }
+
+    /// Returns the usable addresses of all root servers. The server which
+    /// is asked first is selected via the infrastructure cache.
+    pub fn hint(&self) -> Vec<IpAddr> {
+        self.hints
+            .iter()
+            .flat_map(|hint| hint.ip_addrs(self.ip_version))
+            .collect()
+    }
+}
+
+/// Tracks the QNAME minimisation state of a single lookup.
+struct Minimisation {
+    enabled: bool,
+
+    /// The number of labels of the query name sent to the next server
+    labels: usize,
+
+    /// The number of additional queries sent so far
+    iterations: usize,
+}
+
+impl Minimisation {
+    fn new(enabled: bool) -> Self {
+        Self {
+            labels: 1,
+            iterations: 0,
+            enabled,
+        }
+    }
+
+    /// Returns the minimised version of `query`, or `None` if the full query
+    /// has to be sent. The query type of minimised queries is A, as some
+    /// servers don't handle NS queries for names below a zone cut
+    /// correctly. See RFC 9156 Section 3.
+    fn query(&self, query: &Query) -> Option<Query> {
+        if !self.enabled || self.labels >= query.name.num_labels() {
+            return None;
+        }
+
+        Some(Query::new(
+            query.name.ancestor(self.labels),
+            RType::A,
+            query.class,
+        ))
+    }
+
+    /// Moves on to the delegated `zone`. The next query includes one label
+    /// below the zone.
+    fn referral(&mut self, zone: &Name) {
+        // Referrals to zones above the current name still add one label to
+        // make sure the lookup terminates.
+        self.labels = zone.num_labels().max(self.labels) + 1;
+    }
+
+    /// Adds one more label to the query name sent to the same zone. The full
+    /// name is sent once the limit of additional queries is reached.
+    fn extend(&mut self) {
+        self.iterations += 1;
+        self.labels += 1;
 
-        if *hint_index >= self.hints.len() {
-            *hint_index = 0;
+        if self.iterations >= MAX_MINIMISE_COUNT {
+            self.disable();
         }
+    }
+
+    /// Sends the full query name from now on.
+    fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    /// Handles the `rcode` of a response to a minimised query, which is no
+    /// referral. Returns if the same server is asked again. See
+    /// [RFC 9156 Section 3](https://datatracker.ietf.org/doc/html/rfc9156#section-3).
+    fn response(&mut self, rcode: Rcode) -> bool {
+        match rcode {
+            // There is no zone cut at the minimised name, the next query
+            // includes one more label.
+            Rcode::NoError => {
+                self.extend();
+                true
+            }
+            // Broken servers return NXDOMAIN for empty non-terminals, which
+            // is why the full name is sent instead of returning the error.
+            Rcode::NameError => {
+                self.disable();
+                true
+            }
+            // The server failed (e.g. SERVFAIL or REFUSED). This says
+            // nothing about the name, another server has to be asked.
+            _ => false,
+        }
+    }
+}
+
+/// Follows the CNAME and DNAME records of a single query across the
+/// responses of multiple zones.
+struct Chain {
+    ty: RType,
+
+    /// The lowercase name whose records are looked for next
+    name: Name,
+
+    /// All names of the chain so far, used to detect loops
+    visited: Vec<Name>,
+
+    /// The alias records of the chain in order
+    records: Vec<Record>,
+}
+
+impl Chain {
+    fn new(query: &Query) -> Self {
+        let name = query.name.to_lowercase();
+
+        Self {
+            visited: vec![name.clone()],
+            records: Vec::new(),
+            ty: query.ty,
+            name,
+        }
+    }
+
+    /// Follows the aliases in the answers of `response`. Returns the name
+    /// which has to be looked up next if the chain continues in another
+    /// zone. Otherwise the chain ends and the answers of `response` are
+    /// replaced by the complete chain.
+    fn follow(&mut self, response: &mut ResultRecords) -> Result<Option<Name>, ResolverError> {
+        let mut answers = std::mem::take(&mut response.answers);
+        let mut followed = false;
+
+        loop {
+            let mut rrset = take_rrset(&mut answers, &self.name, self.ty);
+            if !rrset.is_empty() {
+                self.records.append(&mut rrset);
+                response.answers = self.finish();
+                return Ok(None);
+            }
+
+            let target = match next_alias(&mut answers, &self.name) {
+                Some((mut records, target)) => {
+                    self.records.append(&mut records);
+                    target.to_lowercase()
+                }
+                None => break,
+            };
+
+            if self.visited.contains(&target) {
+                return Err(ResolverError::AliasLoop(target));
+            }
+
+            if self.visited.len() > MAX_CHAIN_LENGTH {
+                return Err(ResolverError::AliasChainTooLong);
+            }
+
+            self.visited.push(target.clone());
+            self.name = target;
+            followed = true;
+        }
+
+        // The chain ends with a negative answer of the last zone or there is
+        // no alias at all. Otherwise the target is part of another zone,
+        // which has to be asked separately.
+        let negative =
+            response.rcode != Rcode::NoError || response.authorities.iter().any(|r| r.is_soa());
+
+        if !followed || negative {
+            response.answers = match self.records.is_empty() {
+                true => answers,
+                false => self.finish(),
+            };
+
+            return Ok(None);
+        }
+
+        Ok(Some(self.name.clone()))
+    }
+
+    /// Returns the records of the chain. Their TTLs are clamped to the
+    /// lowest TTL of the chain, as the answer is only valid as long as
+    /// every link of the chain is.
+    fn finish(&mut self) -> Vec<Record> {
+        let mut records = std::mem::take(&mut self.records);
+
+        if let Some(ttl) = records.iter().map(|r| r.header().ttl()).min() {
+            for record in &mut records {
+                record.set_header_ttl(ttl);
+            }
+        }
+
+        records
+    }
+}
+
+/// Returns if `message` is a referral to a delegated zone, which contains NS
+/// records in the authority section but neither answers nor a SOA record.
+fn is_referral(message: &Message) -> bool {
+    message.ancount() == 0
+        && message.rcode() == Rcode::NoError
+        && !message.is_soa()
+        && message
+            .authorities()
+            .iter()
+            .any(|r| *r.header().ty() == RType::NS)
+}
+
+/// Returns the record types used to look up the addresses of name servers
+/// with `ip_version`.
+pub(crate) fn address_types(ip_version: IpVersion) -> &'static [RType] {
+    match ip_version {
+        IpVersion::Both => &[RType::A, RType::AAAA],
+        IpVersion::V4 => &[RType::A],
+        IpVersion::V6 => &[RType::AAAA],
+    }
+}
+
+/// Removes the records of type `ty` at `name` and the RRSIGs covering them
+/// from `records` and returns them.
+fn take_rrset(records: &mut Vec<Record>, name: &Name, ty: RType) -> Vec<Record> {
+    let (rrset, rest) = records.drain(..).partition(|record| {
+        let covered = match record.rdata() {
+            RData::RRSIG(rrsig) => rrsig.type_covered(),
+            _ => *record.header().ty(),
+        };
+
+        covered == ty && record.header().name().to_lowercase() == *name
+    });
+
+    *records = rest;
+    rrset
+}
+
+/// Removes the alias of `name` from `answers` and returns it together with
+/// its target. For DNAME records the CNAME is always synthesized, CNAMEs
+/// sent by the server are ignored. See
+/// [RFC 6672 Section 3.4](https://datatracker.ietf.org/doc/html/rfc6672#section-3.4).
+fn next_alias(answers: &mut Vec<Record>, name: &Name) -> Option<(Vec<Record>, Name)> {
+    let dname = answers
+        .iter()
+        .filter_map(|r| match r.rdata() {
+            RData::DNAME(target) => Some((r.header().name().to_lowercase(), target, r)),
+            _ => None,
+        })
+        .filter(|(owner, _, _)| {
+            name.num_labels() > owner.num_labels() && name.is_subdomain_of(owner)
+        })
+        .max_by_key(|(owner, _, _)| owner.num_labels())
+        .map(|(owner, target, r)| (owner, target.clone(), r.header().ttl(), *r.header().class()));
+
+    if let Some((owner, target, ttl, class)) = dname {
+        let target = name.replace_suffix(&owner, &target)?;
+
+        let mut records = take_rrset(answers, &owner, RType::DNAME);
+        take_rrset(answers, name, RType::CNAME);
+
+        // The synthesized CNAME uses the TTL of the DNAME, see RFC 6672
+        // Section 3.3.
+        let mut header = RHeader::new();
+        header.set_name(name.clone());
+        header.set_ty(RType::CNAME);
+        header.set_class(class);
+        header.set_ttl(ttl);
+
+        let mut cname = Record::new_with_header(header);
+        cname
+            .set_rdata(RData::CNAME(target.clone()))
+            .normalize_rdlen();
+
+        records.push(cname);
+        return Some((records, target));
+    }
+
+    let records = take_rrset(answers, name, RType::CNAME);
+    let target = records.iter().find_map(|r| match r.rdata() {
+        RData::CNAME(target) => Some(target.clone()),
+        _ => None,
+    })?;
+
+    Some((records, target))
+}
+
+#[cfg(test)]
+mod test {
+    use portal_proto::Class;
+
+    use super::*;
+
+    fn query(name: &str) -> Query {
+        Query::new(Name::try_from(name).unwrap(), RType::AAAA, Class::IN)
+    }
+
+    fn name(name: &str) -> Name {
+        Name::try_from(name).unwrap()
+    }
+
+    fn record(owner: &str, ttl: u32, rdata: RData) -> Record {
+        let ty = match &rdata {
+            RData::A(_) => RType::A,
+            RData::CNAME(_) => RType::CNAME,
+            RData::DNAME(_) => RType::DNAME,
+            _ => unreachable!(),
+        };
+
+        let mut header = RHeader::new();
+        header.set_name(name(owner));
+        header.set_ty(ty);
+        header.set_class(Class::IN);
+        header.set_ttl(ttl);
+
+        let mut record = Record::new_with_header(header);
+        record.set_rdata(rdata).normalize_rdlen();
+        record
+    }
+
+    fn a(owner: &str, ttl: u32) -> Record {
+        record(owner, ttl, RData::A(Ipv4Addr::new(192, 0, 2, 1)))
+    }
+
+    fn cname(owner: &str, ttl: u32, target: &str) -> Record {
+        record(owner, ttl, RData::CNAME(name(target)))
+    }
+
+    fn response(answers: Vec<Record>) -> ResultRecords {
+        ResultRecords {
+            answers,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_take_rrset() {
+        let mut records = vec![
+            a("WWW.example.com", 300),
+            cname("alias.example.com", 300, "www.example.com"),
+            a("www.example.com", 300),
+            a("other.example.com", 300),
+        ];
+
+        let rrset = take_rrset(&mut records, &name("www.example.com"), RType::A);
+        assert_eq!(rrset.len(), 2);
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_next_alias_dname() {
+        let mut answers = vec![
+            record("example.com", 300, RData::DNAME(name("example.net"))),
+            // The CNAME sent by the server is replaced by the synthesized one
+            cname("www.example.com", 0, "www.example.net"),
+        ];
+
+        let (records, target) = next_alias(&mut answers, &name("www.example.com")).unwrap();
+        assert_eq!(target, name("www.example.net"));
+        assert!(answers.is_empty());
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(*records[1].header().ty(), RType::CNAME);
+        assert_eq!(records[1].header().name(), &name("www.example.com"));
+        assert_eq!(records[1].header().ttl(), 300);
+    }
+
+    #[test]
+    fn test_chain_cross_zone() {
+        let query = Query::new(name("www.example.com"), RType::A, Class::IN);
+        let mut chain = Chain::new(&query);
+
+        // The target is part of another zone, which is asked next
+        let mut first = response(vec![cname("www.example.com", 600, "www.example.net")]);
+        let next = chain.follow(&mut first).unwrap();
+        assert_eq!(next, Some(name("www.example.net")));
+
+        let mut second = response(vec![a("www.example.net", 60)]);
+        assert_eq!(chain.follow(&mut second).unwrap(), None);
+
+        // The TTLs of the chain are clamped to the lowest one
+        assert_eq!(second.answers.len(), 2);
+        assert_eq!(*second.answers[0].header().ty(), RType::CNAME);
+        assert_eq!(*second.answers[1].header().ty(), RType::A);
+        assert!(second.answers.iter().all(|r| r.header().ttl() == 60));
+    }
+
+    #[test]
+    fn test_chain_dname_ttl() {
+        let query = Query::new(name("www.example.com"), RType::A, Class::IN);
+        let mut chain = Chain::new(&query);
+
+        let mut response = response(vec![
+            record("example.com", 120, RData::DNAME(name("example.net"))),
+            a("www.example.net", 300),
+        ]);
+        assert_eq!(chain.follow(&mut response).unwrap(), None);
+
+        let types: Vec<RType> = response.answers.iter().map(|r| *r.header().ty()).collect();
+        assert_eq!(types, vec![RType::DNAME, RType::CNAME, RType::A]);
+        assert!(response.answers.iter().all(|r| r.header().ttl() == 120));
+    }
+
+    #[test]
+    fn test_chain_alias_loop() {
+        let query = Query::new(name("a.example.com"), RType::A, Class::IN);
+        let mut chain = Chain::new(&query);
+
+        let mut response = response(vec![
+            cname("a.example.com", 300, "b.example.com"),
+            cname("b.example.com", 300, "A.example.com"),
+        ]);
+
+        assert!(matches!(
+            chain.follow(&mut response),
+            Err(ResolverError::AliasLoop(target)) if target == name("a.example.com")
+        ));
+    }
+
+    #[test]
+    fn test_chain_max_length() {
+        let aliases = |count: usize| {
+            let mut answers: Vec<Record> = (0..count)
+                .map(|i| {
+                    cname(
+                        &format!("a{i}.example.com"),
+                        300,
+                        &format!("a{}.example.com", i + 1),
+                    )
+                })
+                .collect();
+
+            answers.push(a(&format!("a{count}.example.com"), 300));
+            response(answers)
+        };
+
+        let query = Query::new(name("a0.example.com"), RType::A, Class::IN);
+
+        let mut response = aliases(MAX_CHAIN_LENGTH);
+        assert_eq!(Chain::new(&query).follow(&mut response).unwrap(), None);
+        assert_eq!(response.answers.len(), MAX_CHAIN_LENGTH + 1);
+
+        let mut response = aliases(MAX_CHAIN_LENGTH + 1);
+        assert!(matches!(
+            Chain::new(&query).follow(&mut response),
+            Err(ResolverError::AliasChainTooLong)
+        ));
+    }
+
+    fn minimised_name(minimisation: &Minimisation, query: &Query) -> Option<String> {
+        minimisation
+            .query(query)
+            .map(|minimised| minimised.name.to_string())
+    }
+
+    #[test]
+    fn test_minimisation_query() {
+        let query = query("a.b.example.com");
+
+        let disabled = Minimisation::new(false);
+        assert!(disabled.query(&query).is_none());
+
+        let minimisation = Minimisation::new(true);
+        let minimised = minimisation.query(&query).unwrap();
+        assert_eq!(minimised.name.to_string(), "com.");
+        assert_eq!(minimised.ty, RType::A);
+    }
+
+    #[test]
+    fn test_minimisation_referral() {
+        let query = query("a.b.example.com");
+        let mut minimisation = Minimisation::new(true);
+
+        minimisation.referral(&Name::try_from("example.com").unwrap());
+        assert_eq!(
+            minimised_name(&minimisation, &query).as_deref(),
+            Some("b.example.com.")
+        );
+
+        // The full name is sent once the zone is one label below it
+        minimisation.referral(&Name::try_from("b.example.com").unwrap());
+        assert!(minimisation.query(&query).is_none());
+    }
+
+    #[test]
+    fn test_minimisation_extend_limit() {
+        let query = query(&["a"; 20].join("."));
+        let mut minimisation = Minimisation::new(true);
+
+        for _ in 0..MAX_MINIMISE_COUNT - 1 {
+            minimisation.extend();
+            assert!(minimisation.query(&query).is_some());
+        }
+
+        minimisation.extend();
+        assert!(minimisation.query(&query).is_none());
+    }
+
+    #[test]
+    fn test_minimisation_response() {
+        let query = query("a.b.example.com");
+        let mut minimisation = Minimisation::new(true);
+
+        assert!(minimisation.response(Rcode::NoError));
+        assert_eq!(
+            minimised_name(&minimisation, &query).as_deref(),
+            Some("example.com.")
+        );
 
-        let hint = self.hints.get(*hint_index).unwrap();
-        *hint_index += 1;
+        // Failing servers don't change the minimised name
+        assert!(!minimisation.response(Rcode::ServerFailure));
+        assert!(!minimisation.response(Rcode::Refused));
+        assert_eq!(
+            minimised_name(&minimisation, &query).as_deref(),
+            Some("example.com.")
+        );
 
-        IpAddr::V4(hint.ipv4_addr)
+        // NXDOMAIN falls back to the full name
+        assert!(minimisation.response(Rcode::NameError));
+        assert!(minimisation.query(&query).is_none());
     }
 }
@@ -0,0 +1,915 @@
+use std::net::Ipv4Addr;
+
+use async_trait::async_trait;
+use binbuf::prelude::*;
+use portal_proto::{
+    Class, Name, Query, RData, RHeader, RType, Rcode, Record, TypeBitMaps, DNSKEY, DS, NSEC, NSEC3,
+    RRSIG,
+};
+use portal_resolver::{
+    Bogus, Lookup, ResolveResult, ResultRecords, Security, TrustAnchors, Validator,
+};
+use ring::{
+    digest,
+    rand::SystemRandom,
+    signature::{
+        EcdsaKeyPair, EcdsaSigningAlgorithm, Ed25519KeyPair, KeyPair,
+        ECDSA_P256_SHA256_FIXED_SIGNING, ECDSA_P384_SHA384_FIXED_SIGNING,
+    },
+};
+
+const NOW: u32 = 1_700_000_000;
+const INCEPTION: u32 = NOW - 3600;
+const EXPIRATION: u32 = NOW + 86400;
+
+const RSASHA256: u8 = 8;
+const ECDSAP256SHA256: u8 = 13;
+const ECDSAP384SHA384: u8 = 14;
+const ED25519: u8 = 15;
+
+/// A 2048 bit RSA public key of `example.` in the DNSKEY wire format, which
+/// was generated with OpenSSL. The signatures below were created with the
+/// private key, they are valid between [`INCEPTION`] and [`EXPIRATION`].
+const RSA_PUBLIC_KEY: &str = concat!(
+    "03010001da70f2801b34705725a14e142536f2bff35616694a02323fe7f012f9ff570005",
+    "35594299eb0f60f7d985f7570820e211bd396c8db3201ae2a59c54a4e1dcf57749af06c9",
+    "27c48d39f9b2bcad5b0418085e71a1952c30c5f072cc9c777d038a23d512e196bd32431a",
+    "0d1aab2edfc52c78e739ddf47a8998d6a974fef315746c808c5190554dd68e7d9e8e514e",
+    "16eb9a01e8bfcd5246fb22d64e6ee5c063e79948e5e0d3bc84a288fbfd8f8ec3233b37f1",
+    "2c6fc04007a5dbc24c4e46896913e4ac00d40ee8a11f10513c396ebdc292a739558ce067",
+    "6dd7375d17720cc8eb734f03dce8d2bfd1b6c37cc5f2e9a47f4610f8057d73361ea5895f",
+    "026d2524bdbfced3",
+);
+
+/// The signature of the DNSKEY RRset of `example.`
+const RSA_DNSKEY_SIGNATURE: &str = concat!(
+    "3e5d4b3bda8348410b087bf33fe97a9d96a10c6dff7df264b8ce074e01dd38a4e4394ce9",
+    "bf865f69f4986cb61bbffae1e18cbfe82978ebd5766ac60abe9ea11a495e4dac61c8a3a7",
+    "31b4fa06b271260f4b2cdb81349428bd4d0cf0fff0bed8531855257dbcecd475a2a1602c",
+    "eb61250f5586a9d12a17e4ec78c3f1ec8b8b141862368647185d52d54b4dc8bafc4aa20b",
+    "d9ab46b91bb86b0ff1b0492fcddbee2b1ba0605d4b282d6dee181c13365f8789f77e313c",
+    "bc58e9a6c11ef1f2cd60450238df24b19d96ccf2989eb62497f66ddbab3b8c83a5d1ab88",
+    "6578986a9dcc727683ab5b70dbfc197a02e84e145950df5d7fa2c80bb3f0f9e0d87a7f78",
+    "2a656a2f",
+);
+
+/// The signature of the A RRset of `www.example.` with the address 192.0.2.1
+const RSA_A_SIGNATURE: &str = concat!(
+    "6d6aa01ee8cbb4ea9a1e2ae1f78e24021c8a2005b1db8193de35ee7674d8d61541165180",
+    "1f7f2e7964f93ea62375ff287e3fe6b1a3d00f87dd4633d96dad3c346000bebb356271f1",
+    "ca23bd4529f2e5f75193b311c9140e69be5da33707f1b057263b41c0717209010d4c5818",
+    "fe99a2caeb9d496ad323d4ea768b112bc3d7c64d4d2f59ddda8ec6e0f3d017dab990f815",
+    "135af09dba09ec646daca54681ce16f62c07d3c828eb3b41eb5cdb0bd270dd927d32a971",
+    "483902c7cd4211597df4d7078614366c569a5c7339a4cfd6e04b74cee1dcc5743ff6f230",
+    "9e38910e0da4c2a8d032ab5a0e369c638f6960e7cdf8b274707856763522a4c714aec3f4",
+    "a7a3bd97",
+);
+
+enum SigningKey {
+    Ed25519(Ed25519KeyPair),
+    Ecdsa(EcdsaKeyPair),
+}
+
+/// A zone signing key using Ed25519 (algorithm 15) or ECDSA (algorithm 13
+/// and 14)
+struct Signer {
+    zone: Name,
+    key: SigningKey,
+    dnskey: DNSKEY,
+}
+
+impl Signer {
+    fn new(zone: &str) -> Self {
+        Self::with_algorithm(zone, ED25519)
+    }
+
+    fn with_algorithm(zone: &str, algorithm: u8) -> Self {
+        let rng = SystemRandom::new();
+
+        let (key, public_key) = match algorithm {
+            ED25519 => {
+                let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+                let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+                let public_key = key_pair.public_key().as_ref().to_vec();
+
+                (SigningKey::Ed25519(key_pair), public_key)
+            }
+            ECDSAP256SHA256 | ECDSAP384SHA384 => {
+                let signing: &EcdsaSigningAlgorithm = match algorithm {
+                    ECDSAP256SHA256 => &ECDSA_P256_SHA256_FIXED_SIGNING,
+                    _ => &ECDSA_P384_SHA384_FIXED_SIGNING,
+                };
+
+                let pkcs8 = EcdsaKeyPair::generate_pkcs8(signing, &rng).unwrap();
+                let key_pair = EcdsaKeyPair::from_pkcs8(signing, pkcs8.as_ref(), &rng).unwrap();
+
+                // DNSKEY records store the point without the leading 0x04,
+                // see RFC 6605 Section 4
+                let public_key = key_pair.public_key().as_ref()[1..].to_vec();
+
+                (SigningKey::Ecdsa(key_pair), public_key)
+            }
+            _ => panic!("unsupported algorithm {algorithm}"),
+        };
+
+        Self {
+            zone: name(zone),
+            key,
+            dnskey: DNSKEY::new(
+                DNSKEY::FLAG_ZONE | DNSKEY::FLAG_SEP,
+                3,
+                algorithm,
+                public_key,
+            ),
+        }
+    }
+
+    fn ds(&self) -> DS {
+        ds(&self.zone, &self.dnskey)
+    }
+
+    /// Signs the RRset `records`, which is valid between [`INCEPTION`] and
+    /// [`EXPIRATION`].
+    fn sign(&self, records: &[Record]) -> Record {
+        let (mut rrsig, data) = rrsig(records, &self.dnskey, &self.zone);
+
+        let signature = match &self.key {
+            SigningKey::Ed25519(key_pair) => key_pair.sign(&data).as_ref().to_vec(),
+            SigningKey::Ecdsa(key_pair) => key_pair
+                .sign(&SystemRandom::new(), &data)
+                .unwrap()
+                .as_ref()
+                .to_vec(),
+        };
+
+        rrsig.set_signature(signature);
+        rrsig_record(records, rrsig)
+    }
+}
+
+/// Serves the records of the fixture zones. Missing types are answered with
+/// NODATA and missing names with NXDOMAIN, unless they are empty
+/// non-terminals or covered by a wildcard. Answers synthesized from a
+/// wildcard and all negative answers include all NSEC and NSEC3 records.
+struct Fixture {
+    records: Vec<Record>,
+}
+
+impl Fixture {
+    /// Builds a signed root zone with the signed delegation `example.`, which
+    /// uses NSEC records and contains the wildcard `*.wild.example.` and the
+    /// unsigned delegation `unsigned.example.`.
+    fn new(root: &Signer, example: &Signer) -> Self {
+        let mut fixture = Self::zones(root, example);
+
+        fixture.add(
+            example,
+            vec![record(
+                "*.wild.example.",
+                RType::A,
+                RData::A(Ipv4Addr::new(192, 0, 2, 3)),
+            )],
+        );
+        fixture.add(
+            example,
+            vec![nsec(
+                "example.",
+                "unsigned.example.",
+                &[
+                    RType::SOA,
+                    RType::NS,
+                    RType::DNSKEY,
+                    RType::NSEC,
+                    RType::RRSIG,
+                ],
+            )],
+        );
+        fixture.add(
+            example,
+            vec![nsec(
+                "unsigned.example.",
+                "*.wild.example.",
+                &[RType::NS, RType::NSEC, RType::RRSIG],
+            )],
+        );
+        fixture.add(
+            example,
+            vec![nsec(
+                "*.wild.example.",
+                "www.example.",
+                &[RType::A, RType::NSEC, RType::RRSIG],
+            )],
+        );
+        fixture.add(
+            example,
+            vec![nsec(
+                "www.example.",
+                "example.",
+                &[RType::A, RType::NSEC, RType::RRSIG],
+            )],
+        );
+
+        fixture
+    }
+
+    /// Builds the same zones as [`Fixture::new`] without the wildcard, but
+    /// `example.` uses NSEC3 records with an empty salt. With opt-out, the
+    /// unsigned delegation has no NSEC3 record.
+    fn new_nsec3(root: &Signer, example: &Signer, iterations: u16, opt_out: bool) -> Self {
+        let mut fixture = Self::zones(root, example);
+
+        let mut names = vec![
+            (
+                "example.",
+                vec![
+                    RType::SOA,
+                    RType::NS,
+                    RType::DNSKEY,
+                    RType::NSEC3PARAM,
+                    RType::RRSIG,
+                ],
+            ),
+            ("www.example.", vec![RType::A, RType::RRSIG]),
+        ];
+
+        if !opt_out {
+            names.push(("unsigned.example.", vec![RType::NS]));
+        }
+
+        let mut hashes: Vec<(Vec<u8>, Vec<RType>)> = names
+            .into_iter()
+            .map(|(owner, types)| (nsec3_hash(&name(owner), iterations), types))
+            .collect();
+        hashes.sort();
+
+        let flags = match opt_out {
+            true => NSEC3::FLAG_OPT_OUT,
+            false => 0,
+        };
+
+        for (index, (hash, types)) in hashes.iter().enumerate() {
+            let next = &hashes[(index + 1) % hashes.len()].0;
+            let nsec3 = NSEC3::new(
+                1,
+                flags,
+                iterations,
+                Vec::new(),
+                next.clone(),
+                TypeBitMaps::new(types.iter().copied()),
+            );
+
+            fixture.add(
+                example,
+                vec![record(
+                    &format!("{}.example.", base32hex(hash)),
+                    RType::NSEC3,
+                    RData::NSEC3(nsec3),
+                )],
+            );
+        }
+
+        fixture
+    }
+
+    /// Returns the records shared by all fixtures, without any denial of
+    /// existence records.
+    fn zones(root: &Signer, example: &Signer) -> Self {
+        let mut fixture = Self {
+            records: Vec::new(),
+        };
+
+        fixture.add(
+            root,
+            vec![record(
+                ".",
+                RType::DNSKEY,
+                RData::DNSKEY(root.dnskey.clone()),
+            )],
+        );
+        fixture.add(
+            root,
+            vec![record("example.", RType::DS, RData::DS(example.ds()))],
+        );
+
+        fixture.add(
+            example,
+            vec![record(
+                "example.",
+                RType::DNSKEY,
+                RData::DNSKEY(example.dnskey.clone()),
+            )],
+        );
+        fixture.add(
+            example,
+            vec![record(
+                "www.example.",
+                RType::A,
+                RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+            )],
+        );
+
+        fixture.records.push(record(
+            "unsigned.example.",
+            RType::NS,
+            RData::NS(name("ns.unsigned.example.")),
+        ));
+        fixture.records.push(record(
+            "host.unsigned.example.",
+            RType::A,
+            RData::A(Ipv4Addr::new(192, 0, 2, 2)),
+        ));
+
+        fixture
+    }
+
+    fn add(&mut self, signer: &Signer, rrset: Vec<Record>) {
+        self.records.push(signer.sign(&rrset));
+        self.records.extend(rrset);
+    }
+
+    fn answer(&self, query: &Query) -> ResultRecords {
+        let name = query.name.to_lowercase();
+
+        let covers = |record: &Record, ty: RType| match record.rdata() {
+            RData::RRSIG(rrsig) => rrsig.type_covered() == ty,
+            _ => *record.header().ty() == ty,
+        };
+
+        let select = |owner: Option<&Name>, ty: RType| -> Vec<Record> {
+            self.records
+                .iter()
+                .filter(|r| owner.map_or(true, |owner| r.header().name() == owner))
+                .filter(|r| covers(r, ty))
+                .cloned()
+                .collect()
+        };
+
+        let answers = select(Some(&name), query.ty);
+        if !answers.is_empty() {
+            return ResultRecords {
+                answers,
+                ..Default::default()
+            };
+        }
+
+        let mut denial = select(None, RType::NSEC);
+        denial.extend(select(None, RType::NSEC3));
+
+        let wildcard = name.parent().map(|parent| parent.to_wildcard());
+        let expanded: Vec<Record> = match &wildcard {
+            Some(wildcard) => select(Some(wildcard), query.ty),
+            None => Vec::new(),
+        };
+
+        if !expanded.is_empty() {
+            let answers = expanded
+                .into_iter()
+                .map(|mut record| {
+                    record.set_header_name(name.clone());
+                    record
+                })
+                .collect();
+
+            return ResultRecords {
+                answers,
+                authorities: denial,
+                ..Default::default()
+            };
+        }
+
+        let exists = self.records.iter().any(|r| {
+            let owner = r.header().name();
+            owner.is_subdomain_of(&name) || Some(owner) == wildcard.as_ref()
+        });
+
+        match exists {
+            true => ResultRecords {
+                authorities: denial,
+                ..Default::default()
+            },
+            false => ResultRecords {
+                rcode: Rcode::NameError,
+                authorities: denial,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Lookup for Fixture {
+    async fn lookup(&self, query: Query) -> ResolveResult {
+        Ok(self.answer(&query))
+    }
+}
+
+/// Returns the DS record of `dnskey`, which uses SHA-256 as the digest.
+fn ds(zone: &Name, dnskey: &DNSKEY) -> DS {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(&wire_name(zone));
+    ctx.update(&dnskey.flags().to_be_bytes());
+    ctx.update(&[dnskey.protocol(), dnskey.algorithm()]);
+    ctx.update(dnskey.public_key());
+
+    DS::new(
+        dnskey.key_tag(),
+        dnskey.algorithm(),
+        2,
+        ctx.finish().as_ref().to_vec(),
+    )
+}
+
+/// Returns the unsigned RRSIG of `records` made with `dnskey` of `zone` and
+/// the data which has to be signed.
+fn rrsig(records: &[Record], dnskey: &DNSKEY, zone: &Name) -> (RRSIG, Vec<u8>) {
+    let header = records[0].header();
+
+    // The wildcard label is not counted, see RFC 4034 Section 3.1.3
+    let mut labels = header.name().num_labels();
+    if header.name().is_wildcard() {
+        labels -= 1;
+    }
+
+    let rrsig = RRSIG::new(
+        *header.ty(),
+        dnskey.algorithm(),
+        labels as u8,
+        header.ttl(),
+        EXPIRATION,
+        INCEPTION,
+        dnskey.key_tag(),
+        zone.clone(),
+    );
+
+    let mut buf = WriteBuffer::new();
+    rrsig.write_signed_fields::<BigEndian>(&mut buf).unwrap();
+    let mut data = buf.owned_bytes();
+
+    let mut rdatas: Vec<Vec<u8>> = records
+        .iter()
+        .map(|r| r.rdata().canonical_bytes().unwrap())
+        .collect();
+    rdatas.sort();
+
+    for rdata in rdatas {
+        data.extend(wire_name(header.name()));
+        data.extend(u16::from(header.ty()).to_be_bytes());
+        data.extend(u16::from(header.class()).to_be_bytes());
+        data.extend(header.ttl().to_be_bytes());
+        data.extend((rdata.len() as u16).to_be_bytes());
+        data.extend(rdata);
+    }
+
+    (rrsig, data)
+}
+
+fn rrsig_record(records: &[Record], rrsig: RRSIG) -> Record {
+    record(
+        &records[0].header().name().to_string(),
+        RType::RRSIG,
+        RData::RRSIG(rrsig),
+    )
+}
+
+fn name(name: &str) -> Name {
+    Name::try_from(name).unwrap()
+}
+
+fn record(owner: &str, ty: RType, rdata: RData) -> Record {
+    let mut header = RHeader::new();
+    header.set_name(name(owner));
+    header.set_ty(ty);
+    header.set_class(Class::IN);
+    header.set_ttl(3600);
+
+    let mut record = Record::new_with_header(header);
+    record.set_rdata(rdata).normalize_rdlen();
+    record
+}
+
+fn nsec(owner: &str, next: &str, types: &[RType]) -> Record {
+    let types = TypeBitMaps::new(types.iter().copied());
+    record(
+        owner,
+        RType::NSEC,
+        RData::NSEC(NSEC::new(name(next), types)),
+    )
+}
+
+fn wire_name(name: &Name) -> Vec<u8> {
+    let mut wire = Vec::new();
+
+    for label in name.iter() {
+        wire.push(label.len() as u8);
+        wire.extend(label.0.to_ascii_lowercase());
+    }
+
+    wire.push(0);
+    wire
+}
+
+/// Returns the NSEC3 hash of `name` using SHA-1 and an empty salt.
+fn nsec3_hash(name: &Name, iterations: u16) -> Vec<u8> {
+    let mut hash = digest::digest(&digest::SHA1_FOR_LEGACY_USE_ONLY, &wire_name(name));
+
+    for _ in 0..iterations {
+        hash = digest::digest(&digest::SHA1_FOR_LEGACY_USE_ONLY, hash.as_ref());
+    }
+
+    hash.as_ref().to_vec()
+}
+
+/// Encodes `data` as base32hex without padding, which is used for the owner
+/// names of NSEC3 records.
+fn base32hex(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuv";
+
+    let mut text = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for &byte in data {
+        buffer = buffer << 8 | byte as u32;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            text.push(ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+    }
+
+    if bits > 0 {
+        text.push(ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+
+    text
+}
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn setup() -> (Signer, Signer, Validator) {
+    setup_with(ED25519)
+}
+
+/// Returns the root zone signer, the signer of `example.` using `algorithm`
+/// and a validator trusting the root zone.
+fn setup_with(algorithm: u8) -> (Signer, Signer, Validator) {
+    let root = Signer::new(".");
+    let example = Signer::with_algorithm("example.", algorithm);
+
+    let anchor = record(".", RType::DS, RData::DS(root.ds()));
+    let anchors = TrustAnchors::from_records([&anchor]).unwrap();
+
+    (root, example, Validator::new(anchors))
+}
+
+async fn validate(fixture: &Fixture, validator: &Validator, query: Query, now: u32) -> Security {
+    let records = fixture.answer(&query);
+    validator
+        .validate_at(fixture, &query, &records, now)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_validate_secure_answer() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new(&root, &example);
+
+    let query = Query::new(name("www.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_tampered_answer() {
+    let (root, example, validator) = setup();
+    let mut fixture = Fixture::new(&root, &example);
+
+    for record in &mut fixture.records {
+        if *record.header().ty() == RType::A && record.header().name() == &name("www.example.") {
+            record.set_rdata(RData::A(Ipv4Addr::new(203, 0, 113, 1)));
+        }
+    }
+
+    let query = Query::new(name("www.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Bogus(Bogus::InvalidSignature(name("www.example."), RType::A))
+    );
+}
+
+#[tokio::test]
+async fn test_validate_expired_signature() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new(&root, &example);
+
+    let query = Query::new(name("www.example."), RType::A, Class::IN);
+    let security = validate(&fixture, &validator, query, EXPIRATION + 1).await;
+
+    assert!(matches!(
+        security,
+        Security::Bogus(Bogus::SignatureExpired(..))
+    ));
+}
+
+#[tokio::test]
+async fn test_validate_missing_rrsigs() {
+    let (root, example, validator) = setup();
+    let mut fixture = Fixture::new(&root, &example);
+
+    fixture.records.retain(|r| match r.rdata() {
+        RData::RRSIG(rrsig) => rrsig.type_covered() != RType::A,
+        _ => true,
+    });
+
+    let query = Query::new(name("www.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Bogus(Bogus::RrsigsMissing(name("www.example."), RType::A))
+    );
+}
+
+#[tokio::test]
+async fn test_validate_nxdomain() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new(&root, &example);
+
+    let query = Query::new(name("nope.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_nodata() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new(&root, &example);
+
+    let query = Query::new(name("www.example."), RType::AAAA, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_missing_denial() {
+    let (root, example, validator) = setup();
+    let mut fixture = Fixture::new(&root, &example);
+
+    // Without the NSEC record of www.example. the NODATA answer can't be
+    // proven.
+    fixture.records.retain(|r| {
+        let is_nsec = match r.rdata() {
+            RData::NSEC(_) => true,
+            RData::RRSIG(rrsig) => rrsig.type_covered() == RType::NSEC,
+            _ => false,
+        };
+
+        !is_nsec || r.header().name() != &name("www.example.")
+    });
+
+    let query = Query::new(name("www.example."), RType::AAAA, Class::IN);
+    assert!(validate(&fixture, &validator, query, NOW).await.is_bogus());
+}
+
+#[tokio::test]
+async fn test_validate_insecure_delegation() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new(&root, &example);
+
+    let query = Query::new(name("host.unsigned.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Insecure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_ecdsa_p256() {
+    let (root, example, validator) = setup_with(ECDSAP256SHA256);
+    let fixture = Fixture::new(&root, &example);
+
+    let query = Query::new(name("www.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+
+    let query = Query::new(name("nope.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_ecdsa_p384() {
+    let (root, example, validator) = setup_with(ECDSAP384SHA384);
+    let fixture = Fixture::new(&root, &example);
+
+    let query = Query::new(name("www.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+
+    let query = Query::new(name("nope.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_rsa_sha256() {
+    let (root, _, validator) = setup();
+
+    let example = name("example.");
+    let dnskey = DNSKEY::new(
+        DNSKEY::FLAG_ZONE | DNSKEY::FLAG_SEP,
+        3,
+        RSASHA256,
+        hex(RSA_PUBLIC_KEY),
+    );
+
+    let mut fixture = Fixture {
+        records: Vec::new(),
+    };
+
+    fixture.add(
+        &root,
+        vec![record(
+            ".",
+            RType::DNSKEY,
+            RData::DNSKEY(root.dnskey.clone()),
+        )],
+    );
+    fixture.add(
+        &root,
+        vec![record(
+            "example.",
+            RType::DS,
+            RData::DS(ds(&example, &dnskey)),
+        )],
+    );
+
+    let rrsets = [
+        (
+            record("example.", RType::DNSKEY, RData::DNSKEY(dnskey.clone())),
+            RSA_DNSKEY_SIGNATURE,
+        ),
+        (
+            record(
+                "www.example.",
+                RType::A,
+                RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+            ),
+            RSA_A_SIGNATURE,
+        ),
+    ];
+
+    for (rrset, signature) in rrsets {
+        let rrset = vec![rrset];
+        let (mut rrsig, _) = rrsig(&rrset, &dnskey, &example);
+        rrsig.set_signature(hex(signature));
+
+        fixture.records.push(rrsig_record(&rrset, rrsig));
+        fixture.records.extend(rrset);
+    }
+
+    let query = Query::new(name("www.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query.clone(), NOW).await,
+        Security::Secure
+    );
+
+    for record in &mut fixture.records {
+        if *record.header().ty() == RType::A {
+            record.set_rdata(RData::A(Ipv4Addr::new(203, 0, 113, 1)));
+        }
+    }
+
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Bogus(Bogus::InvalidSignature(name("www.example."), RType::A))
+    );
+}
+
+#[tokio::test]
+async fn test_validate_nsec3_nxdomain() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new_nsec3(&root, &example, 0, false);
+
+    let query = Query::new(name("nope.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_nsec3_nodata() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new_nsec3(&root, &example, 0, false);
+
+    let query = Query::new(name("www.example."), RType::AAAA, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+
+    let query = Query::new(name("host.unsigned.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Insecure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_nsec3_opt_out() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new_nsec3(&root, &example, 0, true);
+
+    // The unsigned delegation is only covered by an opt-out NSEC3 record
+    let query = Query::new(name("host.unsigned.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Insecure
+    );
+
+    let query = Query::new(name("nope.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Insecure
+    );
+
+    let query = Query::new(name("www.example."), RType::AAAA, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_nsec3_too_many_iterations() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new_nsec3(&root, &example, 500, false);
+
+    let query = Query::new(name("nope.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Insecure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_wildcard_expansion() {
+    let (root, example, validator) = setup();
+    let fixture = Fixture::new(&root, &example);
+
+    let query = Query::new(name("host.wild.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+
+    let query = Query::new(name("host.wild.example."), RType::AAAA, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Secure
+    );
+}
+
+#[tokio::test]
+async fn test_validate_wildcard_missing_denial() {
+    let (root, example, validator) = setup();
+    let mut fixture = Fixture::new(&root, &example);
+
+    // Without the NSEC record covering host.wild.example. the expansion
+    // can't be proven.
+    fixture.records.retain(|r| {
+        let is_nsec = match r.rdata() {
+            RData::NSEC(_) => true,
+            RData::RRSIG(rrsig) => rrsig.type_covered() == RType::NSEC,
+            _ => false,
+        };
+
+        !is_nsec || r.header().name() != &name("*.wild.example.")
+    });
+
+    let query = Query::new(name("host.wild.example."), RType::A, Class::IN);
+    assert_eq!(
+        validate(&fixture, &validator, query, NOW).await,
+        Security::Bogus(Bogus::NsecMissing(name("host.wild.example.")))
+    );
+}
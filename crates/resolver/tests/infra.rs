@@ -0,0 +1,104 @@
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::{Duration, Instant},
+};
+
+use portal_resolver::InfraCache;
+
+fn server(last: u8) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(192, 0, 2, last))
+}
+
+#[test]
+fn test_record_rtt_smoothing() {
+    let cache = InfraCache::default();
+    let now = Instant::now();
+
+    cache.record_rtt_at(server(1), Duration::from_millis(80), now);
+    assert_eq!(
+        cache.get(&server(1)).unwrap().srtt,
+        Duration::from_millis(80)
+    );
+
+    cache.record_rtt_at(server(1), Duration::from_millis(160), now);
+    assert_eq!(
+        cache.get(&server(1)).unwrap().srtt,
+        Duration::from_millis(90)
+    );
+}
+
+#[test]
+fn test_select_fastest() {
+    let cache = InfraCache::default();
+    let now = Instant::now();
+
+    cache.record_rtt_at(server(1), Duration::from_millis(900), now);
+    cache.record_rtt_at(server(2), Duration::from_millis(20), now);
+
+    let candidates = [server(1), server(2)];
+    assert_eq!(cache.select_at(&candidates, now), Some(server(2)));
+    assert_eq!(cache.select_at(&[], now), None);
+}
+
+#[test]
+fn test_select_unknown_before_slow() {
+    let cache = InfraCache::default();
+    let now = Instant::now();
+
+    cache.record_rtt_at(server(1), Duration::from_millis(1500), now);
+
+    let candidates = [server(1), server(2)];
+    assert_eq!(cache.select_at(&candidates, now), Some(server(2)));
+}
+
+#[test]
+fn test_timeout_backoff() {
+    let cache = InfraCache::default();
+    let now = Instant::now();
+
+    cache.record_rtt_at(server(1), Duration::from_millis(10), now);
+    cache.record_rtt_at(server(2), Duration::from_millis(1000), now);
+
+    for _ in 0..3 {
+        cache.record_timeout_at(server(1), now);
+    }
+
+    let stats = cache.get(&server(1)).unwrap();
+    assert_eq!(stats.timeouts, 3);
+    assert_eq!(stats.backoff_until, Some(now + Duration::from_secs(5)));
+
+    // The backed off server is skipped while there is a healthy one
+    let candidates = [server(1), server(2)];
+    assert_eq!(cache.select_at(&candidates, now), Some(server(2)));
+    assert_eq!(cache.select_at(&[server(1)], now), Some(server(1)));
+
+    // A response resets the timeouts and the backoff
+    let later = now + Duration::from_secs(6);
+    cache.record_rtt_at(server(1), Duration::from_millis(10), later);
+
+    let stats = cache.get(&server(1)).unwrap();
+    assert_eq!(stats.timeouts, 0);
+    assert_eq!(stats.backoff_until, None);
+    assert_eq!(cache.select_at(&candidates, later), Some(server(1)));
+}
+
+#[test]
+fn test_max_entries() {
+    let cache = InfraCache::new(2);
+    let now = Instant::now();
+
+    cache.record_rtt_at(server(1), Duration::from_millis(10), now);
+    cache.record_rtt_at(
+        server(2),
+        Duration::from_millis(10),
+        now + Duration::from_secs(1),
+    );
+    cache.record_rtt_at(
+        server(3),
+        Duration::from_millis(10),
+        now + Duration::from_secs(2),
+    );
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&server(1)).is_none());
+}
@@ -0,0 +1,337 @@
+use portal_proto::{Name, RData, RType, Rcode, Record, Tree, Zone, ZoneError};
+
+use crate::{config::ZoneOptions, ServerError};
+
+/// The maximum number of CNAME records followed within a zone.
+const MAX_CNAME_CHAIN: usize = 8;
+
+/// The [`Authority`] holds all zones which are served authoritatively.
+/// Queries for names in one of these zones are answered locally and never
+/// reach the resolver.
+#[derive(Debug, Default)]
+pub struct Authority {
+    zones: Vec<AuthoritativeZone>,
+}
+
+impl Authority {
+    /// Loads the master files of all configured `zones`.
+    pub fn load(zones: &[ZoneOptions]) -> Result<Self, ServerError> {
+        let zones = zones
+            .iter()
+            .map(|options| AuthoritativeZone::new(Zone::from_file(options.path.clone())?))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { zones })
+    }
+
+    /// Returns the closest zone enclosing `name`, if any.
+    pub fn find(&self, name: &Name) -> Option<&AuthoritativeZone> {
+        self.zones
+            .iter()
+            .filter(|zone| name.is_subdomain_of(&zone.origin))
+            .max_by_key(|zone| zone.origin.num_labels())
+    }
+}
+
+/// The result of an authoritative lookup.
+#[derive(Debug)]
+pub struct Answer {
+    pub rcode: Rcode,
+
+    /// This is `false` for referrals to a delegated zone.
+    pub authoritative: bool,
+    pub answers: Vec<Record>,
+    pub authorities: Vec<Record>,
+    pub additionals: Vec<Record>,
+}
+
+impl Default for Answer {
+    fn default() -> Self {
+        Self {
+            rcode: Rcode::NoError,
+            authoritative: true,
+            answers: Vec::new(),
+            authorities: Vec::new(),
+            additionals: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AuthoritativeZone {
+    origin: Name,
+    soa: Record,
+    zone: Zone,
+}
+
+impl AuthoritativeZone {
+    /// Creates a new authoritative zone. The apex is the owner of the SOA
+    /// record. Owner names are stored lowercase, which makes lookups case
+    /// insensitive.
+    pub fn new(zone: Zone) -> Result<Self, ServerError> {
+        let mut tree = Tree::new();
+        let mut soa = None;
+
+        for (name, node) in zone.tree.canonical_nodes() {
+            for record in node.records() {
+                if record.is_soa() && soa.is_none() {
+                    soa = Some(record.clone());
+                }
+
+                tree.insert(name.to_lowercase(), record.clone())
+                    .map_err(ZoneError::from)?;
+            }
+        }
+
+        let soa = soa.ok_or(ServerError::MissingSoa)?;
+
+        Ok(Self {
+            origin: soa.header().name().to_lowercase(),
+            zone: Zone { tree },
+            soa,
+        })
+    }
+
+    /// Looks up the records of type `ty` at `name` as described in
+    /// [RFC 1034 Section 4.3.2](https://datatracker.ietf.org/doc/html/rfc1034#section-4.3.2).
+    /// CNAME records are followed as long as the target is part of the zone.
+    pub fn lookup(&self, name: &Name, ty: RType) -> Answer {
+        let mut answer = Answer::default();
+        let mut visited = vec![name.to_lowercase()];
+
+        for _ in 0..MAX_CNAME_CHAIN {
+            let target = match self.lookup_name(visited.last().unwrap(), ty, &mut answer) {
+                Some(target) => target.to_lowercase(),
+                None => break,
+            };
+
+            // Targets outside of the zone are left to the client, loops are
+            // cut short.
+            if !target.is_subdomain_of(&self.origin) || visited.contains(&target) {
+                break;
+            }
+
+            visited.push(target);
+        }
+
+        let mut glue = self.zone.glue(&answer.answers);
+        answer.additionals.append(&mut glue);
+
+        answer
+    }
+
+    /// Looks up a single `name` and adds the result to `answer`. Returns the
+    /// target if `name` is an alias.
+    fn lookup_name(&self, name: &Name, ty: RType, answer: &mut Answer) -> Option<Name> {
+        let lookup = self.zone.tree.lookup(name);
+
+        // Zone cuts below the apex end the lookup with a referral, except for
+        // DS records, which are part of the parent side of the cut.
+        if let Some((cut, node)) = &lookup.zone_cut {
+            if *cut != self.origin && !(ty == RType::DS && cut == name) {
+                self.refer(node.records(), answer);
+                return None;
+            }
+        }
+
+        let records: Vec<Record> = match (lookup.node(), lookup.wildcard) {
+            (Some(node), _) => node.records().clone(),
+            // Wildcard records are synthesized with the owner name of the
+            // query. See RFC 4592 Section 3.3.1.
+            (None, Some(wildcard)) => wildcard
+                .records()
+                .iter()
+                .map(|record| {
+                    let mut record = record.clone();
+                    record.set_header_name(name.clone());
+                    record
+                })
+                .collect(),
+            (None, None) => {
+                answer.rcode = Rcode::NameError;
+                self.add_soa(answer);
+                return None;
+            }
+        };
+
+        let mut matching: Vec<Record> = records
+            .iter()
+            .filter(|r| *r.header().ty() == ty)
+            .cloned()
+            .collect();
+
+        if !matching.is_empty() {
+            answer.answers.append(&mut matching);
+            return None;
+        }
+
+        if let Some(cname) = records.iter().find(|r| *r.header().ty() == RType::CNAME) {
+            answer.answers.push(cname.clone());
+
+            return match cname.rdata() {
+                RData::CNAME(target) => Some(target.clone()),
+                _ => None,
+            };
+        }
+
+        // The name exists, but has no records of type `ty`. This includes
+        // empty non-terminals.
+        self.add_soa(answer);
+        None
+    }
+
+    /// Adds a referral to the delegated zone with the NS `records` to
+    /// `answer`. Addresses of name servers within the zone are added as glue.
+    fn refer(&self, records: &[Record], answer: &mut Answer) {
+        let ns: Vec<&Record> = records
+            .iter()
+            .filter(|r| *r.header().ty() == RType::NS)
+            .collect();
+
+        for record in &ns {
+            let target = match record.rdata() {
+                RData::NS(target) => target.to_lowercase(),
+                _ => continue,
+            };
+
+            if !target.is_subdomain_of(&self.origin) {
+                continue;
+            }
+
+            if let Some(node) = self.zone.tree.find_node(target) {
+                answer.additionals.extend(
+                    node.records()
+                        .iter()
+                        .filter(|r| matches!(r.header().ty(), RType::A | RType::AAAA))
+                        .cloned(),
+                );
+            }
+        }
+
+        answer.authoritative = false;
+        answer.authorities.extend(ns.into_iter().cloned());
+    }
+
+    /// Adds the SOA record to the authority section of negative answers. The
+    /// TTL is capped by the MINIMUM field. See
+    /// [RFC 2308 Section 3](https://datatracker.ietf.org/doc/html/rfc2308#section-3).
+    fn add_soa(&self, answer: &mut Answer) {
+        let mut soa = self.soa.clone();
+
+        if let RData::SOA(rdata) = soa.rdata() {
+            let ttl = soa.header().ttl().min(rdata.get_minimum());
+            soa.set_header_ttl(ttl);
+        }
+
+        answer.authorities.push(soa);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    const ZONE: &str = "$ORIGIN example.com.
+$TTL 3600
+@       IN  SOA ns1 hostmaster 1 7200 900 1209600 300
+@       IN  NS  ns1
+ns1     IN  A   192.0.2.1
+www     IN  A   192.0.2.2
+alias   IN  CNAME www
+loop1   IN  CNAME loop2
+loop2   IN  CNAME loop1
+ext     IN  CNAME www.example.org.
+*.wild  IN  A   192.0.2.3
+a.b     IN  A   192.0.2.4
+sub     IN  NS  ns.sub
+ns.sub  IN  A   192.0.2.5
+";
+
+    fn zone() -> AuthoritativeZone {
+        AuthoritativeZone::new(Zone::from_str(ZONE).unwrap()).unwrap()
+    }
+
+    fn name(name: &str) -> Name {
+        Name::try_from(name).unwrap()
+    }
+
+    #[test]
+    fn test_lookup_answer() {
+        let answer = zone().lookup(&name("WWW.example.com."), RType::A);
+
+        assert_eq!(answer.rcode, Rcode::NoError);
+        assert!(answer.authoritative);
+        assert_eq!(answer.answers.len(), 1);
+        assert!(answer.authorities.is_empty());
+    }
+
+    #[test]
+    fn test_lookup_cname_chain() {
+        let zone = zone();
+
+        let answer = zone.lookup(&name("alias.example.com."), RType::A);
+        let types: Vec<RType> = answer.answers.iter().map(|r| *r.header().ty()).collect();
+        assert_eq!(types, vec![RType::CNAME, RType::A]);
+
+        let answer = zone.lookup(&name("loop1.example.com."), RType::A);
+        assert_eq!(answer.answers.len(), 2);
+
+        let answer = zone.lookup(&name("ext.example.com."), RType::A);
+        assert_eq!(answer.answers.len(), 1);
+        assert_eq!(answer.rcode, Rcode::NoError);
+    }
+
+    #[test]
+    fn test_lookup_wildcard() {
+        let answer = zone().lookup(&name("foo.wild.example.com."), RType::A);
+
+        assert_eq!(answer.answers.len(), 1);
+        assert_eq!(
+            answer.answers[0].header().name(),
+            &name("foo.wild.example.com.")
+        );
+    }
+
+    #[test]
+    fn test_lookup_negative() {
+        let zone = zone();
+
+        let answer = zone.lookup(&name("missing.example.com."), RType::A);
+        assert_eq!(answer.rcode, Rcode::NameError);
+        assert!(answer.authorities[0].is_soa());
+        assert_eq!(answer.authorities[0].header().ttl(), 300);
+
+        // Empty non-terminals exist
+        let answer = zone.lookup(&name("b.example.com."), RType::A);
+        assert_eq!(answer.rcode, Rcode::NoError);
+        assert!(answer.answers.is_empty());
+        assert!(answer.authorities[0].is_soa());
+
+        let answer = zone.lookup(&name("www.example.com."), RType::AAAA);
+        assert_eq!(answer.rcode, Rcode::NoError);
+        assert!(answer.answers.is_empty());
+        assert!(answer.authorities[0].is_soa());
+    }
+
+    #[test]
+    fn test_lookup_referral() {
+        let answer = zone().lookup(&name("host.sub.example.com."), RType::A);
+
+        assert!(!answer.authoritative);
+        assert!(answer.answers.is_empty());
+        assert_eq!(answer.authorities.len(), 1);
+        assert_eq!(answer.additionals.len(), 1);
+    }
+
+    #[test]
+    fn test_authority_find() {
+        let authority = Authority {
+            zones: vec![zone()],
+        };
+
+        assert!(authority.find(&name("www.example.com.")).is_some());
+        assert!(authority.find(&name("example.org.")).is_none());
+    }
+}
@@ -1,41 +1,292 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
     time::{Duration, Instant},
 };
 
-use portal_proto::{Name, RType, Record};
+use portal_proto::{Class, Name, RData, RType, Record};
 
 mod status;
 
 pub use status::*;
 
-// FIXME (Techassi): This idealy should be a tree I guess. But I'm not quite sure if the tree is faster. Also is a
-// hashmap the fastest solution? This cache also naively does NOT store any expiration timestamp. Once a record is
-// stored, it will never be invalidated. The current implementation is only a placeholder and is only in place so
-// that the server is comparable to other DNS servers (with caching).
+/// Records are cached per (name, type, class). The name is always stored in
+/// lowercase, as domain name comparisons are case-insensitive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    name: Name,
+    ty: RType,
+    class: Class,
+
+    /// Set for the key of NXDOMAIN answers, which never collides with the
+    /// key of a regular query (including ANY queries).
+    nxdomain: bool,
+}
+
+impl CacheKey {
+    pub fn new(name: &Name, ty: RType, class: Class) -> Self {
+        Self {
+            name: name.to_lowercase(),
+            nxdomain: false,
+            ty,
+            class,
+        }
+    }
+
+    /// NXDOMAIN answers are valid for every type of the name, so they are
+    /// cached once per (name, class). See RFC 2308 Section 5.
+    fn nxdomain(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            ty: RType::ANY,
+            class: self.class,
+            nxdomain: true,
+        }
+    }
+}
+
+/// The different kinds of negative answers. See
+/// [RFC 2308](https://datatracker.ietf.org/doc/html/rfc2308#section-2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Negative {
+    /// The queried name does not exist.
+    NxDomain,
+
+    /// The queried name exists, but has no records of the queried type.
+    NoData,
+}
+
+/// An answer retrieved from the cache.
+#[derive(Debug)]
+pub enum CachedAnswer {
+    /// The cached records, which belong in the answer section.
+    Records(Vec<Record>),
+
+    /// A cached negative answer. The SOA record of the zone belongs in the
+    /// authority section of the response.
+    Negative(Negative, Record),
+}
+
+/// A TTL-aware response cache. Cached records count their TTL down on every
+/// lookup and are removed once expired. When the cache reaches the maximum
+/// number of entries, the least recently used entry is evicted.
+///
+/// The cache can be shared between tasks, all methods take `&self`.
 pub struct Cache {
-    inner: HashMap<Name, HashMap<RType, Record>>,
-    // inner: Tree,
+    inner: Mutex<CacheInner>,
+}
+
+struct CacheInner {
+    entries: HashMap<CacheKey, CacheEntry>,
+
+    /// Keeps track of the usage order. The key is a monotonic counter which
+    /// is bumped on every insert and hit. The first entry is the least
+    /// recently used one.
+    lru: BTreeMap<u64, CacheKey>,
+    tick: u64,
+
+    /// The maximum number of cached entries
+    max_entries: usize,
+
+    /// The upper limit of the TTL used to cache records, regardless of the
+    /// TTL provided by the record.
+    max_expire: Duration,
+}
+
+struct CacheEntry {
+    negative: Option<Negative>,
+    records: Vec<CachedRecord>,
+    expires_at: Instant,
+    tick: u64,
 }
 
 impl Cache {
-    pub fn insert(&mut self, name: Name, records: Vec<Record>) {
-        let mut cached_records: Vec<CachedRecord> = Vec::new();
+    /// Creates a new cache which stores at most `max_entries` entries. Records
+    /// are cached for at most `max_expire`.
+    pub fn new(max_entries: usize, max_expire: Duration) -> Self {
+        Self {
+            inner: Mutex::new(CacheInner {
+                entries: HashMap::new(),
+                lru: BTreeMap::new(),
+                tick: 0,
+                max_entries,
+                max_expire,
+            }),
+        }
+    }
+
+    /// Inserts the `records` for `key` into the cache. Existing records for
+    /// the same key are replaced. The entry expires once the record with the
+    /// lowest TTL expires.
+    pub fn insert(&self, key: CacheKey, records: Vec<Record>) {
+        let records = records
+            .into_iter()
+            .map(|record| {
+                let ttl = record.header().ttl();
+                (record, ttl)
+            })
+            .collect();
+
+        self.insert_entry(key, None, records)
+    }
+
+    /// Inserts a negative answer for `key` into the cache. The `soa` record
+    /// has to be the SOA from the authority section of the response. The
+    /// negative answer is cached for the minimum of the SOA TTL and the SOA
+    /// MINIMUM field. See RFC 2308 Section 5.
+    pub fn insert_negative(&self, key: CacheKey, negative: Negative, soa: Record) {
+        let minimum = match soa.rdata() {
+            RData::SOA(rdata) => rdata.get_minimum(),
+            _ => return,
+        };
+
+        let ttl = soa.header().ttl().min(minimum);
+        let key = match negative {
+            Negative::NxDomain => key.nxdomain(),
+            Negative::NoData => key,
+        };
+
+        self.insert_entry(key, Some(negative), vec![(soa, ttl)])
+    }
+
+    fn insert_entry(&self, key: CacheKey, negative: Option<Negative>, records: Vec<(Record, u32)>) {
+        if records.is_empty() {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.max_entries == 0 {
+            return;
+        }
 
         // NOTE (Techassi): This can introduce inaccurate expire timestamps,
         // but we avoid calling Instant::now() on every added record.
         let now = Instant::now();
+        let max_expire = inner.max_expire;
+
+        let records: Vec<CachedRecord> = records
+            .into_iter()
+            .map(|(record, ttl)| {
+                let ttl = Duration::from_secs(ttl.into()).min(max_expire);
+                CachedRecord {
+                    expires_at: now + ttl,
+                    record,
+                }
+            })
+            .collect();
 
-        for record in records {
-            // TODO (Techassi): Handle this error
-            let expires_at = now
-                .checked_add(Duration::from_secs(record.header().ttl().into()))
-                .unwrap();
+        // Records with the same key form an RRset, which shares one TTL.
+        let expires_at = records.iter().map(|r| r.expires_at).min().unwrap_or(now);
 
-            cached_records.push(CachedRecord { expires_at, record })
+        inner.remove(&key);
+
+        while inner.entries.len() >= inner.max_entries {
+            if !inner.evict_lru() {
+                break;
+            }
         }
 
-        // self.inner.insert_multi(name, &mut cached_records);
+        let tick = inner.next_tick();
+        inner.lru.insert(tick, key.clone());
+        inner.entries.insert(
+            key,
+            CacheEntry {
+                negative,
+                records,
+                expires_at,
+                tick,
+            },
+        );
+    }
+
+    /// Looks up the answer for `key`. A cached NXDOMAIN for the name is
+    /// returned for every type. On a [`Status::Hit`] the returned records
+    /// carry the remaining TTL. Expired entries are removed and reported as
+    /// [`Status::Expired`].
+    pub fn lookup(&self, key: &CacheKey) -> (Status, Option<CachedAnswer>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        match inner.lookup(key) {
+            (Status::Miss, _) => inner.lookup(&key.nxdomain()),
+            result => result,
+        }
+    }
+
+    /// Returns the number of cached entries, including expired entries
+    /// which were not looked up yet.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    /// Returns if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheInner {
+    fn lookup(&mut self, key: &CacheKey) -> (Status, Option<CachedAnswer>) {
+        let now = Instant::now();
+
+        let expires_at = match self.entries.get(key) {
+            Some(entry) => entry.expires_at,
+            None => return (Status::Miss, None),
+        };
+
+        if expires_at <= now {
+            self.remove(key);
+            return (Status::Expired, None);
+        }
+
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(key).unwrap();
+        let previous_tick = std::mem::replace(&mut entry.tick, tick);
+
+        let mut records: Vec<Record> = entry
+            .records
+            .iter()
+            .map(|cached| {
+                let mut record = cached.record.clone();
+                record.set_header_ttl(cached.ttl(now));
+                record
+            })
+            .collect();
+
+        let answer = match entry.negative {
+            Some(negative) => CachedAnswer::Negative(negative, records.remove(0)),
+            None => CachedAnswer::Records(records),
+        };
+
+        self.lru.remove(&previous_tick);
+        self.lru.insert(tick, key.clone());
+
+        (Status::Hit, Some(answer))
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.lru.remove(&entry.tick);
+        }
+    }
+
+    /// Evicts the least recently used entry. Returns false if the cache is
+    /// empty.
+    fn evict_lru(&mut self) -> bool {
+        let tick = match self.lru.keys().next() {
+            Some(tick) => *tick,
+            None => return false,
+        };
+
+        if let Some(key) = self.lru.remove(&tick) {
+            self.entries.remove(&key);
+        }
+
+        true
     }
 }
 
@@ -52,4 +303,190 @@ impl CachedRecord {
     pub fn get_record(&self) -> &Record {
         &self.record
     }
+
+    /// Returns the remaining TTL in seconds at `now`.
+    pub fn ttl(&self, now: Instant) -> u32 {
+        self.expires_at
+            .saturating_duration_since(now)
+            .as_secs()
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+
+    use binbuf::prelude::*;
+    use portal_proto::{RData, RHeader, SOA};
+
+    use super::*;
+
+    fn a_record(name: &str, ttl: u32) -> Record {
+        let mut header = RHeader::new();
+        header.set_name(Name::try_from(name).unwrap());
+        header.set_ty(RType::A);
+        header.set_ttl(ttl);
+
+        let mut record = Record::new_with_header(header);
+        record.set_rdata(RData::A(Ipv4Addr::new(127, 0, 0, 1)));
+        record
+    }
+
+    fn soa_record(name: &str, ttl: u32, minimum: u32) -> Record {
+        let mut header = RHeader::new();
+        header.set_name(Name::try_from(name).unwrap());
+        header.set_ty(RType::SOA);
+        header.set_ttl(ttl);
+
+        // MNAME and RNAME are the root, followed by SERIAL, REFRESH, RETRY,
+        // EXPIRE and MINIMUM.
+        let mut bytes = vec![0, 0];
+        for field in [1u32, 7200, 900, 1209600, minimum] {
+            bytes.extend_from_slice(&field.to_be_bytes());
+        }
+
+        let soa = SOA::read_be(&mut ReadBuffer::new(&bytes)).unwrap();
+
+        let mut record = Record::new_with_header(header);
+        record.set_rdata(RData::SOA(soa));
+        record
+    }
+
+    fn key(name: &str) -> CacheKey {
+        CacheKey::new(&Name::try_from(name).unwrap(), RType::A, Class::IN)
+    }
+
+    fn records(answer: Option<CachedAnswer>) -> Vec<Record> {
+        match answer {
+            Some(CachedAnswer::Records(records)) => records,
+            answer => panic!("expected cached records, got {answer:?}"),
+        }
+    }
+
+    #[test]
+    fn cache_hit_and_miss() {
+        let cache = Cache::new(10, Duration::from_secs(300));
+        cache.insert(key("example.com"), vec![a_record("example.com", 60)]);
+
+        let (status, answer) = cache.lookup(&key("EXAMPLE.com"));
+        assert_eq!(status, Status::Hit);
+
+        let records = records(answer);
+        assert_eq!(records.len(), 1);
+        assert!(records[0].header().ttl() <= 60);
+
+        let (status, answer) = cache.lookup(&key("example.org"));
+        assert_eq!(status, Status::Miss);
+        assert!(answer.is_none());
+    }
+
+    #[test]
+    fn cache_max_expire() {
+        let cache = Cache::new(10, Duration::from_secs(30));
+        cache.insert(key("example.com"), vec![a_record("example.com", 3600)]);
+
+        let (_, answer) = cache.lookup(&key("example.com"));
+        assert!(records(answer)[0].header().ttl() <= 30);
+    }
+
+    #[test]
+    fn cache_expired() {
+        let cache = Cache::new(10, Duration::from_secs(300));
+        cache.insert(key("example.com"), vec![a_record("example.com", 0)]);
+
+        let (status, _) = cache.lookup(&key("example.com"));
+        assert_eq!(status, Status::Expired);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn cache_evicts_lru() {
+        let cache = Cache::new(2, Duration::from_secs(300));
+        cache.insert(key("a.example.com"), vec![a_record("a.example.com", 60)]);
+        cache.insert(key("b.example.com"), vec![a_record("b.example.com", 60)]);
+
+        // Use a.example.com, which makes b.example.com the LRU entry
+        cache.lookup(&key("a.example.com"));
+        cache.insert(key("c.example.com"), vec![a_record("c.example.com", 60)]);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.lookup(&key("a.example.com")).0, Status::Hit);
+        assert_eq!(cache.lookup(&key("b.example.com")).0, Status::Miss);
+        assert_eq!(cache.lookup(&key("c.example.com")).0, Status::Hit);
+    }
+
+    #[test]
+    fn cache_nxdomain() {
+        let cache = Cache::new(10, Duration::from_secs(300));
+        cache.insert_negative(
+            key("nope.example.com"),
+            Negative::NxDomain,
+            soa_record("example.com", 3600, 60),
+        );
+
+        // NXDOMAIN applies to all types of the name
+        let aaaa = CacheKey::new(
+            &Name::try_from("nope.example.com").unwrap(),
+            RType::AAAA,
+            Class::IN,
+        );
+
+        match cache.lookup(&aaaa) {
+            (Status::Hit, Some(CachedAnswer::Negative(Negative::NxDomain, soa))) => {
+                // The TTL is the minimum of the SOA TTL and MINIMUM field
+                assert!(soa.header().ttl() <= 60);
+            }
+            answer => panic!("expected cached NXDOMAIN, got {answer:?}"),
+        }
+    }
+
+    #[test]
+    fn cache_nodata() {
+        let cache = Cache::new(10, Duration::from_secs(300));
+        cache.insert_negative(
+            key("example.com"),
+            Negative::NoData,
+            soa_record("example.com", 30, 3600),
+        );
+
+        match cache.lookup(&key("example.com")) {
+            (Status::Hit, Some(CachedAnswer::Negative(Negative::NoData, soa))) => {
+                assert!(soa.header().ttl() <= 30);
+            }
+            answer => panic!("expected cached NODATA, got {answer:?}"),
+        }
+
+        // NODATA only applies to the queried type
+        let aaaa = CacheKey::new(
+            &Name::try_from("example.com").unwrap(),
+            RType::AAAA,
+            Class::IN,
+        );
+        assert_eq!(cache.lookup(&aaaa).0, Status::Miss);
+    }
+
+    #[test]
+    fn cache_any_not_used_as_nxdomain() {
+        let cache = Cache::new(10, Duration::from_secs(300));
+        let any = CacheKey::new(
+            &Name::try_from("example.com").unwrap(),
+            RType::ANY,
+            Class::IN,
+        );
+
+        // Only NXDOMAIN answers apply to other types of the name
+        cache.insert(any.clone(), vec![a_record("example.com", 60)]);
+        assert_eq!(cache.lookup(&any).0, Status::Hit);
+        assert_eq!(cache.lookup(&key("example.com")).0, Status::Miss);
+
+        cache.insert_negative(
+            any.clone(),
+            Negative::NoData,
+            soa_record("example.com", 30, 3600),
+        );
+        assert_eq!(cache.lookup(&any).0, Status::Hit);
+        assert_eq!(cache.lookup(&key("example.com")).0, Status::Miss);
+    }
 }
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::config::{ResolverOptionError, ServerOptionError};
+use crate::config::{ResolverOptionError, ServerOptionError, ZoneOptionError};
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -21,4 +21,7 @@ pub enum ConfigError {
 
     #[error("Error while validating server options: {0}")]
     ServerOptionError(#[from] ServerOptionError),
+
+    #[error("Error while validating zone options: {0}")]
+    ZoneOptionError(#[from] ZoneOptionError),
 }
@@ -13,6 +13,7 @@ use toml;
 pub struct Config {
     pub resolver: ResolverOptions,
     pub server: ServerOptions,
+    pub zones: Vec<ZoneOptions>,
 }
 
 #[derive(Deserialize, Default)]
@@ -21,6 +22,9 @@ pub struct RawConfig {
     pub collector: RawCollectorOptions,
     pub resolver: RawResolverOptions,
     pub server: RawServerOptions,
+
+    /// Zones which are served authoritatively, see the `[[zones]]` sections
+    pub zones: Vec<RawZoneOptions>,
 }
 
 impl RawConfig {
@@ -45,9 +49,20 @@ impl RawConfig {
             Err(err) => return Err(ConfigError::ServerOptionError(err)),
         };
 
+        let zone_opts = match self
+            .zones
+            .iter()
+            .map(|zone| zone.validate())
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(opts) => opts,
+            Err(err) => return Err(ConfigError::ZoneOptionError(err)),
+        };
+
         Ok(Config {
             resolver: resolver_opts,
             server: server_opts,
+            zones: zone_opts,
         })
     }
 }
@@ -1,7 +1,9 @@
 mod collector;
 mod resolver;
 mod server;
+mod zone;
 
 pub use collector::*;
 pub use resolver::*;
 pub use server::*;
+pub use zone::*;
@@ -1,5 +1,6 @@
 use std::net::{AddrParseError, IpAddr, Ipv4Addr, SocketAddr};
 
+use portal_common::{IpVersion, IpVersionError};
 use portal_resolver::{ResolveMode, ResolveModeError};
 use serde::Deserialize;
 use thiserror::Error;
@@ -11,34 +12,57 @@ pub enum ResolverOptionError {
 
     #[error("Resolve mode parse error: {0}")]
     ResolveModeParseError(#[from] ResolveModeError),
+
+    #[error("IP version parse error: {0}")]
+    IpVersionParseError(#[from] IpVersionError),
 }
 
 pub struct ResolverOptions {
     pub upstream: SocketAddr,
     pub cache_enabled: bool,
+    pub cache_max_entries: usize,
     pub max_expire: usize,
     pub hint_file_path: String,
+
+    /// The path to the DNSSEC trust anchors. Validation is disabled if this
+    /// is `None`.
+    pub trust_anchor_path: Option<String>,
     pub mode: ResolveMode,
+
+    /// The address families used to contact name servers.
+    pub ip_version: IpVersion,
+
+    /// Send only the labels of the query name required by each server, see
+    /// RFC 9156.
+    pub qname_minimisation: bool,
 }
 
 #[derive(Deserialize)]
 #[serde(default)]
 pub struct RawResolverOptions {
     pub cache_enabled: bool,
+    pub cache_max_entries: usize,
     pub max_expire: usize,
     pub hint_file_path: String,
+    pub trust_anchor_path: String,
     pub upstream: String,
     pub mode: String,
+    pub ip_version: String,
+    pub qname_minimisation: bool,
 }
 
 impl Default for RawResolverOptions {
     fn default() -> Self {
         Self {
             cache_enabled: true,
+            cache_max_entries: 10000,
             max_expire: 300,
             hint_file_path: String::from(""),
+            trust_anchor_path: String::from(""),
             upstream: String::from(""),
             mode: String::from("r"),
+            ip_version: String::from("both"),
+            qname_minimisation: false,
         }
     }
 }
@@ -46,6 +70,7 @@ impl Default for RawResolverOptions {
 impl RawResolverOptions {
     pub fn validate(&self) -> Result<ResolverOptions, ResolverOptionError> {
         let mode: ResolveMode = self.mode.parse()?;
+        let ip_version: IpVersion = self.ip_version.parse()?;
 
         // Only parse the upstream addr when we use the forwarding resolver.
         // Otherwise fallback to 0.0.0.0:0
@@ -54,11 +79,21 @@ impl RawResolverOptions {
             _ => SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 0),
         };
 
+        // An empty path disables DNSSEC validation
+        let trust_anchor_path = match self.trust_anchor_path.is_empty() {
+            true => None,
+            false => Some(self.trust_anchor_path.clone()),
+        };
+
         Ok(ResolverOptions {
             upstream,
             cache_enabled: self.cache_enabled,
+            cache_max_entries: self.cache_max_entries,
             max_expire: self.max_expire,
             hint_file_path: self.hint_file_path.clone(),
+            qname_minimisation: self.qname_minimisation,
+            trust_anchor_path,
+            ip_version,
             mode,
         })
     }
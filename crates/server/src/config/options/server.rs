@@ -1,6 +1,10 @@
-use std::net::{AddrParseError, SocketAddr};
+use std::{
+    net::{AddrParseError, SocketAddr},
+    time::Duration,
+};
 
 use portal_common::{Network, NetworkError};
+use portal_proto::constants::MIN_MESSAGE_SIZE;
 use serde::Deserialize;
 use thiserror::Error;
 
@@ -17,6 +21,9 @@ pub struct ServerOptions {
     pub cache_enabled: bool,
     pub address: SocketAddr,
     pub network: Network,
+    pub tcp_idle_timeout: Duration,
+    pub tcp_max_queries: usize,
+    pub udp_max_payload_size: u16,
 }
 
 #[derive(Deserialize)]
@@ -25,6 +32,16 @@ pub struct RawServerOptions {
     pub cache_enabled: bool,
     pub address: String,
     pub network: String,
+
+    /// Idle timeout of TCP connections in seconds
+    pub tcp_idle_timeout: u64,
+
+    /// Maximum number of queries answered per TCP connection
+    pub tcp_max_queries: usize,
+
+    /// Maximum size of UDP responses in octets. The UDP payload size
+    /// advertised by clients via EDNS is capped by this value.
+    pub udp_max_payload_size: u16,
 }
 
 impl Default for RawServerOptions {
@@ -33,6 +50,9 @@ impl Default for RawServerOptions {
             cache_enabled: true,
             address: String::from("127.0.0.1:53"),
             network: String::from("udp"),
+            tcp_idle_timeout: 10,
+            tcp_max_queries: 100,
+            udp_max_payload_size: 1232,
         }
     }
 }
@@ -43,6 +63,9 @@ impl RawServerOptions {
         let network: Network = self.network.parse()?;
 
         Ok(ServerOptions {
+            tcp_idle_timeout: Duration::from_secs(self.tcp_idle_timeout),
+            tcp_max_queries: self.tcp_max_queries,
+            udp_max_payload_size: self.udp_max_payload_size.max(MIN_MESSAGE_SIZE as u16),
             cache_enabled: self.cache_enabled,
             address,
             network,
@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ZoneOptionError {
+    #[error("Missing path of zone master file")]
+    MissingPath,
+}
+
+pub struct ZoneOptions {
+    pub path: PathBuf,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct RawZoneOptions {
+    /// Path to the master file of the zone. The zone apex is the owner of
+    /// the SOA record in this file.
+    pub path: String,
+}
+
+impl RawZoneOptions {
+    pub fn validate(&self) -> Result<ZoneOptions, ZoneOptionError> {
+        if self.path.is_empty() {
+            return Err(ZoneOptionError::MissingPath);
+        }
+
+        Ok(ZoneOptions {
+            path: PathBuf::from(&self.path),
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use portal_proto::ZoneError;
 use portal_resolver::ResolverError;
 use thiserror::Error;
 
@@ -21,6 +22,12 @@ pub enum ServerError {
     #[error("Failed to bind socket ({0})")]
     Bind(String),
 
+    #[error("Zone error: {0}")]
+    ZoneError(#[from] ZoneError),
+
+    #[error("Zone has no SOA record")]
+    MissingSoa,
+
     #[error("IO error")]
     IO(#[from] std::io::Error),
 }
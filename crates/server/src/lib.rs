@@ -1,17 +1,21 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use portal_common::Network;
-use portal_proto::{constants::MIN_MESSAGE_SIZE, udp::Session};
-use portal_resolver::{ForwardingResolver, RecursiveResolver, ResolveMode, Resolver};
+use portal_proto::{constants::MAX_MESSAGE_SIZE, udp::Session};
+use portal_resolver::{
+    ForwardingResolver, IterativeResolver, RecursiveResolver, ResolveMode, Resolver,
+};
 use tokio::{self, net};
 
-use crate::config::Config;
+use crate::{authority::Authority, cache::Cache, config::Config, query::Context};
 
 mod accept;
+mod authority;
 mod cache;
 mod config;
 mod error;
 mod handler;
+mod query;
 mod request;
 mod response;
 mod tcp;
@@ -40,39 +44,81 @@ impl Server {
         }
         self.running = true;
 
-        // Either start the UDP socket or TCP listener
+        let cache = if self.config.resolver.cache_enabled {
+            Some(Cache::new(
+                self.config.resolver.cache_max_entries,
+                Duration::from_secs(self.config.resolver.max_expire as u64),
+            ))
+        } else {
+            None
+        };
+
+        let ctx = Arc::new(Context {
+            authority: Authority::load(&self.config.zones)?,
+            resolver: self.resolver().await?,
+            udp_max_payload_size: self.config.server.udp_max_payload_size,
+            cache,
+        });
+
+        // Either start the UDP socket, the TCP listener or both on the same
+        // address
         match self.config.server.network {
-            Network::Tcp => todo!(),
-            Network::Udp => self.run_udp().await,
+            Network::Tcp => self.run_tcp(ctx).await,
+            Network::Udp => self.run_udp(ctx).await,
+            Network::Both => {
+                tokio::try_join!(self.run_udp(ctx.clone()), self.run_tcp(ctx))?;
+                Ok(())
+            }
         }
     }
 
-    async fn run_udp(&self) -> Result<(), ServerError> {
-        let socket = match net::UdpSocket::bind(self.config.server.address).await {
-            Ok(socket) => socket,
-            Err(err) => return Err(ServerError::Bind(err.to_string())),
-        };
-
-        let resolver: Resolver = match self.config.resolver.mode {
+    async fn resolver(&self) -> Result<Resolver, ServerError> {
+        let resolver = match self.config.resolver.mode {
             ResolveMode::Recursive => {
-                RecursiveResolver::new(self.config.resolver.hint_file_path.clone())
-                    .await?
-                    .into()
+                let hint_file_path = self.config.resolver.hint_file_path.clone();
+                let ip_version = self.config.resolver.ip_version;
+
+                let mut resolver = match &self.config.resolver.trust_anchor_path {
+                    Some(path) => {
+                        RecursiveResolver::new_validating(hint_file_path, path.clone(), ip_version)
+                            .await?
+                    }
+                    None => RecursiveResolver::new(hint_file_path, ip_version).await?,
+                };
+
+                resolver.with_qname_minimisation(self.config.resolver.qname_minimisation);
+                resolver.into()
             }
-            ResolveMode::Iterative => todo!(),
+            ResolveMode::Iterative => IterativeResolver::new(
+                self.config.resolver.hint_file_path.clone(),
+                self.config.resolver.ip_version,
+            )
+            .await?
+            .into(),
             ResolveMode::Forwarding => ForwardingResolver::new(self.config.resolver.upstream)
                 .await?
                 .into(),
         };
 
-        let resolver = Arc::new(resolver);
+        Ok(resolver)
+    }
+
+    async fn run_udp(&self, ctx: Arc<Context<Resolver>>) -> Result<(), ServerError> {
+        let socket = match net::UdpSocket::bind(self.config.server.address).await {
+            Ok(socket) => socket,
+            Err(err) => return Err(ServerError::Bind(err.to_string())),
+        };
+
         let socket = Arc::new(socket);
 
+        // EDNS queries can be larger than 512 octets, so the receive buffer
+        // has to fit the largest possible UDP datagram.
+        let mut buf = vec![0u8; MAX_MESSAGE_SIZE];
+
         loop {
             // Wait until the socket is readable, this can produce a false positive
             socket.readable().await?;
 
-            let mut buf = [0u8; MIN_MESSAGE_SIZE];
             let (len, addr) = match socket.recv_from(&mut buf).await {
                 Ok(result) => result,
                 Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
@@ -87,7 +133,8 @@ impl Server {
                 }
             };
 
-            let resolver = resolver.clone();
+            let ctx = ctx.clone();
+            let query = buf[..len].to_vec();
 
             let session = Session {
                 socket: socket.clone(),
@@ -95,7 +142,36 @@ impl Server {
             };
 
             tokio::spawn(async move {
-                udp::handle(&buf[..len], session, resolver).await;
+                udp::handle(&query, session, ctx).await;
+            });
+        }
+    }
+
+    async fn run_tcp(&self, ctx: Arc<Context<Resolver>>) -> Result<(), ServerError> {
+        let listener = match net::TcpListener::bind(self.config.server.address).await {
+            Ok(listener) => listener,
+            Err(err) => return Err(ServerError::Bind(err.to_string())),
+        };
+
+        let limits = tcp::ConnectionLimits {
+            idle_timeout: self.config.server.tcp_idle_timeout,
+            max_queries: self.config.server.tcp_max_queries,
+        };
+
+        loop {
+            let (stream, addr) = match listener.accept().await {
+                Ok(result) => result,
+                Err(err) => {
+                    // TODO (Techassi): Log this
+                    println!("{err}");
+                    continue;
+                }
+            };
+
+            let ctx = ctx.clone();
+
+            tokio::spawn(async move {
+                tcp::handle(stream, addr, ctx, limits).await;
             });
         }
     }
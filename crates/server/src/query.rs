@@ -0,0 +1,297 @@
+use binbuf::prelude::*;
+use portal_proto::{
+    constants::EDNS_VERSION,
+    edns::{self, OptionCode, OptionData, EDE},
+    Header, Message, Rcode, OPT,
+};
+use portal_resolver::{ResultRecords, Security, ToResolver};
+
+use crate::{
+    accept,
+    authority::Authority,
+    cache::{Cache, CacheKey, CachedAnswer, Negative, Status},
+};
+
+/// The [`Context`] bundles everything needed to answer queries. It is shared
+/// between all UDP and TCP tasks.
+pub struct Context<R: ToResolver> {
+    pub resolver: R,
+
+    /// The zones which are served authoritatively
+    pub authority: Authority,
+
+    /// The response cache. This is `None` if caching is disabled.
+    pub cache: Option<Cache>,
+
+    /// The maximum UDP payload size of responses. This is advertised to
+    /// EDNS clients and caps the payload size advertised by them.
+    pub udp_max_payload_size: u16,
+}
+
+/// The [`Response`] to a query.
+pub struct Response {
+    pub message: Message,
+
+    /// The UDP payload size advertised by the client via EDNS. This is
+    /// `None` if the query didn't include an OPT record.
+    pub udp_payload_size: Option<u16>,
+}
+
+/// Handles a raw DNS query and returns the [`Response`] which should be sent
+/// back to the client. This is independent of the transport (UDP or TCP) the
+/// query was received with. `None` is returned if the query should be
+/// ignored.
+pub async fn handle<R: ToResolver>(buf: &[u8], ctx: &Context<R>) -> Option<Response> {
+    // Create an unpack buffer which keeps track of the offset automatically
+    let mut buf = ReadBuffer::new(buf);
+
+    // Unpack DNS header data
+    let header = match Header::read_be(&mut buf) {
+        Ok(result) => result,
+        Err(err) => {
+            println!("{err}");
+            return None;
+        }
+    };
+
+    // Decide if the server should accept the message. This is done by looking
+    // at some basic DNS header checks.
+    let rcode = match accept::should_accept(&header).await {
+        accept::Action::Accept => None,
+        accept::Action::Reject => Some(Rcode::Refused),
+        accept::Action::Ignore => return None,
+        accept::Action::NoImpl => Some(Rcode::NotImpl),
+    };
+
+    let mut message = match Message::read::<BigEndian>(&mut buf, header) {
+        Ok(msg) => msg,
+        Err(err) => {
+            println!("{err}");
+            return None;
+        }
+    };
+
+    // The OPT record of the query is not part of the response. If the query
+    // used EDNS, the response includes our own OPT record.
+    let udp_payload_size = message.udp_payload_size();
+    let query_opt = message.remove_opt();
+
+    // Queries with an unsupported EDNS version are answered with BADVERS and
+    // no answers. See RFC 6891 Section 6.1.3.
+    let bad_version = query_opt
+        .as_ref()
+        .is_some_and(|opt| opt.header().version() > EDNS_VERSION);
+
+    let ede = match rcode {
+        Some(rcode) => {
+            message.set_rcode(rcode);
+            None
+        }
+        None if bad_version => None,
+        None => handle_accept(&mut message, ctx).await,
+    };
+
+    // Set some response specific values in the message
+    message.set_is_response(true);
+    message.set_rec_avail(true);
+
+    if let Some(query_opt) = &query_opt {
+        let mut opt = OPT::new(ctx.udp_max_payload_size);
+
+        // The DO bit of the query is copied into the response. See RFC 3225
+        // Section 3.
+        opt.header_mut()
+            .set_dnssec_ok(query_opt.header().dnssec_ok());
+
+        if let Some(ede) = ede {
+            opt.add_option(edns::Option::new(OptionCode::EDE, OptionData::EDE(ede)));
+        }
+
+        message.set_opt(opt);
+
+        if bad_version {
+            message.set_extended_rcode(Rcode::BadVers);
+        }
+    }
+
+    Some(Response {
+        message,
+        udp_payload_size,
+    })
+}
+
+/// Answers the accepted query in `message`. Returns an Extended DNS Error
+/// which should be added to the response if the query used EDNS.
+async fn handle_accept<R: ToResolver>(message: &mut Message, ctx: &Context<R>) -> Option<EDE> {
+    // TODO (Techassi): Lookup in filter engine
+
+    // The query was accepted, so there is exactly one question
+    let question = message.question()?;
+    let key = CacheKey::new(&question.name, question.ty, question.class);
+
+    // Names in local zones are answered authoritatively and are neither
+    // cached nor resolved.
+    if let Some(zone) = ctx.authority.find(&question.name) {
+        let mut answer = zone.lookup(&question.name, question.ty);
+
+        message.set_authoritative(answer.authoritative);
+        message.set_rcode(answer.rcode);
+        message.add_answers(&mut answer.answers);
+        message.add_authorities(&mut answer.authorities);
+        message.add_additionals(&mut answer.additionals);
+
+        return None;
+    }
+
+    // Lookup in cache. Misses and expired entries are resolved below, which
+    // then (re)populates the cache.
+    if let Some(cache) = &ctx.cache {
+        match cache.lookup(&key) {
+            (Status::Hit, Some(CachedAnswer::Records(mut records))) => {
+                message.add_answers(&mut records);
+                return None;
+            }
+            (Status::Hit, Some(CachedAnswer::Negative(negative, soa))) => {
+                if negative == Negative::NxDomain {
+                    message.set_rcode(Rcode::NameError);
+                }
+
+                message.add_authority(soa);
+                return None;
+            }
+            _ => {}
+        }
+    }
+
+    // Resolve via resolver
+    let mut records = match ctx.resolver.resolve(message).await {
+        Ok(recs) => recs,
+        Err(err) => {
+            println!("{err}");
+            message.set_rcode(Rcode::ServerFailure);
+            return None;
+        }
+    };
+
+    // Bogus records are neither cached nor returned, the EDE tells the
+    // client why validation failed. See RFC 8914 Section 4.
+    if let Security::Bogus(bogus) = &records.security {
+        message.set_rcode(Rcode::ServerFailure);
+        return Some(EDE::new(bogus.ede_code(), bogus.to_string()));
+    }
+
+    // The compressed write path calculates RDLEN on its own, but writing the
+    // message in canonical form uses the RDLEN stored in the header. Received
+    // RDLENs can be based on compressed names, so normalize them here.
+    records.normalize_rdlens();
+
+    // OPT records are hop-by-hop and are never forwarded. See RFC 6891
+    // Section 6.1.1.
+    records.additionals.retain(|r| !r.is_edns());
+
+    if let Some(cache) = &ctx.cache {
+        insert_cache(cache, key, &records);
+    }
+
+    message.set_rcode(records.rcode);
+    message.add_answers(&mut records.answers);
+    message.add_authorities(&mut records.authorities);
+    message.add_additionals(&mut records.additionals);
+
+    None
+}
+
+/// Inserts the resolved `records` into the cache. Negative answers are only
+/// cached if the response contains the SOA of the zone, as the SOA provides
+/// the TTL of the negative answer. See RFC 2308 Section 5. Other failures,
+/// like SERVFAIL, are never cached.
+fn insert_cache(cache: &Cache, key: CacheKey, records: &ResultRecords) {
+    if !matches!(records.rcode, Rcode::NoError | Rcode::NameError) {
+        return;
+    }
+
+    // The NXDOMAIN refers to the end of the CNAME chain, not to the name in
+    // the query.
+    if records.rcode == Rcode::NameError && !records.answers.is_empty() {
+        return;
+    }
+
+    if !records.is_negative() {
+        cache.insert(key, records.answers.clone());
+        return;
+    }
+
+    let soa = match records.authorities.iter().find(|r| r.is_soa()) {
+        Some(soa) => soa.clone(),
+        None => return,
+    };
+
+    let negative = match records.rcode {
+        Rcode::NameError => Negative::NxDomain,
+        _ => Negative::NoData,
+    };
+
+    cache.insert_negative(key, negative, soa)
+}
+
+#[cfg(test)]
+mod test {
+    use async_trait::async_trait;
+    use portal_proto::{Class, Name, Opcode, Question, RType, ToQuery};
+    use portal_resolver::ResolveResult;
+
+    use super::*;
+
+    /// The queries in these tests are never resolved.
+    struct NoResolver;
+
+    #[async_trait]
+    impl ToResolver for NoResolver {
+        async fn resolve(&self, _message: &Message) -> ResolveResult {
+            unreachable!()
+        }
+
+        async fn resolve_raw<Q: ToQuery>(&self, _query: Q) -> ResolveResult {
+            unreachable!()
+        }
+    }
+
+    fn context() -> Context<NoResolver> {
+        Context {
+            resolver: NoResolver,
+            authority: Authority::default(),
+            cache: None,
+            udp_max_payload_size: 1232,
+        }
+    }
+
+    fn query(dnssec_ok: bool) -> Vec<u8> {
+        let mut header = Header::new(4242);
+        header.opcode = Opcode::Status;
+
+        let mut message = Message::new_with_header(header);
+        message.add_question(Question::new(
+            Name::try_from("example.com").unwrap(),
+            RType::A,
+            Class::IN,
+        ));
+        message.set_dnssec_ok(dnssec_ok);
+
+        let mut buf = WriteBuffer::new();
+        message.write::<BigEndian>(&mut buf).unwrap();
+        buf.owned_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_handle_echoes_dnssec_ok() {
+        let ctx = context();
+
+        let response = handle(&query(true), &ctx).await.unwrap();
+        assert_eq!(response.message.rcode(), Rcode::NotImpl);
+        assert!(response.message.dnssec_ok());
+
+        let response = handle(&query(false), &ctx).await.unwrap();
+        assert!(response.message.opt().is_some());
+        assert!(!response.message.dnssec_ok());
+    }
+}
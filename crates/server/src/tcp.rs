@@ -1,5 +1,363 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
+use binbuf::prelude::*;
+use portal_common::{timeout, TimeoutResult};
 use portal_resolver::ToResolver;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream,
+    },
+    sync::mpsc,
+};
 
-pub async fn handle(buf: &[u8], res: Arc<impl ToResolver>) {}
+use crate::query::{self, Context};
+
+/// [`ConnectionLimits`] describe how long and how much a single TCP client
+/// connection is allowed to be used.
+///
+/// ### See
+///
+/// - <https://datatracker.ietf.org/doc/html/rfc7766#section-6.2>
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionLimits {
+    /// The duration the server waits for the next query on an idle
+    /// connection before closing it.
+    pub idle_timeout: Duration,
+
+    /// The maximum number of queries answered on a single connection. The
+    /// server stops reading once this number is reached, answers all pending
+    /// queries and then closes the connection.
+    pub max_queries: usize,
+}
+
+/// Handles a single TCP client connection. Each DNS message is prefixed with
+/// a two byte length field (RFC 1035 Section 4.2.2). Clients can pipeline
+/// multiple queries on one connection. Each query is resolved in its own task
+/// and the responses are written back as soon as they are ready, which means
+/// they can be sent out of order (RFC 7766 Section 6.2.1.1).
+pub async fn handle(
+    stream: TcpStream,
+    addr: SocketAddr,
+    ctx: Arc<Context<impl ToResolver + Send + Sync + 'static>>,
+    limits: ConnectionLimits,
+) {
+    let (reader, writer) = stream.into_split();
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(limits.max_queries.max(1));
+
+    let writer = tokio::spawn(write_responses(writer, rx));
+
+    if let Err(err) = read_queries(reader, tx, ctx, limits).await {
+        // TODO (Techassi): Log this
+        println!("TCP connection {addr}: {err}");
+    }
+
+    // All senders are dropped at this point. The writer finishes once all
+    // pending responses were written.
+    if let Err(err) = writer.await {
+        println!("TCP connection {addr}: {err}");
+    }
+}
+
+async fn read_queries(
+    mut reader: OwnedReadHalf,
+    tx: mpsc::Sender<Vec<u8>>,
+    ctx: Arc<Context<impl ToResolver + Send + Sync + 'static>>,
+    limits: ConnectionLimits,
+) -> Result<(), std::io::Error> {
+    for _ in 0..limits.max_queries {
+        // Wait for the length prefix of the next message. If the client
+        // doesn't send anything during the idle timeout or closes the
+        // connection, we are done.
+        let len = match timeout(limits.idle_timeout, reader.read_u16()).await {
+            TimeoutResult::Timeout => return Ok(()),
+            TimeoutResult::Error(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Ok(())
+            }
+            TimeoutResult::Error(err) => return Err(err),
+            TimeoutResult::Ok(len) => len as usize,
+        };
+
+        // The client started to send a message, so it has to send the
+        // complete message during the idle timeout.
+        let mut buf = vec![0u8; len];
+        match timeout(limits.idle_timeout, reader.read_exact(&mut buf)).await {
+            TimeoutResult::Timeout => return Ok(()),
+            TimeoutResult::Error(err) => return Err(err),
+            TimeoutResult::Ok(_) => {}
+        }
+
+        let ctx = ctx.clone();
+        let tx = tx.clone();
+
+        tokio::spawn(async move {
+            if let Some(frame) = handle_query(&buf, ctx).await {
+                // The writer is gone if the connection broke down. There is
+                // nothing left to do for us in that case.
+                let _ = tx.send(frame).await;
+            }
+        });
+    }
+
+    Ok(())
+}
+
+async fn handle_query<R: ToResolver>(buf: &[u8], ctx: Arc<Context<R>>) -> Option<Vec<u8>> {
+    let response = query::handle(buf, ctx.as_ref()).await?;
+
+    // Responses via TCP are never truncated, the EDNS payload size only
+    // applies to UDP.
+    let mut buf = WriteBuffer::new();
+    if let Err(err) = response.message.write::<BigEndian>(&mut buf) {
+        println!("{err}");
+        return None;
+    }
+
+    let bytes = buf.bytes();
+    let len = match u16::try_from(bytes.len()) {
+        Ok(len) => len,
+        Err(_) => {
+            println!("TCP response too long ({} octets)", bytes.len());
+            return None;
+        }
+    };
+
+    let mut frame = Vec::with_capacity(bytes.len() + 2);
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(bytes);
+
+    Some(frame)
+}
+
+async fn write_responses(mut writer: OwnedWriteHalf, mut rx: mpsc::Receiver<Vec<u8>>) {
+    while let Some(frame) = rx.recv().await {
+        if let Err(err) = writer.write_all(&frame).await {
+            println!("{err}");
+            return;
+        }
+    }
+
+    let _ = writer.shutdown().await;
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+
+    use async_trait::async_trait;
+    use portal_proto::{
+        Class, Header, Message, Name, Question, RData, RHeader, RType, Record, ToQuery,
+    };
+    use portal_resolver::{ResolveResult, ResultRecords};
+    use tokio::{net::TcpListener, time::sleep};
+
+    use crate::authority::Authority;
+
+    use super::*;
+
+    /// Answers `slow.example.` after a delay and `big.example.` with more
+    /// records than fit into a single TCP message. All other names have no
+    /// records.
+    struct TestResolver;
+
+    #[async_trait]
+    impl ToResolver for TestResolver {
+        async fn resolve(&self, message: &Message) -> ResolveResult {
+            let name = &message.question().unwrap().name;
+            let mut records = ResultRecords::default();
+
+            if *name == Name::try_from("slow.example.").unwrap() {
+                sleep(Duration::from_millis(200)).await;
+            }
+
+            if *name == Name::try_from("big.example.").unwrap() {
+                records.answers = (0..5000u32).map(|i| a(name, i)).collect();
+            }
+
+            Ok(records)
+        }
+
+        async fn resolve_raw<Q: ToQuery>(&self, _query: Q) -> ResolveResult {
+            unreachable!()
+        }
+    }
+
+    fn a(owner: &Name, addr: u32) -> Record {
+        let mut header = RHeader::new();
+        header.set_name(owner.clone());
+        header.set_ty(RType::A);
+        header.set_class(Class::IN);
+        header.set_ttl(3600);
+
+        let mut record = Record::new_with_header(header);
+        record
+            .set_rdata(RData::A(Ipv4Addr::from(addr)))
+            .normalize_rdlen();
+        record
+    }
+
+    /// Serves TCP connections on a loopback listener and returns its
+    /// address.
+    async fn listen(limits: ConnectionLimits) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let ctx = Arc::new(Context {
+            resolver: TestResolver,
+            authority: Authority::default(),
+            cache: None,
+            udp_max_payload_size: 1232,
+        });
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, addr) = listener.accept().await.unwrap();
+                tokio::spawn(handle(stream, addr, ctx.clone(), limits));
+            }
+        });
+
+        addr
+    }
+
+    fn limits() -> ConnectionLimits {
+        ConnectionLimits {
+            idle_timeout: Duration::from_secs(1),
+            max_queries: 16,
+        }
+    }
+
+    /// Returns the length-prefixed query for `name` with the ID `id`.
+    fn frame(id: u16, name: &str) -> Vec<u8> {
+        let mut message = Message::new_with_header(Header::new(id));
+        message.add_question(Question::new(
+            Name::try_from(name).unwrap(),
+            RType::A,
+            Class::IN,
+        ));
+
+        let mut buf = WriteBuffer::new();
+        message.write::<BigEndian>(&mut buf).unwrap();
+
+        let mut frame = (buf.bytes().len() as u16).to_be_bytes().to_vec();
+        frame.extend_from_slice(buf.bytes());
+        frame
+    }
+
+    /// Reads the next response and returns its ID. Returns `None` once the
+    /// server closed the connection.
+    async fn read_id(stream: &mut TcpStream) -> Option<u16> {
+        let len = stream.read_u16().await.ok()?;
+
+        let mut buf = vec![0u8; len as usize];
+        stream.read_exact(&mut buf).await.unwrap();
+
+        let mut buf = ReadBuffer::new(&buf);
+        let header = Header::read_be(&mut buf).unwrap();
+        let message = Message::read::<BigEndian>(&mut buf, header).unwrap();
+
+        Some(message.transaction_id())
+    }
+
+    #[tokio::test]
+    async fn test_tcp_partial_reads() {
+        let addr = listen(limits()).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        // The first query arrives in pieces, split within the length prefix
+        // and within the message. The second one arrives together with the
+        // end of the first.
+        let first = frame(1, "example.");
+        let second = frame(2, "example.");
+
+        stream.write_all(&first[..1]).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        stream.write_all(&first[1..6]).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        stream
+            .write_all(&[&first[6..], second.as_slice()].concat())
+            .await
+            .unwrap();
+
+        let mut ids = vec![
+            read_id(&mut stream).await.unwrap(),
+            read_id(&mut stream).await.unwrap(),
+        ];
+        ids.sort();
+
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_out_of_order() {
+        let addr = listen(limits()).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        let frames = [frame(1, "slow.example."), frame(2, "example.")].concat();
+        stream.write_all(&frames).await.unwrap();
+
+        // The fast query is answered first
+        assert_eq!(read_id(&mut stream).await, Some(2));
+        assert_eq!(read_id(&mut stream).await, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_tcp_idle_timeout() {
+        let addr = listen(ConnectionLimits {
+            idle_timeout: Duration::from_millis(100),
+            max_queries: 16,
+        })
+        .await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        stream.write_all(&frame(1, "example.")).await.unwrap();
+        assert_eq!(read_id(&mut stream).await, Some(1));
+
+        // Without another query the server closes the connection
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(read_id(&mut stream).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_max_queries() {
+        let addr = listen(ConnectionLimits {
+            idle_timeout: Duration::from_secs(1),
+            max_queries: 2,
+        })
+        .await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        let frames = [frame(1, "example."), frame(2, "example.")].concat();
+        stream.write_all(&frames).await.unwrap();
+
+        let mut ids = vec![
+            read_id(&mut stream).await.unwrap(),
+            read_id(&mut stream).await.unwrap(),
+        ];
+        ids.sort();
+        assert_eq!(ids, vec![1, 2]);
+
+        // The server closes the connection after the second query, so the
+        // third one is never answered
+        let _ = stream.write_all(&frame(3, "example.")).await;
+        assert_eq!(read_id(&mut stream).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_oversized_response() {
+        let addr = listen(ConnectionLimits {
+            idle_timeout: Duration::from_millis(300),
+            max_queries: 16,
+        })
+        .await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        let frames = [frame(1, "big.example."), frame(2, "example.")].concat();
+        stream.write_all(&frames).await.unwrap();
+
+        // The response which doesn't fit into a message is dropped, the
+        // connection is still usable.
+        assert_eq!(read_id(&mut stream).await, Some(2));
+        assert_eq!(read_id(&mut stream).await, None);
+    }
+}
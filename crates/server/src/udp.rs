@@ -1,78 +1,37 @@
 use std::sync::Arc;
 
 use binbuf::prelude::*;
-use portal_proto::{udp::Session, Header, Message};
+use portal_proto::{constants::MIN_MESSAGE_SIZE, udp::Session, Message};
 use portal_resolver::ToResolver;
 
-use crate::accept;
+use crate::query::{self, Context};
 
-pub async fn handle(buf: &[u8], session: Session, res: Arc<impl ToResolver>) {
-    // Create an unpack buffer which keeps track of the offset automatically
-    let mut buf = ReadBuffer::new(buf);
-
-    // Unpack DNS header data
-    let header = match Header::read_be(&mut buf) {
-        Ok(result) => result,
-        Err(err) => {
-            println!("{err}");
-            return;
-        }
+/// Handles a single UDP query. The response is truncated to the UDP payload
+/// size advertised by the client, which is capped by the server's maximum
+/// payload size. Clients without EDNS support only accept 512 octets.
+pub async fn handle<R: ToResolver>(buf: &[u8], session: Session, ctx: Arc<Context<R>>) {
+    let response = match query::handle(buf, ctx.as_ref()).await {
+        Some(response) => response,
+        None => return,
     };
 
-    // Decide if the server should accept the message. This is done by looking
-    // at some basic DNS header checks.
-    match accept::should_accept(&header).await {
-        accept::Action::Accept => {
-            let mut message = match Message::read::<BigEndian>(&mut buf, header) {
-                Ok(msg) => msg,
-                Err(err) => {
-                    println!("{err}");
-                    return;
-                }
-            };
-
-            handle_accept(&mut message, session, res).await;
-        }
-        accept::Action::Reject => todo!(),
-        accept::Action::Ignore => todo!(),
-        accept::Action::NoImpl => todo!(),
-    }
-}
-
-async fn handle_accept(message: &mut Message, session: Session, res: Arc<impl ToResolver>) {
-    // TODO (Techassi): Lookup in filter engine
-
-    // TODO (Techassi): Lookup in cache
-
-    // TODO (Techassi): Look for custom DNS records
-
-    // Resolve via resolver
-    let mut records = match res.resolve(message).await {
-        Ok(recs) => recs,
-        Err(err) => {
-            println!("{err}");
-            // TODO (Techassi): Handle error
-            return;
-        }
+    let payload_size = match response.udp_payload_size {
+        Some(size) => size.min(ctx.udp_max_payload_size) as usize,
+        None => MIN_MESSAGE_SIZE,
     };
 
-    // NOTE (Techassi): Is this the best way to do this? We don't care about
-    // the original rdlens, but we should keep them initially if we actually
-    // need them. Normalizing them here seems the correct way until we
-    // support writing back compressed names / messages.
-    records.normalize_rdlens();
-    // message.add_query_result(&mut records);
-    handle_response(message, session).await;
+    handle_response(response.message, payload_size, session).await;
 }
 
-async fn handle_response(message: &mut Message, session: Session) {
-    let mut buf = WriteBuffer::new();
-
-    // Set some response specific values in the message
-    message.set_is_response(true);
-    message.set_rec_avail(true);
+async fn handle_response(mut message: Message, payload_size: usize, session: Session) {
+    // Drop RRsets and set the TC bit if the response is too large. The
+    // client then retries the query via TCP.
+    if let Err(err) = message.truncate::<BigEndian>(payload_size) {
+        println!("{err}");
+        return;
+    }
 
-    // println!("{message:?}");
+    let mut buf = WriteBuffer::new();
 
     if let Err(err) = message.write::<BigEndian>(&mut buf) {
         // TODO (Techassi): Return message with RCODE 2